@@ -56,6 +56,9 @@ struct NodeSocket {
     type_name: BlenderSocketType,
     default: Option<serde_json::Value>,
     is_multi_input: bool,
+    /// Items of a menu socket, when the dump could resolve them.
+    #[serde(default)]
+    enum_items: Option<Vec<EnumItem>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -179,13 +182,12 @@ fn map_blender_type_to_rust(socket_type: &BlenderSocketType) -> TokenStream {
         BlenderSocketType::NodeSocketString | BlenderSocketType::NodeSocketStringFilePath => {
             quote! { crate::core::types::StringType }
         }
-        BlenderSocketType::NodeSocketShader | BlenderSocketType::NodeSocketClosure => {
-            quote! { crate::core::types::Shader }
-        }
+        BlenderSocketType::NodeSocketShader => quote! { crate::core::types::Shader },
         BlenderSocketType::NodeSocketMatrix => quote! { crate::core::types::Matrix },
         BlenderSocketType::NodeSocketRotation => quote! { crate::core::types::Rotation },
         BlenderSocketType::NodeSocketMenu => quote! { crate::core::types::Menu },
         BlenderSocketType::NodeSocketBundle => quote! { crate::core::types::Bundle },
+        BlenderSocketType::NodeSocketClosure => quote! { crate::core::types::Closure },
         BlenderSocketType::NodeSocketVirtual => quote! { crate::core::types::Any }, // seems amorphous
     }
 }
@@ -193,11 +195,13 @@ fn map_blender_type_to_rust(socket_type: &BlenderSocketType) -> TokenStream {
 // code generator body -----------------------------------------------------------------------------
 
 fn generate_inputs(
+    node_id: &str,
     def: &NodeDef,
     sanitizer: &mut NameSanitizer,
-) -> (Vec<TokenStream>, Vec<TokenStream>) {
+) -> (Vec<TokenStream>, Vec<TokenStream>, Vec<TokenStream>) {
     let mut methods = Vec::new();
     let mut constants = Vec::new();
+    let mut menu_enums = Vec::new();
     let mut used_consts = HashSet::new();
 
     for (i, socket) in def.inputs.iter().enumerate() {
@@ -231,6 +235,13 @@ fn generate_inputs(
         let method_name = format_ident!("{}", safe_name);
         let rust_type = map_blender_type_to_rust(&socket.type_name);
 
+        if socket.type_name == BlenderSocketType::NodeSocketMenu
+            && let Some(items) = &socket.enum_items
+            && !items.is_empty()
+        {
+            menu_enums.push(generate_menu_enum(node_id, socket, items));
+        }
+
         if socket.is_multi_input {
             methods.push(quote! {
                 pub fn #method_name(self, val: impl Into<crate::core::types::NodeSocket<#rust_type>>) -> Self {
@@ -250,7 +261,7 @@ fn generate_inputs(
         }
     }
 
-    (methods, constants)
+    (methods, constants, menu_enums)
 }
 
 fn generate_outputs(
@@ -268,7 +279,7 @@ fn generate_outputs(
         let method_default = format_ident!("{}", default_name);
         defaults.push(quote! {
             pub fn #method_default(self, val: impl Into<crate::core::types::NodeSocket<#rust_type>>) -> Self {
-                crate::core::context::update_output_default(&self.name, #i, val.into().python_expr());
+                crate::core::context::update_output_default(&self.name, #i, #socket_name, val.into());
                 self
             }
         });
@@ -299,9 +310,53 @@ fn generate_enum_property(
         prop.identifier.to_pascal_case()
     );
     let enum_ident = format_ident!("{}", enum_name_str);
+    let enum_def = generate_enum_def(&enum_name_str, items);
+
+    let prop_id = &prop.identifier;
+    let method_str_name = format_ident!("{}_str", method_name);
+    let method_def = quote! {
+        pub fn #method_name(self, val: #enum_ident) -> Self {
+            crate::core::context::update_property(&self.name, #prop_id, crate::core::types::python_string_literal(val.as_str()));
+            self
+        }
+        /// String form of the enum setter, taking the Blender identifier. Panics on unknown values.
+        pub fn #method_str_name(self, val: &str) -> Self {
+            let parsed: #enum_ident = val.parse().unwrap_or_else(|e: String| panic!("{}", e));
+            self.#method_name(parsed)
+        }
+    };
+
+    (method_def, enum_def)
+}
+
+/// Enum for a menu socket with known items. Converts into `NodeSocket<Menu>`, so the regular
+/// `with_*` setter of the socket accepts it next to plain strings.
+fn generate_menu_enum(node_id: &str, socket: &NodeSocket, items: &[EnumItem]) -> TokenStream {
+    // "Menu" suffix keeps it apart from a property enum of the same name (e.g. `mode`).
+    let enum_name_str = format!(
+        "{}{}Menu",
+        node_id.to_pascal_case(),
+        socket.identifier.to_pascal_case()
+    );
+    let enum_ident = format_ident!("{}", enum_name_str);
+    let enum_def = generate_enum_def(&enum_name_str, items);
+
+    quote! {
+        #enum_def
+        impl From<#enum_ident> for crate::core::types::NodeSocket<crate::core::types::Menu> {
+            fn from(val: #enum_ident) -> Self {
+                Self::from(val.as_str())
+            }
+        }
+    }
+}
+
+fn generate_enum_def(enum_name_str: &str, items: &[EnumItem]) -> TokenStream {
+    let enum_ident = format_ident!("{}", enum_name_str);
 
     let mut variants = Vec::new();
     let mut match_arms = Vec::new();
+    let mut parse_arms = Vec::new();
 
     let mut enum_sanitizer = NameSanitizer::new();
 
@@ -324,9 +379,12 @@ fn generate_enum_property(
 
         variants.push(quote! { #variant_ident });
         match_arms.push(quote! { Self::#variant_ident => #item_id });
+        parse_arms.push(quote! { #item_id => Ok(Self::#variant_ident) });
     }
+    let item_ids: Vec<&str> = items.iter().map(|i| i.identifier.as_str()).collect();
+    let expected = item_ids.join(", ");
 
-    let enum_def = quote! {
+    quote! {
         #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
         pub enum #enum_ident {
             #(#variants),*
@@ -343,17 +401,17 @@ fn generate_enum_property(
                 f.write_str(self.as_str())
             }
         }
-    };
-
-    let prop_id = &prop.identifier;
-    let method_def = quote! {
-        pub fn #method_name(self, val: #enum_ident) -> Self {
-            crate::core::context::update_property(&self.name, #prop_id, crate::core::types::python_string_literal(val.as_str()));
-            self
+        impl std::str::FromStr for #enum_ident {
+            type Err = String;
+            /// Parses the Blender identifier (e.g. "ADD"), not the Rust variant name.
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    #(#parse_arms,)*
+                    _ => Err(format!("unknown {} '{}', expected one of: {}", #enum_name_str, s, #expected)),
+                }
+            }
         }
-    };
-
-    (method_def, enum_def)
+    }
 }
 
 fn generate_properties(
@@ -396,12 +454,14 @@ fn generate_node_struct(node_id: &str, def: &NodeDef) -> TokenStream {
 
     let mut sanitizer = NameSanitizer::new();
 
-    let (input_methods, input_constants) = generate_inputs(def, &mut sanitizer);
+    let (input_methods, input_constants, menu_enums) =
+        generate_inputs(node_id, def, &mut sanitizer);
     let (output_defaults, output_getters) = generate_outputs(def, &mut sanitizer);
     let (property_methods, property_enums) = generate_properties(node_id, def, &mut sanitizer);
 
     quote! {
         #(#property_enums)*
+        #(#menu_enums)*
 
         #[derive(Clone, Debug)]
         pub struct #struct_name { pub name: String }
@@ -421,18 +481,77 @@ fn generate_node_struct(node_id: &str, def: &NodeDef) -> TokenStream {
             #(#output_getters)*
             #(#property_methods)*
 
+            /// Sets input `index` to a socket of any type, unchecked. Prefer the typed `with_*`
+            /// setters, or `set_input_checked` where pins are addressed by index.
             pub fn set_input<T>(self, index: usize, val: crate::core::types::NodeSocket<T>) -> Self {
                 crate::core::context::update_input(&self.name, index, val.python_expr(), val.is_literal);
                 self
             }
+            /// `set_input` for typed sockets, reporting a build error if input `index` can't
+            /// take a `T` (see `context::check_input_type`).
+            pub fn set_input_checked<T: crate::core::types::SocketDef>(self, index: usize, val: crate::core::types::NodeSocket<T>) -> Self {
+                crate::core::context::check_input_type(&self.name, index, T::blender_socket_type());
+                self.set_input(index, val)
+            }
             pub fn append_input<T>(self, index: usize, val: crate::core::types::NodeSocket<T>) -> Self {
                 crate::core::context::append_input(&self.name, index, val.python_expr(), val.is_literal);
                 self
             }
+            /// Sets the default of output `index` by position, like `set_input` does for inputs.
+            pub fn set_output_default<T>(self, index: usize, val: crate::core::types::NodeSocket<T>) -> Self {
+                crate::core::context::update_output_default(&self.name, index, &index.to_string(), val);
+                self
+            }
         }
     }
 }
 
+// node index -------------------------------------------------------------------------------------
+fn socket_index_entries(sockets: &[NodeSocket]) -> Vec<serde_json::Value> {
+    sockets
+        .iter()
+        .map(|socket| {
+            serde_json::json!({
+                "name": socket.name,
+                "identifier": socket.identifier,
+                "socket_type": format!("{:?}", socket.type_name),
+            })
+        })
+        .collect()
+}
+
+/// One `nodes_index.json` entry: what a user needs to find a node and its pins by name.
+fn node_index_entry(node_id: &str, categories: &[&str], def: &NodeDef) -> serde_json::Value {
+    let properties: Vec<_> = def
+        .properties
+        .iter()
+        .map(|prop| {
+            let items: Vec<&str> = prop
+                .enum_items
+                .iter()
+                .flatten()
+                .map(|item| item.identifier.as_str())
+                .collect();
+            serde_json::json!({
+                "identifier": prop.identifier,
+                "name": prop.name,
+                "property_type": prop.type_name,
+                "enum_items": items,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "struct_name": node_id.to_pascal_case(),
+        "bl_idname": def.bl_idname,
+        "bl_label": def.bl_label,
+        "categories": categories,
+        "inputs": socket_index_entries(&def.inputs),
+        "outputs": socket_index_entries(&def.outputs),
+        "properties": properties,
+    })
+}
+
 // main ===================================
 
 fn main() {
@@ -449,6 +568,7 @@ fn main() {
 
     let debug_mode = env::var("RAMEN_DEBUG_NODES").is_ok();
     let mut unique_nodes = HashMap::new();
+    let mut node_categories: HashMap<String, Vec<&str>> = HashMap::new();
     for (category, nodes) in [
         ("GeometryNodes", dump.GeometryNodes),
         ("ShaderNodes", dump.ShaderNodes),
@@ -463,6 +583,10 @@ fn main() {
                     key, category
                 );
             }
+            node_categories
+                .entry(key.clone())
+                .or_default()
+                .push(category);
             unique_nodes.insert(key, def);
         }
     }
@@ -471,6 +595,7 @@ fn main() {
     let mut sorted_keys: Vec<_> = unique_nodes.keys().collect();
     sorted_keys.sort();
     let mut seen_struct_names = HashSet::new();
+    let mut index = Vec::new();
 
     for key in sorted_keys {
         let struct_name_str = key.to_pascal_case();
@@ -482,7 +607,9 @@ fn main() {
             );
         }
         seen_struct_names.insert(struct_name_str);
-        structs.push(generate_node_struct(key, &unique_nodes[key]));
+        let def = &unique_nodes[key];
+        structs.push(generate_node_struct(key, def));
+        index.push(node_index_entry(key, &node_categories[key], def));
     }
 
     let out_dir = env::var_os("OUT_DIR").unwrap();
@@ -490,4 +617,7 @@ fn main() {
 
     let raw_code = quote! { #(#structs)* }.to_string();
     fs::write(&dest_path, raw_code).unwrap();
+
+    let index_path = Path::new(&out_dir).join("nodes_index.json");
+    fs::write(&index_path, serde_json::to_string(&index).unwrap()).unwrap();
 }
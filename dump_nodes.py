@@ -1,11 +1,18 @@
+import ast
 import bpy
 import json
 import os
 import mathutils
+import re
 import sys
 
 OUTPUT_FILE = "blender_nodes_dump.json"
 
+# Assigned to menu sockets to make Blender list their items (see `get_menu_items`).
+MENU_PROBE_VALUE = "__ramen_menu_probe__"
+# e.g. `enum "__ramen_menu_probe__" not found in ('Evaluated', 'Count', 'Length')`
+ENUM_NOT_FOUND = re.compile(r"not found in (\(.*\))")
+
 CANDIDATE_PREFIXES = [
     "GeometryNode",
     "ShaderNode",
@@ -86,9 +93,46 @@ def get_socket_info(socket):
             info["default"] = safe_convert(socket.default_value)
         except Exception:
             info["default"] = None
+    if socket.bl_idname == "NodeSocketMenu" and not socket.is_output:
+        items = get_menu_items(socket)
+        if items:
+            info["enum_items"] = items
     return info
 
 
+def get_menu_items(socket):
+    """
+    Items of a menu socket, read from the socket of a node instance.
+
+    Menu items are generated per node at runtime, so the type-level RNA
+    (`bl_rna.properties["default_value"].enum_items`) lists none of them. Assigning an
+    unknown value makes Blender evaluate this socket's items and name them in the
+    error, which is where the Python API exposes them.
+    """
+    label = f"{socket.node.bl_idname}.inputs[{socket.identifier!r}]"
+    try:
+        socket.default_value = MENU_PROBE_VALUE
+    except TypeError as e:
+        match = ENUM_NOT_FOUND.search(str(e))
+        if match is None:
+            print(
+                f"  Warning: could not read menu items of {label}: {e}", file=sys.stderr
+            )
+            return []
+        identifiers = ast.literal_eval(match.group(1))
+        if not identifiers:
+            print(f"  Warning: menu {label} has no items", file=sys.stderr)
+        return [
+            {"identifier": identifier, "name": identifier, "description": ""}
+            for identifier in identifiers
+        ]
+    print(
+        f"  Warning: menu {label} accepted {MENU_PROBE_VALUE!r}, items unknown",
+        file=sys.stderr,
+    )
+    return []
+
+
 def get_properties_info(node):
     props = []
     if not hasattr(node, "bl_rna"):
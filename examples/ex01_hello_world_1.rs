@@ -64,6 +64,7 @@ fn main() {
             // Rule of thumb: Always use raw physical indices (0, 1, 2...) for Group Input/Output nodes,
             // corresponding to the exact order the sockets were registered in the tree's interface.
             // Do not rely on auto-generated `PIN_*` constants for these dynamic nodes.
+            // Indices past the declared sockets are reported when the tree is built.
             NodeGroupOutput::new().set_input(0, set_mat.out_geometry());
         })
         .add_compositor_tree(COMP_NAME, || {
@@ -1,12 +1,12 @@
+use blender_ramen::core::inputs;
 use blender_ramen::core::nodes::{
     GeometryNodeBlurAttribute, GeometryNodeBlurAttributeDataType, GeometryNodeCurvePrimitiveCircle,
-    GeometryNodeCurveToMesh, GeometryNodeInputIndex, GeometryNodeInputNormal,
-    GeometryNodeInputPosition, GeometryNodeResampleCurve, GeometryNodeSetMaterial,
+    GeometryNodeCurveToMesh, GeometryNodeResampleCurve, GeometryNodeSetMaterial,
     GeometryNodeSetPosition, GeometryNodeTransform, NodeGroupOutput, ShaderNodeBsdfPrincipled,
     ShaderNodeCombineXyz, ShaderNodeOutputMaterial, ShaderNodeTexNoise,
 };
 use blender_ramen::core::project::BlenderProject;
-use blender_ramen::core::types::{Color, Float, Geo, NodeSocket, Vector};
+use blender_ramen::core::types::{Color, NodeSocket, Vector};
 use blender_ramen::core::zone::repeat_zone;
 use ramen_macros::ramen_math;
 
@@ -36,12 +36,11 @@ fn main() {
             let initial_geo = GeometryNodeCurvePrimitiveCircle::new()
                 .with_radius(INITIAL_RADIUS)
                 .with_resolution(64)
-                .out_curve()
-                .cast::<Geo>();
+                .out_curve();
 
             let (final_geo,) = repeat_zone(ITERATIONS, (initial_geo,), |(geo,)| {
                 // Growth
-                let normal = GeometryNodeInputNormal::new().out_normal();
+                let normal = inputs::normal();
                 let noise = ShaderNodeTexNoise::new().with_scale(1.0);
                 let noise_fac = noise.out_factor();
 
@@ -57,11 +56,10 @@ fn main() {
                     .with_curve(grown_geo)
                     .with_mode("Length")
                     .with_length(RESAMPLE_LENGTH)
-                    .out_curve()
-                    .cast::<Geo>();
+                    .out_curve();
 
                 // Blur
-                let pos = GeometryNodeInputPosition::new().out_position();
+                let pos = inputs::position();
                 let blurred_pos = GeometryNodeBlurAttribute::new()
                     .with_data_type(GeometryNodeBlurAttributeDataType::FloatVector)
                     .set_input(GeometryNodeBlurAttribute::PIN_VALUE, pos)
@@ -76,7 +74,7 @@ fn main() {
                     .out_geometry();
 
                 // Flatten
-                let current_pos = GeometryNodeInputPosition::new().out_position();
+                let current_pos = inputs::position();
                 let flat_pos =
                     ramen_math!(current_pos * NodeSocket::<Vector>::from((1.0, 1.0, 0.0)));
                 let flattened = GeometryNodeSetPosition::new()
@@ -88,10 +86,10 @@ fn main() {
             });
 
             // Post-Process
-            let index = GeometryNodeInputIndex::new().out_index();
+            let index = inputs::index();
 
             // Wave
-            let noise_coord_x = ramen_math!(NodeSocket::cast::<Float>(index) * Z_FREQUENCY);
+            let noise_coord_x = index.scaled(Z_FREQUENCY);
             let noise_coord = ShaderNodeCombineXyz::new()
                 .with_x(noise_coord_x)
                 .out_vector();
@@ -1,13 +1,13 @@
 use blender_ramen::core::nodes::{
     CompositorNodeGlare, CompositorNodeLensdist, CompositorNodeRLayers, CompositorNodeViewer,
-    GeometryNodeInputPosition, GeometryNodeSetMaterial, GeometryNodeVolumeCube,
-    GeometryNodeVolumeToMesh, NodeGroupInput, NodeGroupOutput, ShaderNodeAddShader,
-    ShaderNodeAmbientOcclusion, ShaderNodeBsdfDiffuse, ShaderNodeEmission,
+    GeometryNodeInputPosition, GeometryNodeSetMaterial, NodeGroupInput, NodeGroupOutput,
+    ShaderNodeAddShader, ShaderNodeAmbientOcclusion, ShaderNodeBsdfDiffuse, ShaderNodeEmission,
     ShaderNodeOutputMaterial, ShaderNodeSeparateXyz,
 };
 use blender_ramen::core::project::BlenderProject;
 use blender_ramen::core::tree::{NodeTree, call_geometry_group};
 use blender_ramen::core::types::{Float, GeometryNodeGroupExt, NodeGroupInputExt, NodeSocket};
+use blender_ramen::core::volume::{self, MeshResolution, Resolution};
 use blender_ramen::core::zone::repeat_zone;
 use ramen_macros::ramen_math;
 
@@ -83,7 +83,7 @@ fn main() {
 
             let r_pow = ramen_math!(pow(r, p));
             let theta_p = ramen_math!(atan2(y, x) * p);
-            let phi_p = ramen_math!(asin(z / (r + 0.000001)) * p);
+            let phi_p = ramen_math!(asin(z.safe_div(r)) * p);
 
             let out_x = ramen_math!(r_pow * cos(phi_p) * cos(theta_p) + cx);
             let out_y = ramen_math!(r_pow * cos(phi_p) * sin(theta_p) + cy);
@@ -158,20 +158,18 @@ fn main() {
             let sdf = ramen_math!(0.5 / log(r_final, std::f32::consts::E) * r_final / final_dr);
             let density = ramen_math!(-sdf);
 
-            let volume_cube = GeometryNodeVolumeCube::new()
-                .with_resolution_x(RESOLUTION)
-                .with_resolution_y(RESOLUTION)
-                .with_resolution_z(RESOLUTION)
-                .with_min((-BOUND_EXTENT, -BOUND_EXTENT, -BOUND_EXTENT))
-                .with_max((BOUND_EXTENT, BOUND_EXTENT, BOUND_EXTENT))
-                .set_input(GeometryNodeVolumeCube::PIN_DENSITY, density);
-
-            let to_mesh = GeometryNodeVolumeToMesh::new()
-                .with_volume(volume_cube.out_volume())
-                .with_threshold(THRESHOLD);
+            let grid = volume::cube(
+                density,
+                (
+                    (-BOUND_EXTENT, -BOUND_EXTENT, -BOUND_EXTENT),
+                    (BOUND_EXTENT, BOUND_EXTENT, BOUND_EXTENT),
+                ),
+                Resolution::Uniform(RESOLUTION),
+            );
+            let mesh = volume::to_mesh(grid, THRESHOLD, MeshResolution::Grid);
 
             let set_mat = GeometryNodeSetMaterial::new()
-                .with_geometry(to_mesh.out_mesh())
+                .with_geometry(mesh)
                 .with_material(MAT_NAME);
 
             NodeGroupOutput::new().set_input(0, set_mat.out_geometry());
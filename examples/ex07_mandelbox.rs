@@ -1,13 +1,13 @@
 use blender_ramen::core::nodes::{
     CompositorNodeGlare, CompositorNodeLensdist, CompositorNodeRLayers, CompositorNodeViewer,
-    GeometryNodeInputPosition, GeometryNodeSetMaterial, GeometryNodeVolumeCube,
-    GeometryNodeVolumeToMesh, NodeGroupInput, NodeGroupOutput, ShaderNodeAddShader,
-    ShaderNodeAmbientOcclusion, ShaderNodeBsdfDiffuse, ShaderNodeEmission,
+    GeometryNodeInputPosition, GeometryNodeSetMaterial, NodeGroupInput, NodeGroupOutput,
+    ShaderNodeAddShader, ShaderNodeAmbientOcclusion, ShaderNodeBsdfDiffuse, ShaderNodeEmission,
     ShaderNodeOutputMaterial, ShaderNodeSeparateXyz,
 };
 use blender_ramen::core::project::BlenderProject;
 use blender_ramen::core::tree::{NodeTree, call_geometry_group};
 use blender_ramen::core::types::{Float, GeometryNodeGroupExt, NodeGroupInputExt, NodeSocket};
+use blender_ramen::core::volume::{self, MeshResolution, Resolution};
 use blender_ramen::core::zone::repeat_zone;
 use ramen_macros::ramen_math;
 
@@ -79,13 +79,13 @@ fn main() {
             let min_r2 = NodeSocket::<Float>::from(MIN_RADIUS * MIN_RADIUS);
 
             // box (clamp) fold
-            let bx = ramen_math!(min(max(x, -1.0), 1.0) * 2.0 - x);
-            let by = ramen_math!(min(max(y, -1.0), 1.0) * 2.0 - y);
-            let bz = ramen_math!(min(max(z, -1.0), 1.0) * 2.0 - z);
+            let bx = ramen_math!(x.clamp(-1.0, 1.0) * 2.0 - x);
+            let by = ramen_math!(y.clamp(-1.0, 1.0) * 2.0 - y);
+            let bz = ramen_math!(z.clamp(-1.0, 1.0) * 2.0 - z);
 
             // sphere fold
             let r2 = ramen_math!(pow(bx, 2.0) + pow(by, 2.0) + pow(bz, 2.0));
-            let fold_mult = ramen_math!(fixed_r2 / min(max(r2, min_r2), fixed_r2));
+            let fold_mult = ramen_math!(fixed_r2 / r2.clamp(min_r2, fixed_r2));
 
             // scaling / calculate offset
             let final_mult = ramen_math!(fold_mult * scale);
@@ -158,20 +158,18 @@ fn main() {
 
             let density = ramen_math!(6.0 - r_final);
 
-            let volume_cube = GeometryNodeVolumeCube::new()
-                .with_resolution_x(RESOLUTION)
-                .with_resolution_y(RESOLUTION)
-                .with_resolution_z(RESOLUTION)
-                .with_min((-BOUND_EXTENT, -BOUND_EXTENT, -BOUND_EXTENT))
-                .with_max((BOUND_EXTENT, BOUND_EXTENT, BOUND_EXTENT))
-                .set_input(GeometryNodeVolumeCube::PIN_DENSITY, density);
-
-            let to_mesh = GeometryNodeVolumeToMesh::new()
-                .with_volume(volume_cube.out_volume())
-                .with_threshold(THRESHOLD);
+            let grid = volume::cube(
+                density,
+                (
+                    (-BOUND_EXTENT, -BOUND_EXTENT, -BOUND_EXTENT),
+                    (BOUND_EXTENT, BOUND_EXTENT, BOUND_EXTENT),
+                ),
+                Resolution::Uniform(RESOLUTION),
+            );
+            let mesh = volume::to_mesh(grid, THRESHOLD, MeshResolution::Grid);
 
             let set_mat = GeometryNodeSetMaterial::new()
-                .with_geometry(to_mesh.out_mesh())
+                .with_geometry(mesh)
                 .with_material(MAT_NAME);
 
             NodeGroupOutput::new().set_input(0, set_mat.out_geometry());
@@ -8,7 +8,7 @@ use blender_ramen::core::nodes::{
     ShaderNodeOutputMaterial, ShaderNodeSeparateXyz,
 };
 use blender_ramen::core::project::BlenderProject;
-use blender_ramen::core::types::{Geo, NodeSocket, Vector};
+use blender_ramen::core::types::{NodeSocket, Vector};
 use blender_ramen::core::zone::repeat_zone;
 use ramen_macros::ramen_math;
 
@@ -82,8 +82,7 @@ fn main() {
             let initial_geo = GeometryNodeCurvePrimitiveLine::new()
                 .with_start(NodeSocket::<Vector>::from(INITIAL_POS))
                 .with_end(NodeSocket::<Vector>::from(INITIAL_POS))
-                .out_curve()
-                .cast::<Geo>();
+                .out_curve();
 
             let (_final_pos, final_geo) =
                 repeat_zone(ITERATIONS, (initial_pos, initial_geo), |(pos, geo)| {
@@ -110,7 +109,7 @@ fn main() {
 
                     let joined = GeometryNodeJoinGeometry::new()
                         .append_geometry(geo)
-                        .append_geometry(segment.out_curve().cast::<Geo>())
+                        .append_geometry(segment.out_curve())
                         .out_geometry();
                     (next_pos, joined)
                 });
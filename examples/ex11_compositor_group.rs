@@ -0,0 +1,71 @@
+use blender_ramen::core::nodes::{
+    CompositorNodeGlare, CompositorNodeLensdist, CompositorNodeRLayers, CompositorNodeViewer,
+    NodeGroupInput, NodeGroupOutput,
+};
+use blender_ramen::core::project::BlenderProject;
+use blender_ramen::core::tree::{NodeTree, call_compositor_group};
+use blender_ramen::core::types::{Color, CompositorNodeGroupExt, Float, NodeGroupInputExt};
+
+// ==========================================
+// Params
+// ==========================================
+const GLARE_FADE: f32 = 0.8;
+const SUBTLE_DISPERSION: f32 = 0.02;
+const STRONG_DISPERSION: f32 = 0.12;
+
+// ==========================================
+// Names
+// ==========================================
+const LOOK_NAME: &str = "CinematicLook";
+const SUBTLE_COMP: &str = "SubtleComp";
+const STRONG_COMP: &str = "StrongComp";
+
+mod look_sockets {
+    pub const IN_IMAGE: usize = 0;
+    pub const IN_DISPERSION: usize = 1;
+}
+
+/// Render layers through the shared look, to the compositor output and the viewer.
+fn graded_render(dispersion: f32) {
+    let render_layers = CompositorNodeRLayers::new();
+    let look = call_compositor_group(LOOK_NAME)
+        .set_input(look_sockets::IN_IMAGE, render_layers.out_image())
+        .set_input(look_sockets::IN_DISPERSION, dispersion.into());
+    let image = look.out_socket::<Color>("Image");
+
+    NodeGroupOutput::new().set_input(0, image);
+    CompositorNodeViewer::new().set_input(CompositorNodeViewer::PIN_IMAGE, image);
+}
+
+fn main() {
+    // The look is defined once...
+    let look = NodeTree::new_compositor_group(LOOK_NAME)
+        .with_input::<Color>("Image")
+        .with_input::<Float>("Dispersion")
+        .with_output::<Color>("Image")
+        .build(|| {
+            let group_in = NodeGroupInput::new();
+
+            let glare = CompositorNodeGlare::new().with_fade(GLARE_FADE).set_input(
+                CompositorNodeGlare::PIN_IMAGE,
+                group_in.socket::<Color>("Image"),
+            );
+
+            let lens_dist = CompositorNodeLensdist::new()
+                .set_input(CompositorNodeLensdist::PIN_IMAGE, glare.out_image())
+                .set_input(
+                    CompositorNodeLensdist::PIN_DISPERSION,
+                    group_in.socket::<Float>("Dispersion"),
+                );
+
+            NodeGroupOutput::new().set_input(0, lens_dist.out_image());
+        });
+
+    // ...and called from two compositor trees. The scene uses the last one added;
+    // switch between them in the compositor's node group selector.
+    BlenderProject::new()
+        .add_subtree(LOOK_NAME, &look)
+        .add_compositor_tree(SUBTLE_COMP, || graded_render(SUBTLE_DISPERSION))
+        .add_compositor_tree(STRONG_COMP, || graded_render(STRONG_DISPERSION))
+        .send();
+}
@@ -0,0 +1,38 @@
+//! Resends the Mandelbulb whenever its parameter file is saved.
+//!
+//! ```text
+//! cargo run --example ex12_live_reload --features watch
+//! ```
+//!
+//! Then edit `mandelbulb.params` (the `ramen-examples` flags, e.g. `--power 6`) and save.
+
+use blender_ramen::core::examples;
+use blender_ramen::core::watch;
+use std::fs;
+use std::time::Duration;
+
+// ==========================================
+// Params
+// ==========================================
+const PARAMS_FILE: &str = "mandelbulb.params";
+const DEFAULT_PARAMS: &str = "--power 8 --resolution 256\n";
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+fn main() {
+    if fs::metadata(PARAMS_FILE).is_err() {
+        fs::write(PARAMS_FILE, DEFAULT_PARAMS).expect("failed to create the parameter file");
+    }
+    let mandelbulb = examples::find("mandelbulb").unwrap();
+
+    let result = watch::watch(&[PARAMS_FILE], DEBOUNCE, || {
+        let params = fs::read_to_string(PARAMS_FILE).unwrap_or_default();
+        let args: Vec<String> = params.split_whitespace().map(str::to_string).collect();
+        // A bad edit panics; the watch reports it and waits for the next save.
+        mandelbulb
+            .build(&args)
+            .unwrap_or_else(|err| panic!("{}: {}", PARAMS_FILE, err))
+    });
+    if let Err(err) = result {
+        eprintln!("❌ Could not watch {}: {}", PARAMS_FILE, err);
+    }
+}
@@ -1,49 +1,307 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::fold::Fold;
-use syn::{Expr, parse_macro_input};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Expr, Ident, LitStr, Token, Type, Visibility, braced, parse_macro_input};
 
-/// Maps a Rust identifier to a Blender `ShaderNodeMath` enum variant (PascalCase)
+mod edit_distance;
+use edit_distance::edit_distance;
+
+/// Rust function names mapped to a Blender `ShaderNodeMath` enum variant (PascalCase)
 /// and the expected number of arguments.
+// TODO: Implement a checking system. (e.g. compile-time checker that verifies every mapped variant string matches the enum names)
+const MATH_FUNCTIONS: &[(&str, &str, usize)] = &[
+    ("sin", "Sine", 1),
+    ("cos", "Cosine", 1),
+    ("tan", "Tangent", 1),
+    ("asin", "Arcsine", 1),
+    ("acos", "Arccosine", 1),
+    ("atan", "Arctangent", 1),
+    ("sinh", "Sinh", 1),
+    ("cosh", "Cosh", 1),
+    ("tanh", "Tanh", 1),
+    ("sqrt", "Sqrt", 1),
+    ("exp", "Exponent", 1),
+    ("round", "Round", 1),
+    ("floor", "Floor", 1),
+    ("ceil", "Ceil", 1),
+    ("trunc", "Trunc", 1),
+    ("fract", "Fract", 1),
+    ("abs", "Absolute", 1),
+    ("sign", "Sign", 1),
+    ("radians", "Radians", 1),
+    ("degrees", "Degrees", 1),
+    ("log", "Logarithm", 2),
+    ("atan2", "Arctan2", 2),
+    ("pow", "Power", 2),
+    ("modulo", "Modulo", 2),
+    ("fmod", "Modulo", 2),
+    ("mod_floor", "FlooredModulo", 2),
+    ("min", "Minimum", 2),
+    ("max", "Maximum", 2),
+    ("snap", "Snap", 2),
+    ("pingpong", "Pingpong", 2),
+    ("wrap", "Wrap", 3),
+    ("smooth_min", "SmoothMin", 3),
+    ("smooth_max", "SmoothMax", 3),
+    ("compare", "Compare", 3),
+    ("multiply_add", "MultiplyAdd", 3),
+];
+
+/// Functions that expand to more than one node, with their expected number of arguments.
+const COMPOSITE_FUNCTIONS: &[(&str, usize)] = &[
+    ("div_floor", 2),
+    ("sin_deg", 1),
+    ("cos_deg", 1),
+    ("turns", 1),
+    ("ease_in", 1),
+    ("ease_out", 1),
+    ("ease_in_out", 1),
+    ("smootherstep", 1),
+    ("in_range", 3),
+    ("outside", 3),
+];
+
 fn get_blender_math_op(name: &str) -> Option<(&'static str, usize)> {
-    match name {
-        // TODO: Implement a checking system. (e.g. compile-time checker that verifies every mapped variant string matches the enum names)
-        "sin" => Some(("Sine", 1)),
-        "cos" => Some(("Cosine", 1)),
-        "tan" => Some(("Tangent", 1)),
-        "asin" => Some(("Arcsine", 1)),
-        "acos" => Some(("Arccosine", 1)),
-        "atan" => Some(("Arctangent", 1)),
-        "sinh" => Some(("Sinh", 1)),
-        "cosh" => Some(("Cosh", 1)),
-        "tanh" => Some(("Tanh", 1)),
-        "sqrt" => Some(("Sqrt", 1)),
-        "exp" => Some(("Exponent", 1)),
-        "round" => Some(("Round", 1)),
-        "floor" => Some(("Floor", 1)),
-        "ceil" => Some(("Ceil", 1)),
-        "trunc" => Some(("Trunc", 1)),
-        "fract" => Some(("Fract", 1)),
-        "abs" => Some(("Absolute", 1)),
-        "sign" => Some(("Sign", 1)),
-        "radians" => Some(("Radians", 1)),
-        "degrees" => Some(("Degrees", 1)),
-
-        "log" => Some(("Logarithm", 2)),
-        "atan2" => Some(("Arctan2", 2)),
-        "pow" => Some(("Power", 2)),
-        "modulo" => Some(("Modulo", 2)),
-        "min" => Some(("Minimum", 2)),
-        "max" => Some(("Maximum", 2)),
-        "snap" => Some(("Snap", 2)),
-        "pingpong" => Some(("Pingpong", 2)),
-
-        "wrap" => Some(("Wrap", 3)),
-        "smooth_min" => Some(("SmoothMin", 3)),
-        "smooth_max" => Some(("SmoothMax", 3)),
-        "compare" => Some(("Compare", 3)),
-        "multiply_add" => Some(("MultiplyAdd", 3)),
+    MATH_FUNCTIONS
+        .iter()
+        .find(|(rust_name, _, _)| *rust_name == name)
+        .map(|&(_, variant, args)| (variant, args))
+}
+
+fn get_composite_op(name: &str) -> Option<usize> {
+    COMPOSITE_FUNCTIONS
+        .iter()
+        .find(|(rust_name, _)| *rust_name == name)
+        .map(|&(_, args)| args)
+}
+
+/// Names of every function `ramen_math!` knows, in table order.
+fn math_function_names() -> Vec<&'static str> {
+    MATH_FUNCTIONS
+        .iter()
+        .map(|(name, _, _)| *name)
+        .chain(COMPOSITE_FUNCTIONS.iter().map(|(name, _)| *name))
+        .collect()
+}
+
+/// Rejects calls to an unqualified function that is not in `known`. Qualified paths
+/// (`self::f`, `module::f`) are left alone as plain Rust calls.
+fn unknown_call_error(call: &syn::ExprCall, macro_name: &str, known: &[&str]) -> Option<String> {
+    let Expr::Path(func_path) = &*call.func else {
+        return None;
+    };
+    if func_path.qself.is_some() || func_path.path.segments.len() != 1 {
+        return None;
+    }
+    let func_name = func_path.path.segments[0].ident.to_string();
+    if known.contains(&func_name.as_str()) {
+        return None;
+    }
+    Some(unknown_function_error(macro_name, &func_name, known))
+}
+
+/// Error for a call to an unqualified function that `macro_name` does not know,
+/// suggesting the closest known name (earliest in table order on ties).
+fn unknown_function_error(macro_name: &str, func_name: &str, known: &[&str]) -> String {
+    let suggestion = known
+        .iter()
+        .map(|name| (edit_distance(func_name, name), *name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| format!(" Did you mean '{}'?", name))
+        .unwrap_or_default();
+
+    format!(
+        "{}!: unknown function '{}'.{} Known functions: {}. \
+         To call a plain Rust function, qualify its path (e.g. `self::{}(..)`).",
+        macro_name,
+        func_name,
+        suggestion,
+        known.join(", "),
+        func_name
+    )
+}
+
+/// Trig functions whose (first) argument is an angle; `@degrees` converts it with RADIANS.
+const TRIG_ANGLE_IN: &[&str] = &["sin", "cos", "tan"];
+/// Trig functions that return an angle; `@degrees` converts the result with DEGREES.
+const TRIG_ANGLE_OUT: &[&str] = &["asin", "acos", "atan", "atan2"];
+
+/// `ShaderNodeMath` with the given operation variant, feeding `args` into inputs 0, 1, ...
+fn shader_math(variant_name: &str, args: &[&Expr]) -> Expr {
+    let variant_ident = syn::Ident::new(variant_name, proc_macro2::Span::call_site());
+    let input_setters = args.iter().enumerate().map(|(i, arg)| {
+        quote! { .set_input(#i, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#arg)) }
+    });
+    syn::parse_quote! {
+        blender_ramen::core::nodes::ShaderNodeMath::new()
+            .with_operation(blender_ramen::core::nodes::ShaderNodeMathOperation::#variant_ident)
+            #(#input_setters)*
+            .out_value()
+    }
+}
+
+/// `FunctionNodeCompare` on floats with the given operation variant.
+fn compare_float(variant_name: &str, left: &Expr, right: &Expr) -> Expr {
+    let op_ident = syn::Ident::new(variant_name, proc_macro2::Span::call_site());
+    syn::parse_quote! {
+        blender_ramen::core::nodes::FunctionNodeCompare::new()
+            .with_data_type(blender_ramen::core::nodes::FunctionNodeCompareDataType::Float)
+            .with_operation(blender_ramen::core::nodes::FunctionNodeCompareOperation::#op_ident)
+            .set_input(0, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#left))
+            .set_input(1, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#right))
+            .out_result()
+    }
+}
+
+/// `FunctionNodeBooleanMath` with the given two-input operation variant.
+fn boolean_math(variant_name: &str, left: &Expr, right: &Expr) -> Expr {
+    let op_ident = syn::Ident::new(variant_name, proc_macro2::Span::call_site());
+    syn::parse_quote! {
+        blender_ramen::core::nodes::FunctionNodeBooleanMath::new()
+            .with_operation(blender_ramen::core::nodes::FunctionNodeBooleanMathOperation::#op_ident)
+            .set_input(0, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Bool>::from(#left))
+            .set_input(1, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Bool>::from(#right))
+            .out_boolean()
+    }
+}
+
+const CHAINED_COMPARISON_MSG: &str = "ramen_math!: comparisons can't be chained (`a < x < b` would compare a boolean with a float); use `in_range(x, a, b)` for a range check";
+
+fn is_comparison(op: &syn::BinOp) -> bool {
+    matches!(
+        op,
+        syn::BinOp::Eq(_)
+            | syn::BinOp::Ne(_)
+            | syn::BinOp::Lt(_)
+            | syn::BinOp::Le(_)
+            | syn::BinOp::Gt(_)
+            | syn::BinOp::Ge(_)
+    )
+}
+
+/// An ordering comparison whose operand is itself a comparison, e.g. `(0.0 < x) < 1.0`: valid
+/// Rust, but it orders a boolean against a float instead of checking a range. `==` / `!=`
+/// between comparisons (`(a < b) == (c < d)`) are meaningful and left alone.
+fn chained_comparison_error(bin: &syn::ExprBinary) -> Option<Expr> {
+    fn unparen(expr: &Expr) -> &Expr {
+        match expr {
+            Expr::Paren(paren) => unparen(&paren.expr),
+            Expr::Group(group) => unparen(&group.expr),
+            other => other,
+        }
+    }
+    let is_ordering = matches!(
+        bin.op,
+        syn::BinOp::Lt(_) | syn::BinOp::Le(_) | syn::BinOp::Gt(_) | syn::BinOp::Ge(_)
+    );
+    let operand_is_comparison =
+        |expr: &Expr| matches!(unparen(expr), Expr::Binary(inner) if is_comparison(&inner.op));
+    if !is_ordering || !(operand_is_comparison(&bin.left) || operand_is_comparison(&bin.right)) {
+        return None;
+    }
+    Some(syn::parse_quote_spanned! {bin.op.span()=>
+        compile_error!(#CHAINED_COMPARISON_MSG)
+    })
+}
+
+/// Span of the second of two comparisons chained at the same level of `tokens` (`a < x < b`),
+/// which Rust does not parse. Groups are separate levels, so `(a < b) == (c < d)` is not chained.
+fn chained_comparison_span(tokens: proc_macro2::TokenStream) -> Option<proc_macro2::Span> {
+    use proc_macro2::{Spacing, TokenTree};
+    // Longest first, so that `<=` is not read as `<`.
+    const OPERATORS: &[&str] = &[
+        "::<", "..=", "<=", ">=", "==", "!=", "&&", "||", "..", "=>", "->", "::",
+    ];
+
+    let trees: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut seen_comparison = false;
+    let mut generic_depth = 0usize;
+    let mut i = 0;
+    while i < trees.len() {
+        let first = match &trees[i] {
+            TokenTree::Punct(punct) => punct,
+            TokenTree::Group(group) => {
+                if let Some(span) = chained_comparison_span(group.stream()) {
+                    return Some(span);
+                }
+                i += 1;
+                continue;
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        // Joint puncts spell one or more operators, e.g. `<=` or `::<`.
+        let mut run = vec![first.clone()];
+        while run.last().unwrap().spacing() == Spacing::Joint
+            && let Some(TokenTree::Punct(next)) = trees.get(i + run.len())
+        {
+            run.push(next.clone());
+        }
+        i += run.len();
+
+        let chars: String = run.iter().map(proc_macro2::Punct::as_char).collect();
+        let mut offset = 0;
+        while offset < chars.len() {
+            let rest = &chars[offset..];
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .copied()
+                .unwrap_or(&rest[..1]);
+            match op {
+                "::<" => generic_depth += 1,
+                "<" if generic_depth > 0 => generic_depth += 1,
+                ">" if generic_depth > 0 => generic_depth -= 1,
+                "<" | ">" | "<=" | ">=" | "==" | "!=" => {
+                    if seen_comparison {
+                        return Some(run[offset].span());
+                    }
+                    seen_comparison = true;
+                }
+                "&&" | "||" | "," | ";" | "=" | ".." | "..=" | "=>" => seen_comparison = false,
+                _ => {}
+            }
+            offset += op.len();
+        }
+    }
+    None
+}
+
+/// Evaluates `arg` once into a local socket and passes its path to `body`, for expansions
+/// that use their argument several times.
+fn with_bound_arg(arg: &Expr, body: impl FnOnce(&Expr) -> Expr) -> Expr {
+    let t: Expr = syn::parse_quote!(__ramen_t);
+    let body = body(&t);
+    syn::parse_quote! {{
+        let __ramen_t = blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#arg);
+        #body
+    }}
+}
 
+/// Value of a numeric literal, optionally negated or parenthesized (`2.0`, `-1`, `(0.5)`).
+fn literal_value(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Float(lit),
+            ..
+        }) => lit.base10_parse().ok(),
+        Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => lit.base10_parse().ok(),
+        Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => literal_value(expr).map(|v| -v),
+        Expr::Paren(paren) => literal_value(&paren.expr),
+        Expr::Group(group) => literal_value(&group.expr),
         _ => None,
     }
 }
@@ -53,14 +311,49 @@ fn get_blender_math_op(name: &str) -> Option<(&'static str, usize)> {
 /// Main roles:
 /// 1. Appends `.clone()` to path expressions (variables, etc.) to facilitate reuse within expressions.
 /// 2. Replaces specific math function calls with code that generates `ShaderNodeMath` nodes.
-struct MathFolder;
+#[derive(Default)]
+struct MathFolder {
+    /// Method calls (as token strings) that occur more than once in the expression.
+    reused_method_calls: std::collections::HashSet<String>,
+    /// `@mod_floor`: `%` emits FLOORED_MODULO instead of the truncated MODULO.
+    floored_rem: bool,
+    /// `@degrees`: trig functions take and return degrees.
+    degrees: bool,
+    /// `@exclusive`: `in_range` / `outside` treat the bounds as outside the range.
+    exclusive: bool,
+    /// `@exact_pow`: `pow` always emits POWER, without the literal exponent rewrites.
+    exact_pow: bool,
+}
 
 impl MathFolder {
+    /// Applies the invocation-level `@flag`s.
+    fn with_flags(flags: &[Ident]) -> syn::Result<Self> {
+        let mut folder = Self::default();
+        for flag in flags {
+            match flag.to_string().as_str() {
+                "mod_floor" => folder.floored_rem = true,
+                "degrees" => folder.degrees = true,
+                "exclusive" => folder.exclusive = true,
+                "exact_pow" => folder.exact_pow = true,
+                other => {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        format!(
+                            "ramen_math!: unknown flag '@{}' (expected: @mod_floor, @degrees, @exclusive, @exact_pow)",
+                            other
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(folder)
+    }
+
     fn process_path(&mut self, path: &syn::ExprPath) -> Option<Expr> {
         // Do not clone identifiers registered as function names
         if path.path.segments.len() == 1 {
             let ident_str = path.path.segments[0].ident.to_string();
-            if get_blender_math_op(&ident_str).is_some() {
+            if get_blender_math_op(&ident_str).is_some() || get_composite_op(&ident_str).is_some() {
                 return None;
             }
         }
@@ -75,7 +368,11 @@ impl MathFolder {
                 None => return Some(folded.clone()),
             };
 
-            let (variant_name, expected_args) = get_blender_math_op(&func_name)?;
+            let math_op = get_blender_math_op(&func_name);
+            let expected_args = match math_op {
+                Some((_, n)) => n,
+                None => get_composite_op(&func_name)?,
+            };
 
             if call.args.len() != expected_args {
                 let msg = format!(
@@ -87,21 +384,132 @@ impl MathFolder {
                 return Some(syn::parse_quote! { compile_error!(#msg) });
             }
 
-            let input_setters = call.args.iter().enumerate().map(|(i, arg)| {
-                quote! { .set_input(#i, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#arg)) }
-            });
+            let mut args: Vec<Expr> = call.args.iter().cloned().collect();
+
+            let Some((variant_name, _)) = math_op else {
+                return self.process_composite(&func_name, &args);
+            };
 
-            let variant_ident = syn::Ident::new(variant_name, proc_macro2::Span::call_site());
-            return Some(syn::parse_quote! {
-                blender_ramen::core::nodes::ShaderNodeMath::new()
-                    .with_operation(blender_ramen::core::nodes::ShaderNodeMathOperation::#variant_ident)
-                    #(#input_setters)*
-                    .out_value()
-            });
+            // `@degrees`: angles in and out of trig functions are in degrees.
+            if self.degrees && TRIG_ANGLE_IN.contains(&func_name.as_str()) {
+                args[0] = shader_math("Radians", &[&args[0]]);
+            }
+            let arg_refs: Vec<&Expr> = args.iter().collect();
+            let node = shader_math(variant_name, &arg_refs);
+            if self.degrees && TRIG_ANGLE_OUT.contains(&func_name.as_str()) {
+                return Some(shader_math("Degrees", &[&node]));
+            }
+            return Some(node);
         }
         None
     }
 
+    /// Functions from `get_composite_op`; `args` already has the expected length.
+    fn process_composite(&self, func_name: &str, args: &[Expr]) -> Option<Expr> {
+        match func_name {
+            // floor(a / b): two nodes
+            "div_floor" => {
+                let quotient = shader_math("Divide", &[&args[0], &args[1]]);
+                Some(shader_math("Floor", &[&quotient]))
+            }
+            "sin_deg" => {
+                let radians = shader_math("Radians", &[&args[0]]);
+                Some(shader_math("Sine", &[&radians]))
+            }
+            "cos_deg" => {
+                let radians = shader_math("Radians", &[&args[0]]);
+                Some(shader_math("Cosine", &[&radians]))
+            }
+            // full turns to radians
+            "turns" => {
+                let tau: Expr = syn::parse_quote!(std::f32::consts::TAU);
+                Some(shader_math("Multiply", &[&args[0], &tau]))
+            }
+            // t^2: one node
+            "ease_in" => Some(with_bound_arg(&args[0], |t| {
+                shader_math("Multiply", &[t, t])
+            })),
+            // 1 - (1 - t)^2 = t * (2 - t): two nodes
+            "ease_out" => Some(with_bound_arg(&args[0], |t| {
+                let two: Expr = syn::parse_quote!(2.0_f32);
+                let rest = shader_math("Subtract", &[&two, t]);
+                shader_math("Multiply", &[t, &rest])
+            })),
+            // t^2 * (3 - 2t): three nodes
+            "ease_in_out" => Some(with_bound_arg(&args[0], |t| {
+                let (minus_two, three): (Expr, Expr) =
+                    (syn::parse_quote!(-2.0_f32), syn::parse_quote!(3.0_f32));
+                let square = shader_math("Multiply", &[t, t]);
+                let slope = shader_math("MultiplyAdd", &[t, &minus_two, &three]);
+                shader_math("Multiply", &[&square, &slope])
+            })),
+            // t^3 * (t * (6t - 15) + 10): five nodes
+            "smootherstep" => Some(with_bound_arg(&args[0], |t| {
+                let (six, minus_fifteen, ten): (Expr, Expr, Expr) = (
+                    syn::parse_quote!(6.0_f32),
+                    syn::parse_quote!(-15.0_f32),
+                    syn::parse_quote!(10.0_f32),
+                );
+                let square = shader_math("Multiply", &[t, t]);
+                let cube = shader_math("Multiply", &[&square, t]);
+                let inner = shader_math("MultiplyAdd", &[t, &six, &minus_fifteen]);
+                let poly = shader_math("MultiplyAdd", &[&inner, t, &ten]);
+                shader_math("Multiply", &[&cube, &poly])
+            })),
+            // lo <= x && x <= hi: three nodes (`@exclusive`: lo < x && x < hi)
+            "in_range" => Some(with_bound_arg(&args[0], |x| {
+                let op = if self.exclusive {
+                    "LessThan"
+                } else {
+                    "LessEqual"
+                };
+                let above_low = compare_float(op, &args[1], x);
+                let below_high = compare_float(op, x, &args[2]);
+                boolean_math("And", &above_low, &below_high)
+            })),
+            // x < lo || hi < x: three nodes (`@exclusive`: x <= lo || hi <= x)
+            "outside" => Some(with_bound_arg(&args[0], |x| {
+                let op = if self.exclusive {
+                    "LessEqual"
+                } else {
+                    "LessThan"
+                };
+                let below_low = compare_float(op, x, &args[1]);
+                let above_high = compare_float(op, &args[2], x);
+                boolean_math("Or", &below_low, &above_high)
+            })),
+            _ => None,
+        }
+    }
+
+    /// `pow(x, e)` with a literal `e` that has a cheaper form; `None` for other calls.
+    /// Runs before folding, since folding turns `-1.0` into a node.
+    fn process_pow(&mut self, call: &syn::ExprCall) -> Option<Expr> {
+        let Expr::Path(func_path) = &*call.func else {
+            return None;
+        };
+        if self.exact_pow || !func_path.path.is_ident("pow") || call.args.len() != 2 {
+            return None;
+        }
+        let exponent = literal_value(&call.args[1])?;
+        if ![2.0, 0.5, 1.0, -1.0].contains(&exponent) {
+            return None;
+        }
+        let base = self.fold_expr(call.args[0].clone());
+        Some(if exponent == 2.0 {
+            with_bound_arg(&base, |t| shader_math("Multiply", &[t, t]))
+        } else if exponent == 0.5 {
+            shader_math("Sqrt", &[&base])
+        } else if exponent == 1.0 {
+            syn::parse_quote! {
+                blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#base)
+            }
+        } else {
+            let one: Expr = syn::parse_quote!(1.0_f32);
+            shader_math("Divide", &[&one, &base])
+        })
+    }
+
     fn process_unary(&mut self, un: &syn::ExprUnary) -> Option<Expr> {
         match un.op {
             syn::UnOp::Not(_) => {
@@ -138,19 +546,19 @@ impl MathFolder {
             _ => None,
         };
 
-        if let Some(op_variant_name) = cmp_op {
-            let left = &bin.left;
-            let right = &bin.right;
-            let op_ident = syn::Ident::new(op_variant_name, proc_macro2::Span::call_site());
+        // `%` maps to MODULO explicitly (truncated, like Rust's `%`), independent of `std::ops::Rem`.
+        // `@mod_floor` switches it to FLOORED_MODULO.
+        if let syn::BinOp::Rem(_) = bin.op {
+            let variant_name = if self.floored_rem {
+                "FlooredModulo"
+            } else {
+                "Modulo"
+            };
+            return Some(shader_math(variant_name, &[&bin.left, &bin.right]));
+        }
 
-            return Some(syn::parse_quote! {
-                blender_ramen::core::nodes::FunctionNodeCompare::new()
-                    .with_data_type(blender_ramen::core::nodes::FunctionNodeCompareDataType::Float)
-                    .with_operation(blender_ramen::core::nodes::FunctionNodeCompareOperation::#op_ident)
-                    .set_input(0, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#left))
-                    .set_input(1, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#right))
-                    .out_result()
-            });
+        if let Some(op_variant_name) = cmp_op {
+            return Some(compare_float(op_variant_name, &bin.left, &bin.right));
         }
 
         let bool_op = match bin.op {
@@ -161,17 +569,7 @@ impl MathFolder {
         };
 
         if let Some(op_variant_name) = bool_op {
-            let left = &bin.left;
-            let right = &bin.right;
-            let op_ident = syn::Ident::new(op_variant_name, proc_macro2::Span::call_site());
-
-            return Some(syn::parse_quote! {
-                blender_ramen::core::nodes::FunctionNodeBooleanMath::new()
-                    .with_operation(blender_ramen::core::nodes::FunctionNodeBooleanMathOperation::#op_ident)
-                    .set_input(0, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Bool>::from(#left))
-                    .set_input(1, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Bool>::from(#right))
-                    .out_boolean()
-            });
+            return Some(boolean_math(op_variant_name, &bin.left, &bin.right));
         }
 
         None
@@ -180,7 +578,33 @@ impl MathFolder {
 
 impl Fold for MathFolder {
     fn fold_expr(&mut self, expr: Expr) -> Expr {
+        // Checked before folding, which would rewrite an unknown callee into `name.clone()`.
+        if let Expr::Call(call) = &expr
+            && let Some(msg) = unknown_call_error(call, "ramen_math", &math_function_names())
+        {
+            return syn::parse_quote! { compile_error!(#msg) };
+        }
+        if let Expr::Binary(bin) = &expr
+            && let Some(error) = chained_comparison_error(bin)
+        {
+            return error;
+        }
+        if let Expr::Call(call) = &expr
+            && let Some(rewritten) = self.process_pow(call)
+        {
+            return rewritten;
+        }
+
+        // Like paths, a method call that appears more than once is cloned at each use.
+        let is_reused_call = matches!(expr, Expr::MethodCall(_))
+            && self
+                .reused_method_calls
+                .contains(&quote!(#expr).to_string());
+
         let folded = syn::fold::fold_expr(self, expr);
+        if is_reused_call {
+            return syn::parse_quote!( #folded.clone() );
+        }
 
         match &folded {
             Expr::Path(path) => {
@@ -218,11 +642,13 @@ impl Fold for MathFolder {
 /// ### Transformation Mechanism
 /// 1. **Automatic Variable Cloning**: Path expressions (variables or constants) in the expression
 ///    are automatically appended with `.clone()`. This allows the same variable to be reused multiple times.
+///    Method calls that appear more than once (e.g. `sep.out_x() * sep.out_x()`) are cloned the same way.
 /// 2. **Function Call Conversion**: Supported function calls are converted into corresponding `ShaderNodeMath` operations.
 /// 3. **Literals**: Numeric literals (e.g., `2.0`) are preserved as is.
 ///
 /// ### Supported Operators
-/// - **Arithmetic**: `+`, `-`, `*`, `/`, `%` (Relies on Rust's `std::ops`, dynamically mapped to appropriate nodes)
+/// - **Arithmetic**: `+`, `-`, `*`, `/` (Relies on Rust's `std::ops`, dynamically mapped to appropriate nodes)
+/// - **Remainder**: `%` (Generates a `ShaderNodeMath` node on `Float`; see *Flags* for the modulo flavor)
 /// - **Comparison**: `==`, `!=`, `<`, `<=`, `>`, `>=` (Generates `FunctionNodeCompare`)
 /// - **Boolean**: `&&` (or `&`), `||` (or `|`), `^`, and unary `!` (Generates `FunctionNodeBooleanMath`)
 ///
@@ -230,8 +656,35 @@ impl Fold for MathFolder {
 /// Supports the following functions available in `ShaderNodeMath` for Blender 5.x and later:
 ///
 /// - **1 argument**: `sin`, `cos`, `tan`, `asin`, `acos`, `atan`, `sinh`, `cosh`, `tanh`, `sqrt`, `exp`, `round`, `floor`, `ceil`, `trunc`, `fract`, `abs`, `sign`, `radians`, `degrees`
-/// - **2 arguments**: `log`, `atan2`, `pow`, `modulo`, `min`, `max`, `snap`, `pingpong`
+/// - **2 arguments**: `log`, `atan2`, `pow`, `modulo`, `min`, `max`, `snap`, `pingpong`,
+///   `fmod` (truncated MODULO), `mod_floor` (FLOORED_MODULO), `div_floor` (`floor(a / b)`, two nodes)
+/// - **Angles**: `sin_deg`, `cos_deg` (RADIANS feeding SINE/COSINE), `turns` (full turns to radians, `x * TAU`)
+/// - **Easing** (for a factor `t` in 0..1, not clamped; `t` is evaluated once):
+///   `ease_in` (`t²`, 1 node), `ease_out` (`t(2 - t)`, 2 nodes),
+///   `ease_in_out` (`t²(3 - 2t)`, the smoothstep polynomial, 3 nodes),
+///   `smootherstep` (`t³(t(6t - 15) + 10)`, 5 nodes)
 /// - **3 arguments**: `wrap`, `smooth_min`, `smooth_max`, `compare`, `multiply_add`
+/// - **Range checks** (two `FunctionNodeCompare` and a `FunctionNodeBooleanMath`, `x` evaluated once):
+///   `in_range(x, lo, hi)` (`lo <= x && x <= hi`), `outside(x, lo, hi)` (`x < lo || hi < x`)
+///
+/// ### Flags
+/// Invocation-level flags go before the expression, e.g. `ramen_math!(@mod_floor x % 1.0)`.
+/// - `@mod_floor`: `%` uses FLOORED_MODULO (result takes the sign of the divisor, so tiling math
+///   keeps working for negative inputs). Without it, `%` uses Blender's truncated MODULO,
+///   matching Rust's `%` and `NodeSocket<Float> % ...` outside the macro.
+/// - `@degrees`: `sin`, `cos`, `tan` take degrees (a RADIANS node is inserted on their input) and
+///   `asin`, `acos`, `atan`, `atan2` return degrees (a DEGREES node is appended). Other functions,
+///   including ones nested inside trig calls, are unaffected.
+/// - `@exclusive`: the bounds of `in_range` / `outside` are not part of the range, so `in_range`
+///   uses `<` and `outside` uses `<=`.
+/// - `@exact_pow`: `pow` always emits a POWER node (see *Power Rewrites*).
+///
+/// ### Power Rewrites
+/// `pow` with a literal exponent emits a cheaper node where one exists: `pow(x, 2.0)` is
+/// MULTIPLY(x, x), `pow(x, 0.5)` is SQRT, `pow(x, 1.0)` is `x` itself (no node) and
+/// `pow(x, -1.0)` is DIVIDE(1.0, x). In `pow(x, 2.0)`, `x` is evaluated once and linked to
+/// both inputs, so a subexpression is not built twice. The one difference in results is at
+/// zero: `pow(0.0, -1.0)` is 0.0 (DIVIDE is a safe divide) where POWER gives infinity.
 ///
 /// ### Example
 /// ```ignore
@@ -251,12 +704,898 @@ impl Fold for MathFolder {
 /// - **Functions and Comparisons**: Unlike arithmetic, functions (e.g., `pow(x, 2)`) and comparisons
 ///   (e.g., `x > 0`) are fully intercepted by the macro and wrap their arguments in `NodeSocket::from(...)`.
 ///   Therefore, using integers like `2` here is perfectly valid and will be implicitly cast to `Float`.
+/// - **Chained Comparisons**: `lo < x < hi` is rejected with an error pointing to `in_range`,
+///   as is the parenthesized `(lo < x) < hi`, which would otherwise order a boolean against a
+///   float. Comparisons can still be tested for equality, e.g. `(a < b) == (c < d)`.
 /// - **Blender Node Types**: All comparison and boolean operations unconditionally generate `FLOAT` and
 ///   `BOOLEAN` type nodes. Non-float types passed into these operations are automatically cast to floats.
 #[proc_macro]
 pub fn ramen_math(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as MathInput);
+    TokenStream::from(expand_ramen_math(input))
+}
+
+/// `[@flag ...] expr`
+struct MathInput {
+    flags: Vec<Ident>,
+    expr: Expr,
+}
+
+impl Parse for MathInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut flags = Vec::new();
+        while input.peek(Token![@]) {
+            input.parse::<Token![@]>()?;
+            flags.push(input.parse()?);
+        }
+        // Rust doesn't parse `a < x < b`; point at the range helpers instead.
+        let tokens: proc_macro2::TokenStream = input.fork().parse()?;
+        let expr =
+            input
+                .parse()
+                .map_err(|err: syn::Error| match chained_comparison_span(tokens) {
+                    Some(span) => syn::Error::new(span, CHAINED_COMPARISON_MSG),
+                    None => err,
+                })?;
+        Ok(Self { flags, expr })
+    }
+}
+
+fn expand_ramen_math(input: MathInput) -> proc_macro2::TokenStream {
+    let mut folder = match MathFolder::with_flags(&input.flags) {
+        Ok(folder) => folder,
+        Err(err) => return err.to_compile_error(),
+    };
+    folder.reused_method_calls = MethodCallCounter::reused_in(&input.expr);
+    let expanded = folder.fold_expr(input.expr);
+    quote!( #expanded )
+}
+
+/// Counts method calls by their token string (a `Fold` that leaves the expression unchanged).
+#[derive(Default)]
+struct MethodCallCounter {
+    counts: std::collections::HashMap<String, usize>,
+}
+
+impl MethodCallCounter {
+    fn reused_in(expr: &Expr) -> std::collections::HashSet<String> {
+        let mut counter = Self::default();
+        counter.fold_expr(expr.clone());
+        counter
+            .counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(call, _)| call)
+            .collect()
+    }
+}
+
+impl Fold for MethodCallCounter {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        if let Expr::MethodCall(_) = &expr {
+            *self.counts.entry(quote!(#expr).to_string()).or_default() += 1;
+        }
+        syn::fold::fold_expr(self, expr)
+    }
+}
+
+/// Rust function names mapped to a Blender `ShaderNodeVectorMath` enum variant (PascalCase),
+/// the input pins their arguments go to, and whether the result is the Float `Value` output
+/// (otherwise `Vector`). Pin 3 (`Scale`, the IOR of REFRACT) takes a Float, every other pin a Vector.
+const VECTOR_FUNCTIONS: &[(&str, &str, &[usize], bool)] = &[
+    ("cross", "CrossProduct", &[0, 1], false),
+    ("project", "Project", &[0, 1], false),
+    ("reflect", "Reflect", &[0, 1], false),
+    ("refract", "Refract", &[0, 1, 3], false),
+    ("faceforward", "Faceforward", &[0, 1, 2], false),
+    ("dot", "DotProduct", &[0, 1], true),
+    ("distance", "Distance", &[0, 1], true),
+    ("length", "Length", &[0], true),
+    ("scale", "Scale", &[0, 3], false),
+    ("normalize", "Normalize", &[0], false),
+    ("abs", "Absolute", &[0], false),
+    ("pow", "Power", &[0, 1], false),
+    ("sign", "Sign", &[0], false),
+    ("min", "Minimum", &[0, 1], false),
+    ("max", "Maximum", &[0, 1], false),
+    ("floor", "Floor", &[0], false),
+    ("ceil", "Ceil", &[0], false),
+    ("fract", "Fraction", &[0], false),
+    ("modulo", "Modulo", &[0, 1], false),
+    ("fmod", "Modulo", &[0, 1], false),
+    ("wrap", "Wrap", &[0, 1, 2], false),
+    ("snap", "Snap", &[0, 1], false),
+    ("sin", "Sine", &[0], false),
+    ("cos", "Cosine", &[0], false),
+    ("tan", "Tangent", &[0], false),
+    ("multiply_add", "MultiplyAdd", &[0, 1, 2], false),
+];
+
+fn get_vector_math_op(name: &str) -> Option<(&'static str, &'static [usize], bool)> {
+    VECTOR_FUNCTIONS
+        .iter()
+        .find(|(rust_name, _, _, _)| *rust_name == name)
+        .map(|&(_, variant, pins, is_value)| (variant, pins, is_value))
+}
+
+/// `ShaderNodeVectorMath` with the given operation variant, feeding `args` into `pins`.
+fn vector_math(variant_name: &str, pins: &[usize], args: &[&Expr], is_value: bool) -> Expr {
+    let variant_ident = syn::Ident::new(variant_name, proc_macro2::Span::call_site());
+    let input_setters = pins.iter().zip(args).map(|(&pin, arg)| {
+        if pin == 3 {
+            quote! { .set_input(#pin, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Float>::from(#arg)) }
+        } else {
+            quote! { .set_input(#pin, blender_ramen::core::types::NodeSocket::<blender_ramen::core::types::Vector>::from(#arg)) }
+        }
+    });
+    let output = if is_value {
+        quote!(out_value)
+    } else {
+        quote!(out_vector)
+    };
+    syn::parse_quote! {
+        blender_ramen::core::nodes::ShaderNodeVectorMath::new()
+            .with_operation(blender_ramen::core::nodes::ShaderNodeVectorMathOperation::#variant_ident)
+            #(#input_setters)*
+            .#output()
+    }
+}
+
+/// The `ramen_vec!` counterpart of `MathFolder`: paths are cloned and known function calls
+/// become `ShaderNodeVectorMath` nodes.
+struct VecFolder;
+
+impl VecFolder {
+    fn process_path(&mut self, path: &syn::ExprPath) -> Option<Expr> {
+        if path.path.segments.len() == 1
+            && get_vector_math_op(&path.path.segments[0].ident.to_string()).is_some()
+        {
+            return None;
+        }
+        Some(syn::parse_quote!( #path.clone() ))
+    }
+
+    fn process_call(&mut self, call: &syn::ExprCall) -> Option<Expr> {
+        let Expr::Path(func_path) = &*call.func else {
+            return None;
+        };
+        let func_name = func_path.path.segments.last()?.ident.to_string();
+        let (variant_name, pins, is_value) = get_vector_math_op(&func_name)?;
+
+        if call.args.len() != pins.len() {
+            let msg = format!(
+                "ramen_vec!: function '{}' expects {} argument(s), but got {}",
+                func_name,
+                pins.len(),
+                call.args.len()
+            );
+            return Some(syn::parse_quote! { compile_error!(#msg) });
+        }
+        let args: Vec<&Expr> = call.args.iter().collect();
+        Some(vector_math(variant_name, pins, &args, is_value))
+    }
+
+    fn process_unary(&mut self, un: &syn::ExprUnary) -> Option<Expr> {
+        let syn::UnOp::Neg(_) = un.op else {
+            return None;
+        };
+        let minus_one: Expr = syn::parse_quote!(-1.0_f32);
+        Some(vector_math(
+            "Scale",
+            &[0, 3],
+            &[&un.expr, &minus_one],
+            false,
+        ))
+    }
+
+    fn process_binary(&mut self, bin: &syn::ExprBinary) -> Option<Expr> {
+        // Componentwise MODULO, like `%` on floats in `ramen_math!`.
+        let syn::BinOp::Rem(_) = bin.op else {
+            return None;
+        };
+        Some(vector_math(
+            "Modulo",
+            &[0, 1],
+            &[&bin.left, &bin.right],
+            false,
+        ))
+    }
+}
+
+impl Fold for VecFolder {
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        if let Expr::Call(call) = &expr {
+            let known: Vec<&str> = VECTOR_FUNCTIONS.iter().map(|(name, ..)| *name).collect();
+            if let Some(msg) = unknown_call_error(call, "ramen_vec", &known) {
+                return syn::parse_quote! { compile_error!(#msg) };
+            }
+        }
+
+        let folded = syn::fold::fold_expr(self, expr);
+
+        let processed = match &folded {
+            Expr::Path(path) => self.process_path(path),
+            Expr::Call(call) => self.process_call(call),
+            Expr::Unary(un) => self.process_unary(un),
+            Expr::Binary(bin) => self.process_binary(bin),
+            _ => None,
+        };
+        processed.unwrap_or(folded)
+    }
+}
+
+/// Sibling of `ramen_math!` for expressions on `NodeSocket<Vector>`.
+///
+/// Function calls become `ShaderNodeVectorMath` nodes, so names shared with `ramen_math!`
+/// (`min`, `abs`, `floor`, ...) unambiguously mean the componentwise vector operation.
+/// Variables are cloned like in `ramen_math!`.
+///
+/// ### Supported Operators
+/// - **Arithmetic**: `+`, `-`, `*`, `/` (Rust's `std::ops`, so `Vector * Float` scales)
+/// - **Remainder**: `%` (componentwise MODULO)
+/// - **Negation**: unary `-` (SCALE by `-1`)
+///
+/// ### Supported Functions
+/// - **Returning Float**: `dot`, `distance`, `length`
+/// - **1 argument**: `normalize`, `abs`, `sign`, `floor`, `ceil`, `fract`, `sin`, `cos`, `tan`
+/// - **2 arguments**: `cross`, `project`, `reflect`, `pow`, `min`, `max`, `modulo` (or `fmod`), `snap`,
+///   `scale` (vector, float)
+/// - **3 arguments**: `refract` (vector, normal, float IOR), `faceforward`, `wrap`, `multiply_add`
+///
+/// ### Example
+/// ```ignore
+/// let n = ramen_vec!(normalize(cross(a, b)));
+/// let facing = ramen_vec!(dot(n, view) * 0.5);
+/// ```
+#[proc_macro]
+pub fn ramen_vec(input: TokenStream) -> TokenStream {
     let expr = parse_macro_input!(input as Expr);
-    let mut folder = MathFolder;
-    let expanded = folder.fold_expr(expr);
-    TokenStream::from(quote!( #expanded ))
+    TokenStream::from(expand_ramen_vec(expr))
+}
+
+fn expand_ramen_vec(expr: Expr) -> proc_macro2::TokenStream {
+    let expanded = VecFolder.fold_expr(expr);
+    quote!( #expanded )
+}
+
+/// One `field: Type = "Socket Name"` entry of `group_sockets!`.
+struct GroupSocketField {
+    ident: Ident,
+    ty: Type,
+    name: LitStr,
+}
+
+impl Parse for GroupSocketField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty: Type = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let name: LitStr = input.parse()?;
+        Ok(Self { ident, ty, name })
+    }
+}
+
+/// `[vis] Name { field: Type = "Socket Name", ... }`
+struct GroupSocketsInput {
+    vis: Visibility,
+    ident: Ident,
+    fields: Punctuated<GroupSocketField, Token![,]>,
+}
+
+impl Parse for GroupSocketsInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis: Visibility = input.parse()?;
+        let ident: Ident = input.parse()?;
+        let content;
+        braced!(content in input);
+        let fields = content.parse_terminated(GroupSocketField::parse, Token![,])?;
+        Ok(Self { vis, ident, fields })
+    }
+}
+
+fn expand_group_sockets(input: GroupSocketsInput) -> proc_macro2::TokenStream {
+    let GroupSocketsInput { vis, ident, fields } = input;
+
+    let mut seen = std::collections::HashSet::new();
+    for field in &fields {
+        if !seen.insert(field.name.value()) {
+            let msg = format!(
+                "group_sockets!: socket name \"{}\" is declared more than once",
+                field.name.value()
+            );
+            return syn::Error::new(field.name.span(), msg).to_compile_error();
+        }
+    }
+
+    let field_defs = fields.iter().map(|f| {
+        let (field_ident, ty) = (&f.ident, &f.ty);
+        quote! { pub #field_ident: blender_ramen::core::types::NodeSocket<#ty> }
+    });
+    let index_consts = fields.iter().enumerate().map(|(i, f)| {
+        let const_ident = format_ident!("{}", f.ident.to_string().to_uppercase());
+        quote! { pub const #const_ident: usize = #i; }
+    });
+    let names = fields.iter().map(|f| &f.name);
+    let count = fields.len();
+    let declares = fields.iter().map(|f| {
+        let (ty, name) = (&f.ty, &f.name);
+        quote! { .with_input::<#ty>(#name) }
+    });
+    let reads = fields.iter().map(|f| {
+        let (field_ident, ty, name) = (&f.ident, &f.ty, &f.name);
+        quote! {
+            #field_ident: blender_ramen::core::types::NodeGroupInputExt::socket::<#ty>(group_in, #name)
+        }
+    });
+
+    quote! {
+        #[derive(Clone, Copy)]
+        #vis struct #ident {
+            #(#field_defs),*
+        }
+
+        impl #ident {
+            #(#index_consts)*
+
+            /// Interface socket names in declaration order.
+            pub const NAMES: [&'static str; #count] = [#(#names),*];
+
+            /// Declares every socket as a group input, in field order.
+            pub fn declare(tree: blender_ramen::core::tree::NodeTree) -> blender_ramen::core::tree::NodeTree {
+                tree #(#declares)*
+            }
+
+            /// Reads every declared socket from a `NodeGroupInput` node.
+            pub fn read(group_in: &blender_ramen::core::nodes::NodeGroupInput) -> Self {
+                Self {
+                    #(#reads),*
+                }
+            }
+        }
+    }
+}
+
+/// Declares the inputs of a node group once and derives both sides of the interface from it.
+///
+/// `group_in.socket::<Float>("X")` only fails inside Blender when "X" was never declared.
+/// This macro keeps the declaration (`with_input`) and the lookup (`socket`) in one place.
+///
+/// ### Generated Items
+/// For `group_sockets!(MandelIn { x: Float = "X", cx: Float = "CX" })`:
+/// - `struct MandelIn { pub x: NodeSocket<Float>, pub cx: NodeSocket<Float> }`
+/// - `MandelIn::X`, `MandelIn::CX`: input indices in declaration order (for `set_input` on the caller side)
+/// - `MandelIn::NAMES`: the socket names in declaration order
+/// - `MandelIn::declare(tree)`: calls `with_input::<T>(name)` for every field, in order
+/// - `MandelIn::read(&group_in)`: reads every field via `NodeGroupInputExt::socket`
+///
+/// An optional visibility may precede the struct name (e.g. `group_sockets!(pub MandelIn { ... })`).
+/// Socket types are resolved at the call site, so they must be in scope.
+///
+/// ### Example
+/// ```ignore
+/// group_sockets!(MandelIn { x: Float = "X", y: Float = "Y" });
+///
+/// let subtree = MandelIn::declare(NodeTree::new_geometry_group("Step"))
+///     .with_output::<Float>("Out")
+///     .build(|| {
+///         let input = MandelIn::read(&NodeGroupInput::new());
+///         NodeGroupOutput::new().set_input(0, ramen_math!(input.x * input.y));
+///     });
+///
+/// call_geometry_group("Step").set_input(MandelIn::X, x).set_input(MandelIn::Y, y);
+/// ```
+#[proc_macro]
+pub fn group_sockets(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as GroupSocketsInput);
+    TokenStream::from(expand_group_sockets(input))
+}
+
+/// `#[derive(RamenItems)]` on a struct of `NodeSocket<T>` fields implements
+/// `blender_ramen::core::zone::RepeatItems`, so the struct can be the state of `repeat_zone`.
+/// Field order is item order, exactly like the tuple implementations.
+#[proc_macro_derive(RamenItems)]
+pub fn derive_ramen_items(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    TokenStream::from(expand_ramen_items(input))
+}
+
+fn expand_ramen_items(input: syn::DeriveInput) -> proc_macro2::TokenStream {
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(named),
+            ..
+        }) => &named.named,
+        _ => {
+            return syn::Error::new_spanned(
+                &input.ident,
+                "RamenItems can only be derived for structs with named fields",
+            )
+            .to_compile_error();
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let zone_socket = quote!(blender_ramen::core::zone::ZoneSocket);
+
+    let idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    // Repeat Input has the iterations socket first.
+    let inner_indices: Vec<_> = (1..=fields.len()).collect();
+    let outer_indices: Vec<_> = (0..fields.len()).collect();
+
+    quote! {
+        impl #impl_generics blender_ramen::core::zone::RepeatItems for #ident #ty_generics #where_clause {
+            fn setup_items(out_name: &str, post_code: &mut String) {
+                #( <#types as #zone_socket>::write_repeat_item(out_name, post_code); )*
+            }
+            fn link_initial(&self, in_name: &str) {
+                #( #zone_socket::link_to(&self.#idents, in_name, #inner_indices); )*
+            }
+            fn create_inner(in_name: &str) -> Self {
+                Self {
+                    #( #idents: <#types as #zone_socket>::from_output(in_name, #inner_indices), )*
+                }
+            }
+            fn link_result(&self, out_name: &str) {
+                #( #zone_socket::link_to(&self.#idents, out_name, #outer_indices); )*
+            }
+            fn create_output(out_name: &str) -> Self {
+                Self {
+                    #( #idents: <#types as #zone_socket>::from_output(out_name, #outer_indices), )*
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------
+// unittest
+// ---------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(tokens: proc_macro2::TokenStream) -> String {
+        let input: MathInput = syn::parse2(tokens).unwrap();
+        expand_ramen_math(input).to_string()
+    }
+
+    #[test]
+    fn test_ramen_math_rem_routes_to_modulo() {
+        let expanded = expand(quote!(a % 2.0));
+
+        assert!(expanded.contains("ShaderNodeMath :: new ()"));
+        assert!(expanded.contains("ShaderNodeMathOperation :: Modulo"));
+        assert!(expanded.contains("from (a . clone ())"));
+        assert!(expanded.contains("from (2.0)"));
+        assert!(!expanded.contains('%'));
+    }
+
+    #[test]
+    fn test_ramen_math_modulo_flavors() {
+        let expanded = expand(quote!(fmod(a, 2.0)));
+        assert!(expanded.contains("ShaderNodeMathOperation :: Modulo"));
+
+        let expanded = expand(quote!(mod_floor(a, 2.0)));
+        assert!(expanded.contains("ShaderNodeMathOperation :: FlooredModulo"));
+        assert!(!expanded.contains("mod_floor"));
+
+        let expanded = expand(quote!(@mod_floor a % 2.0));
+        assert!(expanded.contains("ShaderNodeMathOperation :: FlooredModulo"));
+        assert!(!expanded.contains("ShaderNodeMathOperation :: Modulo "));
+    }
+
+    #[test]
+    fn test_ramen_math_div_floor_expands_to_two_nodes() {
+        let expanded = expand(quote!(div_floor(a, b)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 2);
+
+        // Floor wraps Divide: the outer node is created first in the token stream.
+        let floor = expanded.find("ShaderNodeMathOperation :: Floor").unwrap();
+        let divide = expanded.find("ShaderNodeMathOperation :: Divide").unwrap();
+        assert!(floor < divide);
+        assert!(expanded.contains("from (a . clone ())"));
+        assert!(expanded.contains("from (b . clone ())"));
+
+        let expanded = expand(quote!(div_floor(a)));
+        assert!(expanded.contains("compile_error"));
+    }
+
+    #[test]
+    fn test_ramen_math_degree_helpers() {
+        let expanded = expand(quote!(sin_deg(a)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 2);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Sine"));
+        assert!(expanded.contains("ShaderNodeMathOperation :: Radians"));
+
+        let expanded = expand(quote!(cos_deg(a)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 2);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Cosine"));
+
+        let expanded = expand(quote!(turns(a)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 1);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Multiply"));
+        assert!(expanded.contains("std :: f32 :: consts :: TAU"));
+    }
+
+    #[test]
+    fn test_ramen_math_easing_functions() {
+        for (func, nodes) in [
+            (quote!(ease_in(t)), 1),
+            (quote!(ease_out(t)), 2),
+            (quote!(ease_in_out(t)), 3),
+            (quote!(smootherstep(t)), 5),
+        ] {
+            let expanded = expand(func.clone());
+            assert_eq!(
+                expanded.matches("ShaderNodeMath :: new ()").count(),
+                nodes,
+                "{}",
+                func
+            );
+            // The argument is evaluated once and the nodes read the bound socket.
+            assert_eq!(expanded.matches("from (t . clone ())").count(), 1);
+        }
+
+        // t^2 * (3 - 2t): Multiply(Multiply(t, t), MultiplyAdd(t, -2, 3))
+        let expanded = expand(quote!(ease_in_out(t)));
+        let ops: Vec<_> = expanded
+            .match_indices("ShaderNodeMathOperation :: ")
+            .map(|(i, m)| expanded[i + m.len()..].split(')').next().unwrap().trim())
+            .collect();
+        assert_eq!(ops, vec!["Multiply", "Multiply", "MultiplyAdd"]);
+        assert!(expanded.contains("from (- 2.0_f32)"));
+        assert!(expanded.contains("from (3.0_f32)"));
+        assert_eq!(expanded.matches("from (__ramen_t)").count(), 3);
+    }
+
+    #[test]
+    fn test_ramen_math_range_checks() {
+        let expanded = expand(quote!(in_range(x, 0.0, 1.0)));
+        assert_eq!(expanded.matches("FunctionNodeCompare :: new ()").count(), 2);
+        assert_eq!(
+            expanded
+                .matches("FunctionNodeBooleanMath :: new ()")
+                .count(),
+            1
+        );
+        assert_eq!(
+            expanded
+                .matches("FunctionNodeCompareOperation :: LessEqual")
+                .count(),
+            2
+        );
+        assert!(expanded.contains("FunctionNodeBooleanMathOperation :: And"));
+        // `x` is bound once and both compares read the binding.
+        assert_eq!(expanded.matches("x . clone ()").count(), 1);
+        assert_eq!(expanded.matches("from (__ramen_t)").count(), 2);
+
+        let expanded = expand(quote!(outside(x, 0.0, 1.0)));
+        assert_eq!(
+            expanded
+                .matches("FunctionNodeCompareOperation :: LessThan")
+                .count(),
+            2
+        );
+        assert!(expanded.contains("FunctionNodeBooleanMathOperation :: Or"));
+
+        let expanded = expand(quote!(@exclusive in_range(x, 0.0, 1.0)));
+        assert_eq!(
+            expanded
+                .matches("FunctionNodeCompareOperation :: LessThan")
+                .count(),
+            2
+        );
+        let expanded = expand(quote!(@exclusive outside(x, 0.0, 1.0)));
+        assert_eq!(
+            expanded
+                .matches("FunctionNodeCompareOperation :: LessEqual")
+                .count(),
+            2
+        );
+
+        assert!(expand(quote!(in_range(x, 1.0))).contains("compile_error"));
+    }
+
+    /// `"<index>: <argument>"` of every `set_input` in an expansion, in order.
+    fn wired_inputs(expanded: &str) -> Vec<String> {
+        expanded
+            .split(". set_input (")
+            .skip(1)
+            .map(|call| {
+                let (index, rest) = call.split_once(" ,").unwrap();
+                let arg = &rest[rest.find(":: from (").unwrap() + ":: from (".len()..];
+                let mut depth = 0;
+                let end = arg
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        match c {
+                            '(' => depth += 1,
+                            ')' if depth == 0 => return true,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        false
+                    })
+                    .unwrap()
+                    .0;
+                format!("{}: {}", index.trim_end_matches("usize"), arg[..end].trim())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ramen_math_comparisons_wire_distinct_pins() {
+        for op in [
+            quote!(a < b),
+            quote!(a <= b),
+            quote!(a > b),
+            quote!(a >= b),
+            quote!(a == b),
+            quote!(a != b),
+        ] {
+            let expanded = expand(op.clone());
+            assert_eq!(
+                wired_inputs(&expanded),
+                ["0: a . clone ()", "1: b . clone ()"],
+                "{}",
+                op
+            );
+        }
+
+        // Same operand on both sides still feeds both pins.
+        let expanded = expand(quote!(x < x));
+        assert_eq!(
+            wired_inputs(&expanded),
+            ["0: x . clone ()", "1: x . clone ()"]
+        );
+
+        let expanded = expand(quote!(compare(a, b, 0.1)));
+        assert_eq!(
+            wired_inputs(&expanded),
+            ["0: a . clone ()", "1: b . clone ()", "2: 0.1"]
+        );
+    }
+
+    #[test]
+    fn test_ramen_math_rejects_chained_comparisons() {
+        let err = syn::parse2::<MathInput>(quote!(0.0 < x < 1.0))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("use `in_range(x, a, b)`"));
+
+        let err = syn::parse2::<MathInput>(quote!(x == y <= 1.0))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("use `in_range(x, a, b)`"));
+
+        let expanded = expand(quote!((0.0 < x) < 1.0));
+        assert!(expanded.contains("compile_error"));
+        assert!(expanded.contains("use `in_range(x, a, b)`"));
+        assert!(!expanded.contains("FunctionNodeCompare"));
+
+        // Equality of comparisons, generics and boolean logic are not chains.
+        for input in [
+            quote!((a < b) == (c < d)),
+            quote!((a < b) && (b < c)),
+            quote!(a < NodeSocket::<Float>::pi() && b >= c),
+        ] {
+            let expanded = expand(input);
+            assert!(!expanded.contains("compile_error"), "{}", expanded);
+        }
+
+        // Other parse errors are left as they are.
+        let err = syn::parse2::<MathInput>(quote!(a < ))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(!err.contains("in_range"));
+    }
+
+    #[test]
+    fn test_ramen_math_degrees_flag() {
+        // sin + RADIANS, and the `* r` multiply
+        let expanded = expand(quote!(@degrees sin(angle) * r));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 2);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Radians"));
+
+        // without the flag: sin only
+        let expanded = expand(quote!(sin(angle) * r));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 1);
+        assert!(!expanded.contains("Radians"));
+
+        // inverse trig gets a DEGREES node on its result
+        let expanded = expand(quote!(@degrees atan2(y, x)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 2);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Degrees"));
+    }
+
+    #[test]
+    fn test_ramen_math_degrees_flag_skips_non_trig() {
+        let expanded = expand(quote!(@degrees sqrt(abs(x))));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 2);
+        assert!(!expanded.contains("Radians"));
+
+        // only the sin input is converted, not the nested sqrt
+        let expanded = expand(quote!(@degrees sin(sqrt(x))));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 3);
+        assert_eq!(
+            expanded
+                .matches("ShaderNodeMathOperation :: Radians")
+                .count(),
+            1
+        );
+        let radians = expanded.find("Radians").unwrap();
+        let sqrt = expanded.find("Sqrt").unwrap();
+        assert!(radians < sqrt);
+    }
+
+    #[test]
+    fn test_ramen_math_pow_literal_exponent_rewrites() {
+        let expanded = expand(quote!(pow(a + b, 2.0)));
+        assert!(!expanded.contains("Power"));
+        assert!(expanded.contains("ShaderNodeMathOperation :: Multiply"));
+        // the base is bound once and linked to both inputs
+        assert!(expanded.contains("let __ramen_t = "));
+        assert_eq!(wired_inputs(&expanded), ["0: __ramen_t", "1: __ramen_t"]);
+
+        let expanded = expand(quote!(pow(x, 0.5)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 1);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Sqrt"));
+
+        let expanded = expand(quote!(pow(x, 1)));
+        assert!(!expanded.contains("ShaderNodeMath"));
+        assert!(expanded.contains("from (x . clone ())"));
+
+        let expanded = expand(quote!(pow(x, -1.0)));
+        assert_eq!(expanded.matches("ShaderNodeMath :: new ()").count(), 1);
+        assert!(expanded.contains("ShaderNodeMathOperation :: Divide"));
+        assert_eq!(wired_inputs(&expanded), ["0: 1.0_f32", "1: x . clone ()"]);
+
+        // other and non-literal exponents stay POWER
+        for tokens in [quote!(pow(x, 3.0)), quote!(pow(x, e))] {
+            let expanded = expand(tokens);
+            assert!(expanded.contains("ShaderNodeMathOperation :: Power"));
+        }
+    }
+
+    #[test]
+    fn test_ramen_math_exact_pow_flag() {
+        let expanded = expand(quote!(@exact_pow pow(x, 2.0) + pow(y, 0.5)));
+        assert_eq!(
+            expanded.matches("ShaderNodeMathOperation :: Power").count(),
+            2
+        );
+        assert!(!expanded.contains("Multiply"));
+        assert!(!expanded.contains("Sqrt"));
+    }
+
+    #[test]
+    fn test_ramen_math_unknown_flag() {
+        let expanded = expand(quote!(@wat a % 2.0));
+        assert!(expanded.contains("compile_error"));
+        assert!(expanded.contains("unknown flag '@wat'"));
+    }
+
+    #[test]
+    fn test_ramen_math_unknown_function_suggests_known_name() {
+        let expanded = expand(quote!(sinn(a) + 1.0));
+        assert!(expanded.contains("compile_error"));
+        assert!(expanded.contains("unknown function 'sinn'"));
+        assert!(expanded.contains("Did you mean 'sin'?"));
+        assert!(expanded.contains("self::sinn(..)"));
+    }
+
+    #[test]
+    fn test_ramen_math_qualified_call_is_plain_rust() {
+        let expanded = expand(quote!(self::helper(a) * 2.0));
+        assert!(!expanded.contains("compile_error"));
+        assert!(expanded.contains("helper"));
+    }
+
+    #[test]
+    fn test_ramen_math_reused_method_call_is_cloned() {
+        let expanded = expand(quote!(sep.out_x() * sep.out_x() + sqrt(sep.out_y())));
+
+        assert_eq!(
+            expanded
+                .matches("sep . clone () . out_x () . clone ()")
+                .count(),
+            2
+        );
+        assert!(expanded.contains("from (sep . clone () . out_y ())"));
+        assert!(!expanded.contains("out_y () . clone ()"));
+    }
+
+    #[test]
+    fn test_group_sockets_indices_follow_declaration_order() {
+        let input: GroupSocketsInput = syn::parse_quote!(MandelIn {
+            x: Float = "X",
+            y: Float = "Y",
+            cz: Float = "CZ"
+        });
+        let expanded = expand_group_sockets(input).to_string();
+
+        assert!(expanded.contains("pub const X : usize = 0usize"));
+        assert!(expanded.contains("pub const Y : usize = 1usize"));
+        assert!(expanded.contains("pub const CZ : usize = 2usize"));
+
+        let x = expanded.find("with_input :: < Float > (\"X\")").unwrap();
+        let y = expanded.find("with_input :: < Float > (\"Y\")").unwrap();
+        let cz = expanded.find("with_input :: < Float > (\"CZ\")").unwrap();
+        assert!(x < y && y < cz);
+    }
+
+    #[test]
+    fn test_group_sockets_rejects_duplicate_names() {
+        let input: GroupSocketsInput = syn::parse_quote!(Dup {
+            a: Float = "A",
+            b: Float = "A"
+        });
+        let expanded = expand_group_sockets(input).to_string();
+
+        assert!(expanded.contains("compile_error"));
+        assert!(expanded.contains("declared more than once"));
+    }
+
+    #[test]
+    fn test_derive_ramen_items_indices_follow_field_order() {
+        let input: syn::DeriveInput = syn::parse2(quote! {
+            struct State {
+                pos: NodeSocket<Vector>,
+                geo: NodeSocket<Geo>,
+            }
+        })
+        .unwrap();
+        let expanded = expand_ramen_items(input).to_string();
+
+        assert!(expanded.contains("RepeatItems for State"));
+        assert!(expanded.contains("link_to (& self . pos , in_name , 1usize)"));
+        assert!(expanded.contains("link_to (& self . geo , in_name , 2usize)"));
+        assert!(expanded.contains("link_to (& self . geo , out_name , 1usize)"));
+    }
+
+    #[test]
+    fn test_derive_ramen_items_rejects_tuple_struct() {
+        let input: syn::DeriveInput = syn::parse2(quote!(
+            struct State(NodeSocket<Float>);
+        ))
+        .unwrap();
+        let expanded = expand_ramen_items(input).to_string();
+        assert!(expanded.contains("compile_error"));
+    }
+
+    #[test]
+    fn test_ramen_vec_normalize_of_sum() {
+        let expanded = expand_ramen_vec(syn::parse_quote!(normalize(a + b))).to_string();
+
+        assert_eq!(
+            expanded.matches("ShaderNodeVectorMath :: new ()").count(),
+            1
+        );
+        assert!(expanded.contains("ShaderNodeVectorMathOperation :: Normalize"));
+        assert!(expanded.contains("NodeSocket :: < blender_ramen :: core :: types :: Vector > :: from (a . clone () + b . clone ())"));
+        assert!(expanded.ends_with(". out_vector ()"));
+    }
+
+    #[test]
+    fn test_ramen_vec_float_results_and_errors() {
+        let expanded = expand_ramen_vec(syn::parse_quote!(dot(a, b))).to_string();
+        assert!(expanded.contains("ShaderNodeVectorMathOperation :: DotProduct"));
+        assert!(expanded.ends_with(". out_value ()"));
+
+        let expanded = expand_ramen_vec(syn::parse_quote!(min(a, b))).to_string();
+        assert!(expanded.contains("ShaderNodeVectorMathOperation :: Minimum"));
+        assert!(!expanded.contains("ShaderNodeMath ::"));
+
+        let expanded = expand_ramen_vec(syn::parse_quote!(crosss(a, b))).to_string();
+        assert!(expanded.contains("ramen_vec!: unknown function 'crosss'. Did you mean 'cross'?"));
+
+        let expanded = expand_ramen_vec(syn::parse_quote!(cross(a))).to_string();
+        assert!(expanded.contains("expects 2 argument(s), but got 1"));
+    }
 }
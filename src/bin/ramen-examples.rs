@@ -0,0 +1,57 @@
+//! Lists and runs the bundled examples with command-line parameters.
+//!
+//! ```text
+//! cargo run --bin ramen-examples
+//! cargo run --bin ramen-examples -- mandelbulb --help
+//! cargo run --bin ramen-examples -- mandelbulb --power 6 --resolution 256
+//! ```
+
+use blender_ramen::core::examples::{self, EXAMPLES, Example};
+use std::process::ExitCode;
+
+fn print_examples() {
+    println!("Usage: ramen-examples <example> [--flag value]...\n");
+    println!("Examples:");
+    for example in EXAMPLES {
+        println!("  {:<12} {}", example.name, example.description);
+    }
+}
+
+fn print_flags(example: &Example) {
+    println!("Usage: ramen-examples {} [--flag value]...\n", example.name);
+    println!("Flags:");
+    for (flag, description) in example.flags {
+        println!("  --{:<14} {}", flag, description);
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((name, rest)) = args.split_first() else {
+        print_examples();
+        return ExitCode::SUCCESS;
+    };
+
+    let Some(example) = examples::find(name) else {
+        eprintln!("❌ Unknown example '{}'\n", name);
+        print_examples();
+        return ExitCode::FAILURE;
+    };
+
+    if rest.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print_flags(example);
+        return ExitCode::SUCCESS;
+    }
+
+    match example.build(rest) {
+        Ok(project) => {
+            project.send();
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("❌ {}\n", err);
+            print_flags(example);
+            ExitCode::FAILURE
+        }
+    }
+}
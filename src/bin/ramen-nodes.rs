@@ -0,0 +1,78 @@
+//! Looks up the generated nodes by name.
+//!
+//! ```text
+//! cargo run --bin ramen-nodes -- search lens
+//! cargo run --bin ramen-nodes -- show GeometryNodeMeshCube
+//! ```
+
+use blender_ramen::core::meta::{self, NodeEntry, SocketEntry};
+use std::process::ExitCode;
+
+fn print_usage() {
+    println!("Usage: ramen-nodes search <query>");
+    println!("       ramen-nodes show <struct name or bl_idname>");
+}
+
+fn print_sockets(title: &str, sockets: &[SocketEntry]) {
+    println!("  {}:", title);
+    for (pin, socket) in sockets.iter().enumerate() {
+        println!("    {:>2}  {:<24} {}", pin, socket.name, socket.socket_type);
+    }
+}
+
+fn print_node(entry: &NodeEntry) {
+    println!("{} ({})", entry.struct_name, entry.bl_label);
+    println!("  bl_idname: {}", entry.bl_idname);
+    println!("  trees:     {}", entry.categories.join(", "));
+    print_sockets("inputs", &entry.inputs);
+    print_sockets("outputs", &entry.outputs);
+    if !entry.properties.is_empty() {
+        println!("  properties:");
+        for prop in &entry.properties {
+            if prop.enum_items.is_empty() {
+                println!("    {:<28} {}", prop.identifier, prop.property_type);
+            } else {
+                println!(
+                    "    {:<28} {}",
+                    prop.identifier,
+                    prop.enum_items.join(" | ")
+                );
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["search", query] => {
+            let hits = meta::search(query);
+            if hits.is_empty() {
+                eprintln!("❌ No node matches '{}'", query);
+                return ExitCode::FAILURE;
+            }
+            for entry in hits {
+                println!("  {:<40} {}", entry.struct_name, entry.bl_label);
+            }
+            ExitCode::SUCCESS
+        }
+        ["show", name] => match meta::find(name) {
+            Some(entry) => {
+                print_node(entry);
+                ExitCode::SUCCESS
+            }
+            None => {
+                eprintln!(
+                    "❌ Unknown node '{}'; try `ramen-nodes search {}`",
+                    name, name
+                );
+                ExitCode::FAILURE
+            }
+        },
+        _ => {
+            print_usage();
+            ExitCode::FAILURE
+        }
+    }
+}
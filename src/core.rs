@@ -1,10 +1,29 @@
 #![allow(dead_code)]
 
+pub mod attrs;
+pub mod bundle;
 pub mod context;
+pub mod debug;
+pub mod diagnostics;
+pub mod examples;
+pub mod fields;
+pub mod graph;
+pub mod helpers;
+pub mod inputs;
 pub mod live_link;
+pub mod materials;
+pub mod meta;
 pub mod nodes;
+pub mod observer;
 pub mod ops;
+pub mod param;
 pub mod project;
+pub mod rotation;
+pub mod snapshot;
+pub mod testing;
+pub mod textures;
 pub mod tree;
 pub mod types;
+pub mod volume;
+pub mod watch;
 pub mod zone;
@@ -0,0 +1,183 @@
+//! # Domain Sizes
+//!
+//! Element counts of one component of a geometry, typed by the component so only the counts
+//! it has are reachable:
+//!
+//! ```ignore
+//! let counts = attrs::domain_size(grid, attrs::Mesh);
+//! let t = fields::index_normalized(counts.points);
+//! let curves = attrs::domain_size(path, attrs::Curve);
+//! repeat_zone(curves.splines, ...);
+//! ```
+//!
+//! `GeometryNodeAttributeDomainSize` shows a different set of outputs per `component`; the
+//! others read 0 in Blender, so they are not exposed.
+
+use crate::core::nodes::{
+    GeometryNodeAttributeDomainSize, GeometryNodeAttributeDomainSizeComponent,
+};
+use crate::core::types::{Geo, Int, NodeSocket};
+
+/// Point, edge, face and face corner counts of a mesh.
+#[derive(Clone, Copy)]
+pub struct MeshCounts {
+    pub points: NodeSocket<Int>,
+    pub edges: NodeSocket<Int>,
+    pub faces: NodeSocket<Int>,
+    pub corners: NodeSocket<Int>,
+}
+
+/// Control point and spline counts of curves.
+#[derive(Clone, Copy)]
+pub struct CurveCounts {
+    pub points: NodeSocket<Int>,
+    pub splines: NodeSocket<Int>,
+}
+
+/// A geometry component `domain_size` can count, with the counts it reports.
+pub trait Component {
+    const COMPONENT: GeometryNodeAttributeDomainSizeComponent;
+    type Counts;
+
+    fn counts(node: &GeometryNodeAttributeDomainSize) -> Self::Counts;
+}
+
+pub struct Mesh;
+pub struct PointCloud;
+pub struct Curve;
+pub struct Instances;
+
+impl Component for Mesh {
+    const COMPONENT: GeometryNodeAttributeDomainSizeComponent =
+        GeometryNodeAttributeDomainSizeComponent::Mesh;
+    type Counts = MeshCounts;
+
+    fn counts(node: &GeometryNodeAttributeDomainSize) -> MeshCounts {
+        MeshCounts {
+            points: node.out_point_count(),
+            edges: node.out_edge_count(),
+            faces: node.out_face_count(),
+            corners: node.out_face_corner_count(),
+        }
+    }
+}
+
+impl Component for PointCloud {
+    const COMPONENT: GeometryNodeAttributeDomainSizeComponent =
+        GeometryNodeAttributeDomainSizeComponent::Pointcloud;
+    /// The point count.
+    type Counts = NodeSocket<Int>;
+
+    fn counts(node: &GeometryNodeAttributeDomainSize) -> NodeSocket<Int> {
+        node.out_point_count()
+    }
+}
+
+impl Component for Curve {
+    const COMPONENT: GeometryNodeAttributeDomainSizeComponent =
+        GeometryNodeAttributeDomainSizeComponent::Curve;
+    type Counts = CurveCounts;
+
+    fn counts(node: &GeometryNodeAttributeDomainSize) -> CurveCounts {
+        CurveCounts {
+            points: node.out_point_count(),
+            splines: node.out_spline_count(),
+        }
+    }
+}
+
+impl Component for Instances {
+    const COMPONENT: GeometryNodeAttributeDomainSizeComponent =
+        GeometryNodeAttributeDomainSizeComponent::Instances;
+    /// The instance count.
+    type Counts = NodeSocket<Int>;
+
+    fn counts(node: &GeometryNodeAttributeDomainSize) -> NodeSocket<Int> {
+        node.out_instance_count()
+    }
+}
+
+/// Counts the elements of the `component` of `geo` (`GeometryNodeAttributeDomainSize`).
+pub fn domain_size<C: Component>(geo: impl Into<NodeSocket<Geo>>, _component: C) -> C::Counts {
+    let node = GeometryNodeAttributeDomainSize::new()
+        .with_component(C::COMPONENT)
+        .with_geometry(geo.into());
+    C::counts(&node)
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::{self, NodeData, test_utils::GLOBAL_TEST_LOCK};
+
+    fn geo() -> NodeSocket<Geo> {
+        NodeSocket::new_output("grid.outputs[0]")
+    }
+
+    /// The single node of `nodes`, checked to count `component` of `geo()`.
+    fn domain_size_node<'a>(nodes: &'a [NodeData], component: &str) -> &'a NodeData {
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeAttributeDomainSize");
+        assert_eq!(node.properties["component"], format!("\"{}\"", component));
+        assert_eq!(
+            node.inputs[&GeometryNodeAttributeDomainSize::PIN_GEOMETRY][0].expr,
+            "grid.outputs[0]"
+        );
+        node
+    }
+
+    #[test]
+    fn test_mesh_domain_size() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+        let counts = domain_size(geo(), Mesh);
+        let nodes = context::exit_zone();
+
+        let name = &domain_size_node(&nodes, "MESH").name;
+        let output = |socket: &str| format!("{}.outputs[\"{}\"]", name, socket);
+        assert_eq!(counts.points.python_expr(), output("Point Count"));
+        assert_eq!(counts.edges.python_expr(), output("Edge Count"));
+        assert_eq!(counts.faces.python_expr(), output("Face Count"));
+        assert_eq!(counts.corners.python_expr(), output("Face Corner Count"));
+    }
+
+    #[test]
+    fn test_curve_and_instance_domain_sizes() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+        let counts = domain_size(geo(), Curve);
+        let nodes = context::exit_zone();
+
+        let name = &domain_size_node(&nodes, "CURVE").name;
+        assert_eq!(
+            counts.points.python_expr(),
+            format!("{}.outputs[\"Point Count\"]", name)
+        );
+        assert_eq!(
+            counts.splines.python_expr(),
+            format!("{}.outputs[\"Spline Count\"]", name)
+        );
+
+        context::enter_zone();
+        let count = domain_size(geo(), Instances);
+        let nodes = context::exit_zone();
+        let name = &domain_size_node(&nodes, "INSTANCES").name;
+        assert_eq!(
+            count.python_expr(),
+            format!("{}.outputs[\"Instance Count\"]", name)
+        );
+
+        context::enter_zone();
+        let count = domain_size(geo(), PointCloud);
+        let nodes = context::exit_zone();
+        let name = &domain_size_node(&nodes, "POINTCLOUD").name;
+        assert_eq!(
+            count.python_expr(),
+            format!("{}.outputs[\"Point Count\"]", name)
+        );
+    }
+}
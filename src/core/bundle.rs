@@ -0,0 +1,196 @@
+//! # Bundles
+//!
+//! A bundle carries named values of any type through a single socket. Combine and Separate
+//! Bundle nodes have no items until they are registered, so these helpers register one per
+//! value, in call order:
+//!
+//! ```ignore
+//! let params = bundle::combine()
+//!     .item("Radius", radius)
+//!     .item("Offset", offset)
+//!     .out_bundle();
+//! // ...
+//! let mut fields = bundle::separate(params);
+//! let radius = fields.get::<Float>("Radius");
+//! ```
+//!
+//! Items are matched by name, so `get` has to use the names (and types) given to `item`.
+
+use crate::core::context::update_post_creation;
+use crate::core::nodes::{NodeCombineBundle, NodeSeparateBundle};
+use crate::core::types::{Bundle, NodeSocket, SocketDef, python_string_literal};
+use crate::core::zone::add_custom_link;
+use std::fmt::Write;
+
+/// Appends `<node>.bundle_items.new(...)` to `post_code` and re-registers the script.
+fn register_item<T: SocketDef>(node: &str, name: &str, post_code: &mut String) {
+    let _ = writeln!(
+        post_code,
+        "{}.bundle_items.new('{}', {})",
+        node,
+        T::socket_type(),
+        python_string_literal(name)
+    );
+    update_post_creation(node, post_code.as_str());
+}
+
+fn clear_items(node: &str) -> String {
+    let post_code = format!("{}.bundle_items.clear()\n", node);
+    update_post_creation(node, post_code.as_str());
+    post_code
+}
+
+/// Combine Bundle node being filled; see `combine`.
+pub struct BundleBuilder {
+    node: NodeCombineBundle,
+    names: Vec<String>,
+    post_code: String,
+}
+
+/// Starts a Combine Bundle node; add values with `item` and take `out_bundle`.
+pub fn combine() -> BundleBuilder {
+    let node = NodeCombineBundle::new();
+    let post_code = clear_items(&node.name);
+    BundleBuilder {
+        node,
+        names: Vec::new(),
+        post_code,
+    }
+}
+
+impl BundleBuilder {
+    /// Adds `socket` to the bundle as the item `name`.
+    pub fn item<T: SocketDef>(mut self, name: &str, socket: NodeSocket<T>) -> Self {
+        assert!(
+            !self.names.iter().any(|n| n == name),
+            "bundle item '{}' is added twice",
+            name
+        );
+        register_item::<T>(&self.node.name, name, &mut self.post_code);
+        add_custom_link(&socket, &self.node.name, self.names.len());
+        self.names.push(name.to_string());
+        self
+    }
+
+    pub fn out_bundle(&self) -> NodeSocket<Bundle> {
+        self.node.out_bundle()
+    }
+}
+
+/// Separate Bundle node reading items by name; see `separate`.
+pub struct SeparatedBundle {
+    node: NodeSeparateBundle,
+    items: Vec<(String, &'static str)>,
+    post_code: String,
+}
+
+/// Separate Bundle node reading `bundle`; get its items with `get`.
+pub fn separate(bundle: impl Into<NodeSocket<Bundle>>) -> SeparatedBundle {
+    let node = NodeSeparateBundle::new().with_bundle(bundle.into());
+    let post_code = clear_items(&node.name);
+    SeparatedBundle {
+        node,
+        items: Vec::new(),
+        post_code,
+    }
+}
+
+impl SeparatedBundle {
+    /// The item `name` of the bundle. Reading the same item again reuses its output.
+    pub fn get<T: SocketDef>(&mut self, name: &str) -> NodeSocket<T> {
+        match self.items.iter().find(|(n, _)| n == name) {
+            Some((_, socket_type)) => assert_eq!(
+                *socket_type,
+                T::socket_type(),
+                "bundle item '{}' is read as two different types",
+                name
+            ),
+            None => {
+                register_item::<T>(&self.node.name, name, &mut self.post_code);
+                self.items.push((name.to_string(), T::socket_type()));
+            }
+        }
+        NodeSocket::new_output(format!(
+            "{}.outputs[{}]",
+            self.node.name,
+            python_string_literal(name)
+        ))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::{self, test_utils::GLOBAL_TEST_LOCK};
+    use crate::core::types::{Float, Geo, Vector};
+
+    #[test]
+    fn test_combine_registers_items_in_order() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let radius = NodeSocket::<Float>::new_output("radius.outputs[0]");
+        let geo = NodeSocket::<Geo>::new_output("mesh.outputs[0]");
+        let bundle = combine()
+            .item("Radius", radius)
+            .item("Mesh", geo)
+            .item("Offset", NodeSocket::<Vector>::from((0.0, 0.0, 1.0)))
+            .out_bundle();
+
+        let nodes = context::exit_zone();
+        let node = &nodes[0];
+        let name = &node.name;
+        assert_eq!(
+            node.post_creation_script,
+            format!(
+                "{name}.bundle_items.clear()\n\
+                 {name}.bundle_items.new('FLOAT', \"Radius\")\n\
+                 {name}.bundle_items.new('GEOMETRY', \"Mesh\")\n\
+                 {name}.bundle_items.new('VECTOR', \"Offset\")\n"
+            )
+        );
+        assert!(node.custom_links_script.contains(&format!(
+            "tree.links.new(radius.outputs[0], {name}.inputs[0])"
+        )));
+        assert!(node.custom_links_script.contains(&format!(
+            "tree.links.new(mesh.outputs[0], {name}.inputs[1])"
+        )));
+        assert!(node.custom_links_script.contains(&format!(
+            "{name}.inputs[2].default_value = (0.0000, 0.0000, 1.0000)"
+        )));
+        assert_eq!(bundle.python_expr(), format!("{name}.outputs[\"Bundle\"]"));
+    }
+
+    #[test]
+    fn test_separate_typed_accessors() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let mut fields = separate(NodeSocket::<Bundle>::new_output("params.outputs[0]"));
+        let radius = fields.get::<Float>("Radius");
+        let mesh = fields.get::<Geo>("Mesh");
+        let again = fields.get::<Float>("Radius");
+
+        let nodes = context::exit_zone();
+        let node = &nodes[0];
+        let name = &node.name;
+        assert_eq!(radius.python_expr(), format!("{name}.outputs[\"Radius\"]"));
+        assert_eq!(mesh.python_expr(), format!("{name}.outputs[\"Mesh\"]"));
+        assert_eq!(again.python_expr(), radius.python_expr());
+        assert_eq!(
+            node.post_creation_script,
+            format!(
+                "{name}.bundle_items.clear()\n\
+                 {name}.bundle_items.new('FLOAT', \"Radius\")\n\
+                 {name}.bundle_items.new('GEOMETRY', \"Mesh\")\n"
+            )
+        );
+        assert_eq!(
+            node.inputs[&NodeSeparateBundle::PIN_BUNDLE][0].expr,
+            "params.outputs[0]"
+        );
+    }
+}
@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+use crate::core::diagnostics::{Diagnostic, DiagnosticKind, Severity, is_output_node};
+use crate::core::meta;
+use crate::core::types::NodeSocket;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Write;
 use std::sync::{LazyLock, Mutex};
 
@@ -68,7 +72,6 @@ impl NodeData {
             );
         }
 
-        code.push_str(&self.post_creation_script);
         code
     }
 
@@ -78,28 +81,160 @@ impl NodeData {
         }
 
         let mut code = String::new();
+        for (_, line) in self.input_links() {
+            code.push_str(&line);
+        }
+
+        code.push_str(&self.custom_links_script);
+        code
+    }
+
+    /// The `tree.links.new(...)` lines for the linked inputs, each with its source expression,
+    /// in `links_script` order. `custom_links_script` is not included.
+    pub(crate) fn input_links(&self) -> Vec<(&str, String)> {
+        let mut links = Vec::new();
+        if self.bl_idname.is_empty() {
+            return links;
+        }
         for (idx, inputs_vec) in &self.inputs {
             for InputValue { expr, is_literal } in inputs_vec {
                 if !*is_literal {
-                    let _ = writeln!(
-                        &mut code,
-                        "tree.links.new({}, {}.inputs[{}])",
-                        expr, self.name, idx
-                    );
+                    links.push((
+                        expr.as_str(),
+                        format!("tree.links.new({}, {}.inputs[{}])\n", expr, self.name, idx),
+                    ));
                 }
             }
         }
-
-        code.push_str(&self.custom_links_script);
-        code
+        links
     }
 }
 
 pub type Scope = Vec<NodeData>;
 
+/// Node types `with_expression_cache` never merges: zone pairs and nodes that matter for their
+/// side effects rather than their outputs (output nodes are excluded as well).
+const UNCACHEABLE_NODES: &[&str] = &[
+    "GeometryNodeRepeatInput",
+    "GeometryNodeRepeatOutput",
+    "GeometryNodeSimulationInput",
+    "GeometryNodeSimulationOutput",
+    "GeometryNodeForeachGeometryElementInput",
+    "GeometryNodeForeachGeometryElementOutput",
+    "GeometryNodeBake",
+    "GeometryNodeWarning",
+    "NodeFrame",
+];
+
+/// Identity of a node for `with_expression_cache`: type, properties and ordered inputs.
+/// `None` for nodes that must never be merged.
+fn expression_key(node: &NodeData) -> Option<String> {
+    if UNCACHEABLE_NODES.contains(&node.bl_idname.as_str())
+        || is_output_node(node)
+        || !node.post_creation_script.is_empty()
+        || !node.custom_links_script.is_empty()
+    {
+        return None;
+    }
+    let properties: BTreeMap<_, _> = node.properties.iter().collect();
+    let inputs: BTreeMap<_, Vec<_>> = node
+        .inputs
+        .iter()
+        .map(|(index, values)| {
+            let values = values.iter().map(|v| (&v.expr, v.is_literal)).collect();
+            (index, values)
+        })
+        .collect();
+    let output_defaults: BTreeMap<_, _> = node.output_defaults.iter().collect();
+    Some(format!(
+        "{}|{:?}|{:?}|{:?}",
+        node.bl_idname, properties, inputs, output_defaults
+    ))
+}
+
+/// Rewrites `<node>.outputs[...]` so that it reads from the node `node` was merged into.
+fn resolve_alias(expr: &str, aliases: &HashMap<String, String>) -> Option<String> {
+    let (node, rest) = expr.split_once(".outputs[")?;
+    let target = aliases.get(node)?;
+    Some(format!("{}.outputs[{}", target, rest))
+}
+
+fn rewrite_references(node: &mut NodeData, aliases: &HashMap<String, String>) {
+    for value in node.inputs.values_mut().flatten() {
+        if !value.is_literal
+            && let Some(expr) = resolve_alias(&value.expr, aliases)
+        {
+            value.expr = expr;
+        }
+    }
+    replace_aliases(&mut node.post_creation_script, aliases);
+    replace_aliases(&mut node.custom_links_script, aliases);
+}
+
+/// Node names are unique (uuid suffix), so plain substring replacement is safe in scripts.
+fn replace_aliases(script: &mut String, aliases: &HashMap<String, String>) {
+    for (from, to) in aliases {
+        if script.contains(from.as_str()) {
+            *script = script.replace(from.as_str(), to);
+        }
+    }
+}
+
+/// Callback run once when the scope it was registered in is exited.
+pub type CleanupHook = Box<dyn FnOnce() + Send>;
+
 pub struct BuildContext {
     nodes: HashMap<String, NodeData>,
     stack: Vec<Vec<String>>,
+    /// Shared input nodes (e.g. Position) per scope frame, keyed by bl_idname.
+    input_cache: Vec<HashMap<String, String>>,
+    /// Cleanup hooks per scope frame.
+    cleanup_hooks: Vec<Vec<CleanupHook>>,
+    /// Hooks of exited scopes, waiting to be run outside the context lock.
+    pending_cleanup: Vec<CleanupHook>,
+    /// Python modules that expressions of the tree being built need besides `bpy` (e.g. `mathutils`).
+    required_imports: BTreeSet<&'static str>,
+    /// Diagnostics raised while building (e.g. lossy literals), waiting to be attached to a tree.
+    diagnostics: Vec<Diagnostic>,
+    /// Group calls of the tree a project is building; `None` outside a project.
+    group_calls: Option<Vec<GroupCall>>,
+    /// Nodes merged by `with_expression_cache`, mapped to the node kept in their place.
+    /// Cleared when the outermost scope (the tree being collected) is exited.
+    aliases: HashMap<String, String>,
+    /// Most nodes the context may hold at once; see `set_node_limit`.
+    node_limit: usize,
+    /// Script-time checks from `core::debug`, waiting to be emitted by the tree being built.
+    debug_statements: Vec<String>,
+    /// How `core::param` params are read in the tree being built; `None` outside a build.
+    param_target: Option<ParamTarget>,
+    /// Params read by the tree being built, in first-use order.
+    used_params: Vec<ParamUse>,
+}
+
+/// A group node calling `group`, recorded for `BlenderProject` to check against its items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupCall {
+    pub node: String,
+    pub group: String,
+}
+
+/// How the tree being built reads a `Param`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamTarget {
+    /// From a group input declared for the param (geometry trees and groups).
+    GroupInput,
+    /// From a value node driven by a custom property of the material (shader trees).
+    MaterialDriver,
+    /// The tree can't expose it; the default is used as a plain literal.
+    Literal,
+}
+
+/// A `Param` read by the tree being built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamUse {
+    pub name: String,
+    pub blender_type: String,
+    pub default_expr: String,
 }
 
 impl BuildContext {
@@ -107,9 +242,148 @@ impl BuildContext {
         Self {
             nodes: HashMap::new(),
             stack: vec![Vec::new()],
+            input_cache: vec![HashMap::new()],
+            cleanup_hooks: vec![Vec::new()],
+            pending_cleanup: Vec::new(),
+            required_imports: BTreeSet::new(),
+            diagnostics: Vec::new(),
+            group_calls: None,
+            aliases: HashMap::new(),
+            node_limit: DEFAULT_NODE_LIMIT,
+            debug_statements: Vec::new(),
+            param_target: None,
+            used_params: Vec::new(),
+        }
+    }
+
+    pub fn require_import(&mut self, module: &'static str) {
+        self.required_imports.insert(module);
+    }
+
+    /// Installs the required imports of a new build, returning the ones it replaces.
+    pub fn swap_required_imports(
+        &mut self,
+        imports: BTreeSet<&'static str>,
+    ) -> BTreeSet<&'static str> {
+        std::mem::replace(&mut self.required_imports, imports)
+    }
+
+    /// Records `diagnostic` unless the same one is already pending.
+    pub fn report(&mut self, diagnostic: Diagnostic) {
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    pub fn push_debug_statement(&mut self, statement: String) {
+        self.debug_statements.push(statement);
+    }
+
+    pub fn take_debug_statements(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.debug_statements)
+    }
+
+    /// Records a use of `param` and returns how to read it. A param is recorded once per build;
+    /// using the same name with another type or default is an error.
+    pub fn use_param(&mut self, param: ParamUse) -> Result<ParamTarget, String> {
+        let Some(target) = self.param_target else {
+            return Ok(ParamTarget::GroupInput);
+        };
+        match self.used_params.iter().find(|p| p.name == param.name) {
+            Some(used) if *used != param => Err(format!(
+                "param '{}' is used as {} = {} and as {} = {}",
+                param.name,
+                used.blender_type,
+                used.default_expr,
+                param.blender_type,
+                param.default_expr
+            )),
+            Some(_) => Ok(target),
+            None => {
+                self.used_params.push(param);
+                Ok(target)
+            }
         }
     }
 
+    /// Installs the param state of a new build, returning the one it replaces.
+    pub fn swap_params(
+        &mut self,
+        target: Option<ParamTarget>,
+        used: Vec<ParamUse>,
+    ) -> (Option<ParamTarget>, Vec<ParamUse>) {
+        let previous = (self.param_target, std::mem::take(&mut self.used_params));
+        self.param_target = target;
+        self.used_params = used;
+        previous
+    }
+
+    /// Records `call` if group calls are being recorded (see `record_group_calls`).
+    pub fn push_group_call(&mut self, call: GroupCall) {
+        if let Some(calls) = &mut self.group_calls {
+            calls.push(call);
+        }
+    }
+
+    /// Starts (`true`) or stops recording group calls, returning the ones recorded so far.
+    pub fn record_group_calls(&mut self, record: bool) -> Vec<GroupCall> {
+        let calls = self.group_calls.take().unwrap_or_default();
+        if record {
+            self.group_calls = Some(Vec::new());
+        }
+        calls
+    }
+
+    /// Merges each node of the current scope from position `start` on into the first earlier
+    /// one (from `start` on) with the same `expression_key`, and redirects every reference.
+    pub fn merge_duplicates(&mut self, start: usize) {
+        let Some(frame) = self.stack.last() else {
+            return;
+        };
+        let candidates = frame.get(start..).unwrap_or_default().to_vec();
+
+        let mut first_by_key: HashMap<String, String> = HashMap::new();
+        for name in candidates {
+            let Some(node) = self.nodes.get_mut(&name) else {
+                continue;
+            };
+            // Creation order: inputs from merged nodes are already redirected here.
+            rewrite_references(node, &self.aliases);
+            if let Some(key) = expression_key(node) {
+                match first_by_key.entry(key) {
+                    Entry::Occupied(first) => {
+                        self.aliases.insert(name, first.get().clone());
+                    }
+                    Entry::Vacant(slot) => {
+                        slot.insert(name);
+                    }
+                }
+            }
+        }
+
+        if let Some(frame) = self.stack.last_mut() {
+            frame.retain(|name| !self.aliases.contains_key(name));
+        }
+        self.nodes
+            .retain(|name, _| !self.aliases.contains_key(name));
+        for node in self.nodes.values_mut() {
+            rewrite_references(node, &self.aliases);
+        }
+    }
+
+    pub fn set_node_limit(&mut self, limit: usize) {
+        self.node_limit = limit;
+    }
+
+    /// True if adding another node would exceed the node limit.
+    pub fn is_at_node_limit(&self) -> bool {
+        self.nodes.len() >= self.node_limit
+    }
+
     pub fn add_node(&mut self, data: NodeData) {
         let name = data.name.clone();
         self.nodes.insert(name.clone(), data);
@@ -132,14 +406,26 @@ impl BuildContext {
         val: impl Into<String>,
         is_literal: bool,
     ) {
-        if let Some(node) = self.nodes.get_mut(name) {
-            node.inputs.insert(
-                index,
-                vec![InputValue {
-                    expr: val.into(),
-                    is_literal,
-                }],
-            );
+        let expr = self.resolve_input(val.into(), is_literal);
+        let Some(node) = self.nodes.get_mut(name) else {
+            return;
+        };
+        let bl_idname = node.bl_idname.clone();
+        let replaced = node
+            .inputs
+            .insert(index, vec![InputValue { expr, is_literal }])
+            .is_some();
+        self.check_input_index(name, &bl_idname, index);
+        if replaced {
+            self.report(Diagnostic {
+                severity: Severity::Warning,
+                kind: DiagnosticKind::InputSetTwice,
+                node: name.to_string(),
+                message: format!(
+                    "{} is set twice, the last value is used",
+                    meta::describe_input(&bl_idname, index)
+                ),
+            });
         }
     }
 
@@ -150,14 +436,88 @@ impl BuildContext {
         val: impl Into<String>,
         is_literal: bool,
     ) {
-        if let Some(node) = self.nodes.get_mut(name) {
-            node.inputs.entry(index).or_default().push(InputValue {
-                expr: val.into(),
-                is_literal,
+        let expr = self.resolve_input(val.into(), is_literal);
+        let Some(node) = self.nodes.get_mut(name) else {
+            return;
+        };
+        let bl_idname = node.bl_idname.clone();
+        node.inputs
+            .entry(index)
+            .or_default()
+            .push(InputValue { expr, is_literal });
+        self.check_input_index(name, &bl_idname, index);
+    }
+
+    /// Reports a `socket_type` socket set on input `index` of node `name` where Blender can't
+    /// convert it: menus only take menus (and vice versa). Other pairs convert implicitly or
+    /// fail visibly, so they are not checked yet; neither are nodes missing from `core::meta`.
+    pub fn check_input_type(&mut self, name: &str, index: usize, socket_type: &str) {
+        let Some(node) = self.nodes.get(name) else {
+            return;
+        };
+        let Some(pin) = meta::by_bl_idname(&node.bl_idname).and_then(|e| e.inputs.get(index))
+        else {
+            return;
+        };
+        let is_menu = |socket_type: &str| socket_type == "NodeSocketMenu";
+        if is_menu(&pin.socket_type) != is_menu(socket_type) {
+            let message = format!(
+                "{} can't take a {} socket",
+                meta::describe_input(&node.bl_idname, index),
+                socket_type
+            );
+            self.report(Diagnostic {
+                severity: Severity::Error,
+                kind: DiagnosticKind::InputType,
+                node: name.to_string(),
+                message,
             });
         }
     }
 
+    /// Reports an `index` past the fixed inputs of the node (nodes with item or interface
+    /// sockets, and nodes missing from `core::meta`, are not checked).
+    fn check_input_index(&mut self, name: &str, bl_idname: &str, index: usize) {
+        let Some(entry) = meta::by_bl_idname(bl_idname) else {
+            return;
+        };
+        match entry.fixed_input_count() {
+            Some(count) if index >= count => {
+                let inputs: Vec<String> = (0..count)
+                    .map(|i| meta::describe_input(bl_idname, i))
+                    .collect();
+                self.report(Diagnostic {
+                    severity: Severity::Error,
+                    kind: DiagnosticKind::InputIndex,
+                    node: name.to_string(),
+                    message: format!(
+                        "input {} is out of range for {}, which has {}",
+                        index,
+                        bl_idname,
+                        inputs.join(", ")
+                    ),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Expression of input `index` on node `name` if it holds a single literal value.
+    pub fn get_input_literal(&self, name: &str, index: usize) -> Option<String> {
+        match self.nodes.get(name)?.inputs.get(&index)?.as_slice() {
+            [value] if value.is_literal => Some(value.expr.clone()),
+            _ => None,
+        }
+    }
+
+    /// Redirects a link to a node merged by `with_expression_cache`.
+    fn resolve_input(&self, expr: String, is_literal: bool) -> String {
+        if is_literal {
+            return expr;
+        }
+        resolve_alias(&expr, &self.aliases).unwrap_or(expr)
+    }
+
     pub fn update_output_default(&mut self, name: &str, index: usize, val: impl Into<String>) {
         if let Some(node) = self.nodes.get_mut(name) {
             node.output_defaults.insert(index, val.into());
@@ -171,18 +531,63 @@ impl BuildContext {
     }
 
     pub fn append_custom_link(&mut self, name: &str, script: &str) {
+        let mut script = script.to_string();
+        replace_aliases(&mut script, &self.aliases);
         if let Some(node) = self.nodes.get_mut(name) {
-            node.custom_links_script.push_str(script);
+            node.custom_links_script.push_str(&script);
+        }
+    }
+
+    /// Looks up a cached input node in the innermost scope only.
+    /// Nodes from enclosing scopes are never returned, since each scope ends up in its own tree.
+    pub fn cached_input(&self, bl_idname: &str) -> Option<String> {
+        self.input_cache
+            .last()
+            .and_then(|cache| cache.get(bl_idname))
+            .filter(|name| self.nodes.contains_key(*name))
+            .cloned()
+    }
+
+    pub fn cache_input(&mut self, bl_idname: &str, name: &str) {
+        if let Some(cache) = self.input_cache.last_mut() {
+            cache.insert(bl_idname.to_string(), name.to_string());
+        }
+    }
+
+    pub fn register_cleanup_hook(&mut self, hook: CleanupHook) {
+        if let Some(hooks) = self.cleanup_hooks.last_mut() {
+            hooks.push(hook);
+        }
+    }
+
+    /// Hooks queued by `exit_scope`/`take_root`, in registration order.
+    pub fn take_pending_cleanup(&mut self) -> Vec<CleanupHook> {
+        std::mem::take(&mut self.pending_cleanup)
+    }
+
+    /// Queues the hooks of the innermost scope without exiting it.
+    pub fn flush_cleanup(&mut self) {
+        if let Some(hooks) = self.cleanup_hooks.last_mut() {
+            self.pending_cleanup.append(hooks);
         }
     }
 
     pub fn enter_scope(&mut self) {
         self.stack.push(Vec::new());
+        self.input_cache.push(HashMap::new());
+        self.cleanup_hooks.push(Vec::new());
     }
 
     pub fn exit_scope(&mut self) -> Scope {
         if self.stack.len() > 1 {
+            self.input_cache.pop();
+            if let Some(hooks) = self.cleanup_hooks.pop() {
+                self.pending_cleanup.extend(hooks);
+            }
             let scope_names = self.stack.pop().unwrap();
+            if self.stack.len() == 1 {
+                self.aliases.clear();
+            }
             scope_names
                 .into_iter()
                 .filter_map(|name| self.nodes.remove(&name))
@@ -193,7 +598,11 @@ impl BuildContext {
     }
 
     pub fn take_root(&mut self) -> Scope {
+        self.input_cache[0].clear();
+        let root_hooks = std::mem::take(&mut self.cleanup_hooks[0]);
+        self.pending_cleanup.extend(root_hooks);
         let root_names = std::mem::take(&mut self.stack[0]);
+        self.aliases.clear();
         root_names
             .into_iter()
             .filter_map(|name| self.nodes.remove(&name))
@@ -201,6 +610,10 @@ impl BuildContext {
     }
 }
 
+/// Far more nodes than any hand-built tree has, but small enough to stop a runaway loop
+/// before it exhausts memory.
+pub const DEFAULT_NODE_LIMIT: usize = 1_000_000;
+
 /// **[WARNING: Logical Thread Safety]**
 ///
 /// `GLOBAL_CONTEXT` utilizes a `Mutex` to prevent memory corruption (data races),
@@ -222,8 +635,27 @@ impl BuildContext {
 pub static GLOBAL_CONTEXT: LazyLock<Mutex<BuildContext>> =
     LazyLock::new(|| Mutex::new(BuildContext::new()));
 
+/// Panics once the context holds more nodes than the node limit (`set_node_limit`).
 pub fn add_node(data: NodeData) {
-    GLOBAL_CONTEXT.lock().unwrap().add_node(data);
+    let limit = {
+        let mut ctx = GLOBAL_CONTEXT.lock().unwrap();
+        if !ctx.is_at_node_limit() {
+            ctx.add_node(data);
+            return;
+        }
+        ctx.node_limit
+    };
+    // Panic outside the lock, so the context stays usable for whoever catches it.
+    panic!(
+        "node limit of {} exceeded while adding '{}': is a Rust loop creating nodes that \
+         should be a repeat zone (`zone::repeat_zone`)? Raise it with `context::set_node_limit` \
+         if the tree really needs that many nodes",
+        limit, data.name
+    );
+}
+/// Caps the nodes the context may hold at once (`DEFAULT_NODE_LIMIT` by default).
+pub fn set_node_limit(limit: usize) {
+    GLOBAL_CONTEXT.lock().unwrap().set_node_limit(limit);
 }
 pub fn update_property(name: &str, key: &str, val: impl Into<String>) {
     GLOBAL_CONTEXT
@@ -237,17 +669,43 @@ pub fn update_input(name: &str, index: usize, val: impl Into<String>, is_literal
         .unwrap()
         .update_input(name, index, val, is_literal);
 }
+pub fn check_input_type(name: &str, index: usize, socket_type: &str) {
+    GLOBAL_CONTEXT
+        .lock()
+        .unwrap()
+        .check_input_type(name, index, socket_type);
+}
+
 pub fn append_input(name: &str, index: usize, val: impl Into<String>, is_literal: bool) {
     GLOBAL_CONTEXT
         .lock()
         .unwrap()
         .append_input(name, index, val, is_literal);
 }
-pub fn update_output_default(name: &str, index: usize, val: impl Into<String>) {
+/// Python expression of the literal set on input `index` of node `name`.
+/// `None` if the input is unset, linked, or holds several values (multi-input sockets).
+pub fn get_input_literal(name: &str, index: usize) -> Option<String> {
+    GLOBAL_CONTEXT
+        .lock()
+        .unwrap()
+        .get_input_literal(name, index)
+}
+/// Sets the default value of output `index` (named `socket`, for the panic message).
+///
+/// Output defaults are plain values, so `val` must be a literal; a linked socket would emit
+/// `node.outputs[i].default_value = other.outputs[...]`, which fails in Blender.
+pub fn update_output_default<T>(name: &str, index: usize, socket: &str, val: NodeSocket<T>) {
+    assert!(
+        val.is_literal,
+        "default of output '{}' on node '{}' must be a literal value, got the linked socket `{}`",
+        socket,
+        name,
+        val.python_expr()
+    );
     GLOBAL_CONTEXT
         .lock()
         .unwrap()
-        .update_output_default(name, index, val);
+        .update_output_default(name, index, val.python_expr());
 }
 pub fn update_post_creation(name: &str, script: impl Into<String>) {
     GLOBAL_CONTEXT
@@ -261,14 +719,138 @@ pub fn append_custom_link(name: &str, script: &str) {
         .unwrap()
         .append_custom_link(name, script);
 }
+pub fn cached_input(bl_idname: &str) -> Option<String> {
+    GLOBAL_CONTEXT.lock().unwrap().cached_input(bl_idname)
+}
+pub fn cache_input(bl_idname: &str, name: &str) {
+    GLOBAL_CONTEXT.lock().unwrap().cache_input(bl_idname, name);
+}
+/// Registers `hook` to run when the current scope is left via `exit_zone` or `take_root_nodes`
+/// (or earlier, via `run_cleanup_hooks`).
+///
+/// Hooks run after the context lock is released, so they may build nodes themselves.
+pub fn register_cleanup_hook(hook: impl FnOnce() + Send + 'static) {
+    GLOBAL_CONTEXT
+        .lock()
+        .unwrap()
+        .register_cleanup_hook(Box::new(hook));
+}
+/// Runs the hooks registered in the current scope now, instead of waiting for it to be exited.
+pub fn run_cleanup_hooks() {
+    let hooks = {
+        let mut ctx = GLOBAL_CONTEXT.lock().unwrap();
+        ctx.flush_cleanup();
+        ctx.take_pending_cleanup()
+    };
+    hooks.into_iter().for_each(|hook| hook());
+}
+/// Records that the script of the tree being built needs `import {module}` (see `NodeTree::build`).
+pub fn require_import(module: &'static str) {
+    GLOBAL_CONTEXT.lock().unwrap().require_import(module);
+}
+/// Installs the required imports of a new build, returning the ones it replaces.
+pub fn swap_required_imports(imports: BTreeSet<&'static str>) -> BTreeSet<&'static str> {
+    GLOBAL_CONTEXT
+        .lock()
+        .unwrap()
+        .swap_required_imports(imports)
+}
+/// Records a build warning; repeated identical diagnostics are kept once until drained.
+pub fn warn(kind: DiagnosticKind, message: String) {
+    GLOBAL_CONTEXT.lock().unwrap().report(Diagnostic {
+        severity: Severity::Warning,
+        kind,
+        node: String::new(),
+        message,
+    });
+}
+/// Records a build error on node `node`; it makes `NodeTree::build_checked` fail.
+pub fn report_error(kind: DiagnosticKind, node: &str, message: String) {
+    GLOBAL_CONTEXT.lock().unwrap().report(Diagnostic {
+        severity: Severity::Error,
+        kind,
+        node: node.to_string(),
+        message,
+    });
+}
+/// Drains the diagnostics recorded since the last call; trees attach them to their own.
+pub fn take_diagnostics() -> Vec<Diagnostic> {
+    GLOBAL_CONTEXT.lock().unwrap().take_diagnostics()
+}
+/// Records a script-time debug statement (see `core::debug`).
+pub fn push_debug_statement(statement: String) {
+    GLOBAL_CONTEXT
+        .lock()
+        .unwrap()
+        .push_debug_statement(statement);
+}
+/// Drains the debug statements recorded since the last call.
+pub fn take_debug_statements() -> Vec<String> {
+    GLOBAL_CONTEXT.lock().unwrap().take_debug_statements()
+}
+/// Records a use of `param`; panics if it conflicts with an earlier use of the same name.
+pub fn use_param(param: ParamUse) -> ParamTarget {
+    let result = GLOBAL_CONTEXT.lock().unwrap().use_param(param);
+    result.unwrap_or_else(|err| panic!("{}", err))
+}
+/// Installs the param state of a new build, returning the one it replaces.
+pub fn swap_params(
+    target: Option<ParamTarget>,
+    used: Vec<ParamUse>,
+) -> (Option<ParamTarget>, Vec<ParamUse>) {
+    GLOBAL_CONTEXT.lock().unwrap().swap_params(target, used)
+}
+/// Records that node `node` calls the group `group`; kept only while a project records them.
+pub fn push_group_call(node: &str, group: &str) {
+    GLOBAL_CONTEXT.lock().unwrap().push_group_call(GroupCall {
+        node: node.to_string(),
+        group: group.to_string(),
+    });
+}
+/// Starts (`true`) or stops recording group calls, returning the ones recorded so far.
+/// `BlenderProject` records them while building each of its trees.
+pub fn record_group_calls(record: bool) -> Vec<GroupCall> {
+    GLOBAL_CONTEXT.lock().unwrap().record_group_calls(record)
+}
+/// Runs `body` with expression caching: when it returns, each node it created that repeats an
+/// earlier node of the same call (same type, properties and ordered inputs) is removed, and
+/// everything linked to it reads from that first node instead.
+///
+/// This lets separate `ramen_math!` invocations (or hand-built chains) share common
+/// subexpressions. Sockets of merged nodes stay usable after `body` returns (until the tree is
+/// collected), since links to them are redirected as they are made. Output, zone and script-carrying nodes are never merged.
+pub fn with_expression_cache<R>(body: impl FnOnce() -> R) -> R {
+    let (depth, start) = {
+        let ctx = GLOBAL_CONTEXT.lock().unwrap();
+        (ctx.stack.len(), ctx.stack.last().map_or(0, Vec::len))
+    };
+    let result = body();
+    let mut ctx = GLOBAL_CONTEXT.lock().unwrap();
+    if ctx.stack.len() == depth {
+        ctx.merge_duplicates(start);
+    }
+    result
+}
 pub fn enter_zone() {
     GLOBAL_CONTEXT.lock().unwrap().enter_scope();
 }
 pub fn exit_zone() -> Scope {
-    GLOBAL_CONTEXT.lock().unwrap().exit_scope()
+    let (scope, hooks) = {
+        let mut ctx = GLOBAL_CONTEXT.lock().unwrap();
+        let scope = ctx.exit_scope();
+        (scope, ctx.take_pending_cleanup())
+    };
+    hooks.into_iter().for_each(|hook| hook());
+    scope
 }
 pub fn take_root_nodes() -> Scope {
-    GLOBAL_CONTEXT.lock().unwrap().take_root()
+    let (scope, hooks) = {
+        let mut ctx = GLOBAL_CONTEXT.lock().unwrap();
+        let scope = ctx.take_root();
+        (scope, ctx.take_pending_cleanup())
+    };
+    hooks.into_iter().for_each(|hook| hook());
+    scope
 }
 
 // ---------------------------------------------------------
@@ -276,8 +858,7 @@ pub fn take_root_nodes() -> Scope {
 // ---------------------------------------------------------
 #[cfg(test)]
 pub mod test_utils {
-    use std::sync::{LazyLock, Mutex};
-    pub static GLOBAL_TEST_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+    pub use crate::core::testing::TEST_LOCK as GLOBAL_TEST_LOCK;
 }
 
 #[cfg(test)]
@@ -315,6 +896,17 @@ mod tests {
         assert!(script.contains("math_1.outputs[0].default_value = 0.0"));
     }
 
+    #[test]
+    fn test_creation_script_excludes_post_creation() {
+        let mut node = NodeData::new("rep_in".to_string(), "GeometryNodeRepeatInput".to_string());
+        node.post_creation_script = "rep_in.pair_with_output(rep_out)\n".to_string();
+
+        let script = node.creation_script();
+
+        assert!(script.contains("rep_in = tree.nodes.new('GeometryNodeRepeatInput')"));
+        assert!(!script.contains("pair_with_output"));
+    }
+
     #[test]
     fn test_node_data_links_script() {
         let mut node = NodeData::new("math_1".to_string(), "ShaderNodeMath".to_string());
@@ -392,4 +984,299 @@ mod tests {
         assert_eq!(root_nodes.len(), 1);
         assert_eq!(root_nodes[0].name, "root_node");
     }
+
+    #[test]
+    fn test_input_cache_is_scoped() {
+        let mut ctx = BuildContext::new();
+
+        ctx.add_node(NodeData::new("pos_A".to_string(), "PosType".to_string()));
+        ctx.cache_input("PosType", "pos_A");
+        assert_eq!(ctx.cached_input("PosType").as_deref(), Some("pos_A"));
+
+        ctx.enter_scope();
+        assert_eq!(ctx.cached_input("PosType"), None);
+        ctx.add_node(NodeData::new("pos_B".to_string(), "PosType".to_string()));
+        ctx.cache_input("PosType", "pos_B");
+        assert_eq!(ctx.cached_input("PosType").as_deref(), Some("pos_B"));
+        let _ = ctx.exit_scope();
+
+        assert_eq!(ctx.cached_input("PosType").as_deref(), Some("pos_A"));
+        let _ = ctx.take_root();
+        assert_eq!(ctx.cached_input("PosType"), None);
+    }
+
+    #[test]
+    fn test_cleanup_hooks_run_on_scope_exit() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let outer_flag = Arc::new(AtomicBool::new(false));
+        let inner_flag = Arc::new(AtomicBool::new(false));
+
+        enter_zone();
+        let flag = outer_flag.clone();
+        register_cleanup_hook(move || flag.store(true, Ordering::SeqCst));
+
+        enter_zone();
+        let flag = inner_flag.clone();
+        register_cleanup_hook(move || flag.store(true, Ordering::SeqCst));
+        let _ = exit_zone();
+
+        assert!(inner_flag.load(Ordering::SeqCst));
+        assert!(!outer_flag.load(Ordering::SeqCst));
+
+        let _ = exit_zone();
+        assert!(outer_flag.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_run_cleanup_hooks_runs_once() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        enter_zone();
+        let counter = count.clone();
+        register_cleanup_hook(move || {
+            // Hooks run outside the lock, so touching the context must not deadlock.
+            add_node(NodeData::new("hook_node".to_string(), String::new()));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        run_cleanup_hooks();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        let scope = exit_zone();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(scope.iter().any(|n| n.name == "hook_node"));
+    }
+
+    #[test]
+    fn test_output_default_accepts_literals() {
+        use crate::core::nodes::{
+            CompositorNodeRgb, FunctionNodeBooleanMath, FunctionNodeInputString,
+            FunctionNodeIntegerMath, ShaderNodeCombineXyz, ShaderNodeValue,
+        };
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+
+        enter_zone();
+        ShaderNodeValue::new().default_value(0.95);
+        CompositorNodeRgb::new().default_color((1.0, 0.5, 0.0, 1.0));
+        FunctionNodeIntegerMath::new().default_value(3);
+        FunctionNodeBooleanMath::new().default_boolean(true);
+        FunctionNodeInputString::new().default_string("label");
+        ShaderNodeCombineXyz::new().default_vector((0.0, 0.0, 1.0));
+        let nodes = exit_zone();
+
+        let defaults: Vec<&str> = nodes
+            .iter()
+            .map(|n| n.output_defaults.get(&0).unwrap().as_str())
+            .collect();
+        assert_eq!(
+            defaults,
+            vec![
+                "0.9500",
+                "(1.0000, 0.5000, 0.0000, 1.0000)",
+                "3",
+                "True",
+                "\"label\"",
+                "(0.0000, 0.0000, 1.0000)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_output_default_by_index() {
+        use crate::core::nodes::ShaderNodeValue;
+        use crate::core::types::Float;
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+
+        enter_zone();
+        ShaderNodeValue::new().set_output_default(0, NodeSocket::<Float>::from(0.25));
+        let nodes = exit_zone();
+
+        assert!(nodes[0].creation_script().contains(&format!(
+            "{}.outputs[0].default_value = 0.2500",
+            nodes[0].name
+        )));
+    }
+
+    #[test]
+    fn test_input_diagnostics_name_sockets() {
+        use crate::core::nodes::{GeometryNodeGroup, ShaderNodeMath};
+        use crate::core::types::Float;
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+        let _ = take_diagnostics();
+
+        enter_zone();
+        let math = ShaderNodeMath::new()
+            .with_value(1.0)
+            .with_value(2.0)
+            .set_input(5, NodeSocket::<Float>::from(3.0));
+        // Group inputs come from the interface, so any index is accepted.
+        GeometryNodeGroup::new().set_input(5, NodeSocket::<Float>::from(3.0));
+        let _ = exit_zone();
+
+        let diagnostics = take_diagnostics();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::InputSetTwice);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].node, math.name);
+        assert_eq!(
+            diagnostics[0].message,
+            "input 0 ('Value', NodeSocketFloat) is set twice, the last value is used"
+        );
+        assert_eq!(diagnostics[1].kind, DiagnosticKind::InputIndex);
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(
+            diagnostics[1].message,
+            "input 5 is out of range for ShaderNodeMath, which has \
+             input 0 ('Value', NodeSocketFloat), input 1 ('Value', NodeSocketFloat), \
+             input 2 ('Value', NodeSocketFloat)"
+        );
+    }
+
+    #[test]
+    fn test_set_input_checked_guards_menu_pins() {
+        use crate::core::nodes::{GeometryNodeResampleCurve, GeometryNodeResampleCurveModeMenu};
+        use crate::core::types::{Float, Menu};
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+        let _ = take_diagnostics();
+        let mode = GeometryNodeResampleCurve::PIN_MODE;
+
+        enter_zone();
+        // The typed setter only takes menu values; `set_input` takes anything, unchecked.
+        GeometryNodeResampleCurve::new().with_mode(GeometryNodeResampleCurveModeMenu::Length);
+        GeometryNodeResampleCurve::new().set_input(mode, NodeSocket::<Float>::from(1.0));
+        assert!(take_diagnostics().is_empty());
+
+        GeometryNodeResampleCurve::new()
+            .set_input_checked(mode, NodeSocket::<Menu>::from("Count"))
+            .set_input_checked(
+                GeometryNodeResampleCurve::PIN_LENGTH,
+                NodeSocket::<Float>::from(0.1),
+            );
+        assert!(take_diagnostics().is_empty());
+
+        let miswired = GeometryNodeResampleCurve::new()
+            .set_input_checked(mode, NodeSocket::<Float>::from(1.0));
+        let _ = exit_zone();
+
+        let diagnostics = take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::InputType);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].node, miswired.name);
+        assert_eq!(
+            diagnostics[0].message,
+            "input 2 ('Mode', NodeSocketMenu) can't take a NodeSocketFloat socket"
+        );
+    }
+
+    #[test]
+    fn test_output_default_rejects_linked_socket() {
+        use crate::core::nodes::ShaderNodeValue;
+        use crate::core::types::Float;
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+
+        enter_zone();
+        let linked = NodeSocket::<Float>::new_output("other.outputs[0]");
+        let result = std::panic::catch_unwind(|| {
+            ShaderNodeValue::new().default_value(linked);
+        });
+        let nodes = exit_zone();
+
+        let err = result.unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        assert!(message.contains(&format!("on node '{}'", nodes[0].name)));
+        assert!(message.contains("output 'Value'"));
+        assert!(message.contains("other.outputs[0]"));
+        assert!(nodes[0].output_defaults.is_empty());
+    }
+
+    #[test]
+    fn test_expression_cache_merges_repeated_math() {
+        use crate::core::nodes::NodeGroupOutput;
+        use crate::core::types::Float;
+        use ramen_macros::ramen_math;
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+        let power_count = |nodes: &Scope| {
+            nodes
+                .iter()
+                .filter(|n| n.properties.get("operation").map(String::as_str) == Some("\"POWER\""))
+                .count()
+        };
+
+        enter_zone();
+        let x = NodeSocket::<Float>::new_output("src.outputs[0]");
+        let late = with_expression_cache(|| {
+            let a = ramen_math!(pow(x, 3.0));
+            let b = ramen_math!(pow(x, 3.0));
+            NodeGroupOutput::new().set_input(0, a + b);
+            NodeGroupOutput::new().set_input(0, a + b);
+            b
+        });
+        let after = ramen_math!(late * 3.0);
+        let nodes = exit_zone();
+        // The collected tree's aliases are dropped; later trees don't scan them.
+        assert!(GLOBAL_CONTEXT.lock().unwrap().aliases.is_empty());
+
+        assert_eq!(power_count(&nodes), 1);
+        let power = nodes
+            .iter()
+            .find(|n| n.bl_idname == "ShaderNodeMath")
+            .unwrap();
+        let add = nodes
+            .iter()
+            .find(|n| n.properties.get("operation").map(String::as_str) == Some("\"ADD\""))
+            .unwrap();
+        for index in [0, 1] {
+            assert!(add.inputs[&index][0].expr.starts_with(&power.name));
+        }
+        // Output nodes are never merged; the second `a + b` is.
+        let outputs: Vec<_> = nodes
+            .iter()
+            .filter(|n| n.bl_idname == "NodeGroupOutput")
+            .collect();
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(outputs[0].inputs[&0][0].expr, outputs[1].inputs[&0][0].expr);
+        // A socket of the merged node, linked after the cache scope ended.
+        let mul = nodes
+            .iter()
+            .find(|n| n.properties.get("operation").map(String::as_str) == Some("\"MULTIPLY\""))
+            .unwrap();
+        assert!(mul.inputs[&0][0].expr.starts_with(&power.name));
+        assert!(after.python_expr().starts_with(&mul.name));
+
+        enter_zone();
+        let _ = ramen_math!(pow(x, 3.0));
+        let _ = ramen_math!(pow(x, 3.0));
+        assert_eq!(power_count(&exit_zone()), 2);
+    }
+
+    #[test]
+    fn test_node_limit_panics_when_exceeded() {
+        use crate::core::types::Float;
+        let _lock = test_utils::GLOBAL_TEST_LOCK.lock().unwrap();
+
+        enter_zone();
+        set_node_limit(3);
+        let result = std::panic::catch_unwind(|| {
+            for _ in 0..5 {
+                let _ = NodeSocket::<Float>::from(1.0) + 2.0;
+            }
+        });
+        set_node_limit(DEFAULT_NODE_LIMIT);
+        let nodes = exit_zone();
+
+        let err = result.unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        assert!(message.contains("node limit of 3 exceeded"));
+        assert!(message.contains("repeat_zone"));
+        assert_eq!(nodes.len(), 3);
+    }
 }
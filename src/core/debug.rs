@@ -0,0 +1,137 @@
+//! # Script-Time Debugging
+//!
+//! Checks and prints that run in Blender while the generated script executes:
+//!
+//! ```ignore
+//! let radius = NodeSocket::<Float>::from(params.radius);
+//! debug::py_assert(radius, "value > 0");
+//! debug::py_print("radius", radius);
+//! ```
+//!
+//! Only literal sockets have a value at script time; for linked sockets a comment is emitted
+//! instead. Statements go to the post-creation phase of the tree being built and are stripped
+//! from projects built with `BlenderProject::release_scripts`.
+
+use crate::core::context::push_debug_statement;
+use crate::core::types::{NodeSocket, python_string_literal};
+
+/// Suffix of every emitted debug line, used to strip them from release scripts.
+pub const DEBUG_MARKER: &str = "# ramen:debug";
+
+/// Asserts the single-line Python expression `condition` over `value`, the value of `socket`.
+pub fn py_assert<T>(socket: NodeSocket<T>, condition: &str) {
+    assert!(
+        !condition.contains('\n'),
+        "py_assert condition must be a single line, got {:?}",
+        condition
+    );
+    let statement = if socket.is_literal {
+        format!(
+            "assert (lambda value: {})({}), {}",
+            condition,
+            socket.python_expr(),
+            python_string_literal(condition)
+        )
+    } else {
+        linked_note("py_assert", condition, &socket)
+    };
+    push_debug_statement(statement);
+}
+
+/// Prints `label` and the value of `socket` to Blender's console.
+pub fn py_print<T>(label: &str, socket: NodeSocket<T>) {
+    let statement = if socket.is_literal {
+        format!(
+            "print({}, {})",
+            python_string_literal(label),
+            socket.python_expr()
+        )
+    } else {
+        linked_note("py_print", label, &socket)
+    };
+    push_debug_statement(statement);
+}
+
+fn linked_note<T>(function: &str, label: &str, socket: &NodeSocket<T>) -> String {
+    format!(
+        "# {}({}): {} is linked and can't be evaluated at script time",
+        function,
+        python_string_literal(label),
+        socket.python_expr()
+    )
+}
+
+/// The statement as emitted, tagged with `DEBUG_MARKER`.
+pub(crate) fn tag_debug_line(statement: &str) -> String {
+    format!("{}  {}\n", statement, DEBUG_MARKER)
+}
+
+/// `script` without the lines emitted by this module.
+pub(crate) fn strip_debug_lines(script: &str) -> String {
+    script
+        .split_inclusive('\n')
+        .filter(|line| !line.trim_end().ends_with(DEBUG_MARKER))
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::nodes::NodeGroupOutput;
+    use crate::core::project::BlenderProject;
+    use crate::core::tree::NodeTree;
+    use crate::core::types::Float;
+
+    fn debug_tree() {
+        let radius = NodeSocket::<Float>::from(0.5);
+        py_assert(radius, "value > 0");
+        py_print("radius \"r\"\nnext", radius);
+        py_print("linked", NodeSocket::<Float>::new_output("src.outputs[0]"));
+        NodeGroupOutput::new();
+    }
+
+    #[test]
+    fn test_debug_statements_in_post_creation_phase() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let script = NodeTree::new_geometry("Debug").build(debug_tree);
+
+        let post = script.find("# --- Node Post Creation Phase ---").unwrap();
+        let linking = script.find("# --- Node Linking Phase ---").unwrap();
+        let assert_line = script
+            .find("assert (lambda value: value > 0)(0.5000), \"value > 0\"  # ramen:debug\n")
+            .unwrap();
+        assert!(post < assert_line && assert_line < linking);
+        assert!(script.contains("print(\"radius \\\"r\\\"\\nnext\", 0.5000)  # ramen:debug\n"));
+        assert!(script.contains(
+            "# py_print(\"linked\"): src.outputs[0] is linked and can't be evaluated at script time"
+        ));
+
+        // Drained by the build: the next tree starts clean.
+        let next = NodeTree::new_geometry("Clean").build(|| {});
+        assert!(!next.contains(DEBUG_MARKER));
+    }
+
+    #[test]
+    fn test_release_scripts_strip_debug_lines() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let debug = BlenderProject::new().add_geometry_tree("Debug", debug_tree);
+        assert_eq!(
+            debug.try_build_all().unwrap().matches(DEBUG_MARKER).count(),
+            3
+        );
+
+        let release = BlenderProject::new()
+            .release_scripts()
+            .add_geometry_tree("Debug", debug_tree);
+        let script = release.try_build_all().unwrap();
+        assert!(!script.contains(DEBUG_MARKER));
+        assert!(!script.contains("print("));
+        assert!(script.contains("# --- Node Post Creation Phase ---\n"));
+    }
+}
@@ -0,0 +1,627 @@
+//! # Tree Diagnostics
+//!
+//! Validation passes over the nodes of a single tree, used by `NodeTree::build_checked`.
+//! Every pass reports into one `TreeDiagnostics` instead of panicking, so that all problems
+//! of a tree can be shown at once. Only errors make a checked build fail; warnings are
+//! informational.
+//!
+//! The graph passes here only look at `NodeData` (links are recovered from the
+//! `<node>.outputs[...]` expressions). Checks against the tree interface live in `tree.rs`.
+
+use crate::core::context::{self, NodeData};
+use crate::core::meta;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[path = "../../ramen_macros/src/edit_distance.rs"]
+mod edit_distance;
+use edit_distance::edit_distance;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A link reads from a node that is not part of this tree (e.g. a socket leaked from another tree).
+    UnknownNode,
+    /// Links form a cycle.
+    Cycle,
+    /// A node whose outputs are never used and which is not an output node.
+    OrphanNode,
+    /// The tree has no output node at all.
+    MissingOutput,
+    /// A group input/output socket is used that the tree interface does not declare.
+    InterfaceMismatch,
+    /// Project items could not be ordered.
+    Dependency,
+    /// The tree has more nodes than its budget allows.
+    NodeBudget,
+    /// A sequential chain of linked nodes is long enough to evaluate slowly in Blender.
+    DeepChain,
+    /// A float literal is emitted with too few digits to represent its value.
+    PrecisionLoss,
+    /// A group node refers to a tree the project does not contain.
+    UnknownGroup,
+    /// The tree's builder panicked.
+    BuilderPanic,
+    /// Two project items share a name but not a script.
+    DuplicateItem,
+    /// An input index the node does not have.
+    InputIndex,
+    /// An input is set twice; the last value wins.
+    InputSetTwice,
+    /// A geometry input is left unlinked, so the node works on empty geometry.
+    MissingInput,
+    /// A socket is set on an input of a type Blender can't convert it to.
+    InputType,
+}
+
+impl DiagnosticKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnknownNode => "unknown-node",
+            Self::Cycle => "cycle",
+            Self::OrphanNode => "orphan-node",
+            Self::MissingOutput => "missing-output",
+            Self::InterfaceMismatch => "interface-mismatch",
+            Self::Dependency => "dependency",
+            Self::NodeBudget => "node-budget",
+            Self::DeepChain => "deep-chain",
+            Self::PrecisionLoss => "precision-loss",
+            Self::UnknownGroup => "unknown-group",
+            Self::BuilderPanic => "builder-panic",
+            Self::DuplicateItem => "duplicate-item",
+            Self::InputIndex => "input-index",
+            Self::InputSetTwice => "input-set-twice",
+            Self::MissingInput => "missing-input",
+            Self::InputType => "input-type",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    /// Name of the offending node (empty for tree-level problems).
+    pub node: String,
+    pub message: String,
+}
+
+/// All diagnostics reported for one tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeDiagnostics {
+    pub tree: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TreeDiagnostics {
+    pub fn new(tree: &str) -> Self {
+        Self {
+            tree: tree.to_string(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn warning(&mut self, kind: DiagnosticKind, node: &str, message: impl Into<String>) {
+        self.push(Severity::Warning, kind, node, message);
+    }
+
+    pub fn error(&mut self, kind: DiagnosticKind, node: &str, message: impl Into<String>) {
+        self.push(Severity::Error, kind, node, message);
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        kind: DiagnosticKind,
+        node: &str,
+        message: impl Into<String>,
+    ) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            kind,
+            node: node.to_string(),
+            message: message.into(),
+        });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+impl fmt::Display for TreeDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "tree '{}': {} error(s), {} warning(s)",
+            self.tree,
+            self.errors().count(),
+            self.warnings().count()
+        )?;
+        for d in &self.diagnostics {
+            let severity = match d.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            if d.node.is_empty() {
+                writeln!(f, "  {}[{}]: {}", severity, d.kind.as_str(), d.message)?;
+            } else {
+                writeln!(
+                    f,
+                    "  {}[{}] {}: {}",
+                    severity,
+                    d.kind.as_str(),
+                    d.node,
+                    d.message
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TreeDiagnostics {}
+
+/// Node types that consume their inputs without feeding other nodes.
+const OUTPUT_NODES: &[&str] = &[
+    "NodeGroupOutput",
+    "ShaderNodeOutputMaterial",
+    "ShaderNodeOutputWorld",
+    "ShaderNodeOutputLight",
+    "ShaderNodeOutputAOV",
+    "CompositorNodeComposite",
+    "CompositorNodeViewer",
+    "CompositorNodeOutputFile",
+    "GeometryNodeViewer",
+];
+
+pub(crate) fn is_output_node(node: &NodeData) -> bool {
+    OUTPUT_NODES.contains(&node.bl_idname.as_str())
+}
+
+/// One `<node>.outputs[<key>]` reference; `key` is the raw Python subscript (`0` or `"Name"`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OutputRef {
+    pub node: String,
+    pub key: String,
+}
+
+/// Extracts every `<node>.outputs[<key>]` reference from a Python expression or script.
+pub(crate) fn output_refs(expr: &str) -> Vec<OutputRef> {
+    const PATTERN: &str = ".outputs[";
+    let mut refs = Vec::new();
+
+    for (pos, _) in expr.match_indices(PATTERN) {
+        let head = &expr[..pos];
+        let start = head
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .map_or(0, |i| i + 1);
+        let node = &head[start..];
+        if node.is_empty() {
+            continue;
+        }
+
+        let tail = &expr[pos + PATTERN.len()..];
+        let key_len = if tail.starts_with('"') {
+            // Skip the quoted name, honoring backslash escapes.
+            let mut escaped = false;
+            tail.char_indices()
+                .skip(1)
+                .find(|&(_, c)| {
+                    let closes = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closes
+                })
+                .map(|(i, _)| i + 1)
+        } else {
+            tail.find(']')
+        };
+
+        if let Some(len) = key_len {
+            refs.push(OutputRef {
+                node: node.to_string(),
+                key: tail[..len].to_string(),
+            });
+        }
+    }
+
+    refs
+}
+
+/// Every output reference a node depends on: linked inputs and custom links.
+pub(crate) fn node_refs(node: &NodeData) -> Vec<OutputRef> {
+    let mut refs: Vec<OutputRef> = node
+        .inputs
+        .values()
+        .flatten()
+        .filter(|input| !input.is_literal)
+        .flat_map(|input| output_refs(&input.expr))
+        .collect();
+    refs.extend(output_refs(&node.custom_links_script));
+    refs
+}
+
+/// Runs the graph passes (unknown nodes, cycles, orphans, unlinked geometry inputs) over `nodes`.
+pub(crate) fn check_graph(nodes: &[NodeData], diagnostics: &mut TreeDiagnostics) {
+    let names: HashSet<&str> = nodes.iter().map(|n| n.name.as_str()).collect();
+    let mut deps: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut used: HashSet<String> = HashSet::new();
+
+    for node in nodes {
+        let mut node_deps = Vec::new();
+        let mut reported = HashSet::new();
+        for r in node_refs(node) {
+            if !names.contains(r.node.as_str()) {
+                if reported.insert(r.node.clone()) {
+                    diagnostics.error(
+                        DiagnosticKind::UnknownNode,
+                        &node.name,
+                        format!("links from '{}', which is not part of this tree", r.node),
+                    );
+                }
+                continue;
+            }
+            used.insert(r.node.clone());
+            node_deps.push(r.node);
+        }
+        deps.insert(node.name.as_str(), node_deps);
+    }
+
+    check_cycles(nodes, &deps, diagnostics);
+    check_geometry_inputs(nodes, diagnostics);
+
+    for node in nodes {
+        if !used.contains(&node.name) && !is_output_node(node) && node.bl_idname != "NodeGroupInput"
+        {
+            diagnostics.warning(
+                DiagnosticKind::OrphanNode,
+                &node.name,
+                format!("'{}' is never used", node.bl_idname),
+            );
+        }
+    }
+}
+
+/// Warns about unlinked geometry inputs of nodes with fixed inputs (see `core::meta`).
+fn check_geometry_inputs(nodes: &[NodeData], diagnostics: &mut TreeDiagnostics) {
+    for node in nodes {
+        let Some(entry) = meta::by_bl_idname(&node.bl_idname) else {
+            continue;
+        };
+        if entry.fixed_input_count().is_none() {
+            continue;
+        }
+        for (index, socket) in entry.inputs.iter().enumerate() {
+            let is_set = node.inputs.contains_key(&index)
+                || node
+                    .custom_links_script
+                    .contains(&format!(".inputs[{}]", index));
+            if socket.socket_type == "NodeSocketGeometry" && !is_set {
+                diagnostics.warning(
+                    DiagnosticKind::MissingInput,
+                    &node.name,
+                    format!(
+                        "{} is not linked, so the node works on empty geometry",
+                        meta::describe_input(&node.bl_idname, index)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn check_cycles(
+    nodes: &[NodeData],
+    deps: &HashMap<&str, Vec<String>>,
+    diagnostics: &mut TreeDiagnostics,
+) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    fn visit<'a>(
+        name: &'a str,
+        deps: &'a HashMap<&str, Vec<String>>,
+        marks: &mut HashMap<&'a str, Mark>,
+        diagnostics: &mut TreeDiagnostics,
+    ) {
+        match marks.get(name) {
+            Some(Mark::Done) => return,
+            Some(Mark::Visiting) => {
+                diagnostics.error(
+                    DiagnosticKind::Cycle,
+                    name,
+                    "node depends on its own output",
+                );
+                return;
+            }
+            None => {}
+        }
+
+        marks.insert(name, Mark::Visiting);
+        for dep in deps.get(name).into_iter().flatten() {
+            visit(dep, deps, marks, diagnostics);
+        }
+        marks.insert(name, Mark::Done);
+    }
+
+    let mut marks = HashMap::new();
+    for node in nodes {
+        visit(&node.name, deps, &mut marks, diagnostics);
+    }
+}
+
+/// Longest chain of linked nodes at which `check_budget` warns.
+pub(crate) const DEEP_CHAIN_WARNING: usize = 500;
+
+/// Names in `known` within edit distance 2 of `name`, closest first.
+pub(crate) fn near_matches<'a>(
+    name: &str,
+    known: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let mut matches: Vec<(usize, &str)> = known
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Moves the diagnostics recorded in the build context (see `context::warn`) into `diagnostics`.
+pub(crate) fn collect_build_diagnostics(diagnostics: &mut TreeDiagnostics) {
+    diagnostics.diagnostics.extend(context::take_diagnostics());
+}
+
+/// Reports an error if `nodes` exceeds `budget`, naming the 5 most frequent node types,
+/// and a warning if the longest link chain reaches `DEEP_CHAIN_WARNING`.
+pub(crate) fn check_budget(nodes: &[NodeData], budget: usize, diagnostics: &mut TreeDiagnostics) {
+    let depth = chain_depth(nodes);
+
+    if nodes.len() > budget {
+        let top = top_contributors(nodes, 5)
+            .into_iter()
+            .map(|(bl_idname, count)| format!("{} x{}", bl_idname, count))
+            .collect::<Vec<_>>()
+            .join(", ");
+        diagnostics.error(
+            DiagnosticKind::NodeBudget,
+            "",
+            format!(
+                "{} nodes exceed the budget of {} (top: {}; longest chain: {} nodes)",
+                nodes.len(),
+                budget,
+                top,
+                depth
+            ),
+        );
+    }
+
+    if depth >= DEEP_CHAIN_WARNING {
+        diagnostics.warning(
+            DiagnosticKind::DeepChain,
+            "",
+            format!(
+                "longest chain of linked nodes is {} nodes deep and will evaluate sequentially",
+                depth
+            ),
+        );
+    }
+}
+
+/// The most frequent node types, most frequent first (ties by name).
+pub(crate) fn top_contributors(nodes: &[NodeData], n: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for node in nodes {
+        *counts.entry(node.bl_idname.as_str()).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(bl_idname, count)| (bl_idname.to_string(), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.truncate(n);
+    counts
+}
+
+/// Number of nodes on the longest path through the link graph. Links that close a cycle
+/// and links to nodes outside `nodes` are ignored.
+pub(crate) fn chain_depth(nodes: &[NodeData]) -> usize {
+    let names: HashSet<&str> = nodes.iter().map(|n| n.name.as_str()).collect();
+    let deps: HashMap<&str, Vec<String>> = nodes
+        .iter()
+        .map(|node| {
+            let node_deps = node_refs(node)
+                .into_iter()
+                .map(|r| r.node)
+                .filter(|dep| names.contains(dep.as_str()))
+                .collect();
+            (node.name.as_str(), node_deps)
+        })
+        .collect();
+
+    // Iterative DFS, so that long chains cannot overflow the stack.
+    let mut depth: HashMap<&str, usize> = HashMap::new();
+    let mut visiting: HashSet<&str> = HashSet::new();
+    for node in nodes {
+        let mut stack: Vec<(&str, bool)> = vec![(node.name.as_str(), false)];
+        while let Some((name, expanded)) = stack.pop() {
+            if depth.contains_key(name) {
+                continue;
+            }
+            let node_deps = &deps[name];
+            if expanded {
+                let d = node_deps
+                    .iter()
+                    .filter_map(|dep| depth.get(dep.as_str()))
+                    .max()
+                    .map_or(1, |d| d + 1);
+                visiting.remove(name);
+                depth.insert(name, d);
+                continue;
+            }
+            visiting.insert(name);
+            stack.push((name, true));
+            for dep in node_deps {
+                if !visiting.contains(dep.as_str()) && !depth.contains_key(dep.as_str()) {
+                    stack.push((dep.as_str(), false));
+                }
+            }
+        }
+    }
+    depth.values().copied().max().unwrap_or(0)
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::InputValue;
+
+    fn node(name: &str, bl_idname: &str, links: &[&str]) -> NodeData {
+        let mut data = NodeData::new(name.to_string(), bl_idname.to_string());
+        for (i, expr) in links.iter().enumerate() {
+            data.inputs.insert(
+                i,
+                vec![InputValue {
+                    expr: expr.to_string(),
+                    is_literal: false,
+                }],
+            );
+        }
+        data
+    }
+
+    #[test]
+    fn test_output_refs() {
+        let refs =
+            output_refs(r#"tree.links.new(a_1.outputs["Va\"l]ue"], b.inputs[0]) + c.outputs[2]"#);
+        assert_eq!(
+            refs,
+            vec![
+                OutputRef {
+                    node: "a_1".to_string(),
+                    key: r#""Va\"l]ue""#.to_string()
+                },
+                OutputRef {
+                    node: "c".to_string(),
+                    key: "2".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_graph_reports_cycle_unknown_and_orphan() {
+        let nodes = vec![
+            node("a", "ShaderNodeMath", &["b.outputs[0]"]),
+            node("b", "ShaderNodeMath", &["a.outputs[0]"]),
+            node("c", "ShaderNodeMath", &["elsewhere.outputs[0]"]),
+            node("out", "NodeGroupOutput", &["a.outputs[0]"]),
+        ];
+        let mut diagnostics = TreeDiagnostics::new("T");
+        check_graph(&nodes, &mut diagnostics);
+
+        let kinds: Vec<_> = diagnostics
+            .diagnostics
+            .iter()
+            .map(|d| (d.kind, d.node.as_str()))
+            .collect();
+        assert!(kinds.contains(&(DiagnosticKind::UnknownNode, "c")));
+        assert!(kinds.contains(&(DiagnosticKind::OrphanNode, "c")));
+        assert_eq!(
+            diagnostics
+                .errors()
+                .filter(|d| d.kind == DiagnosticKind::Cycle)
+                .count(),
+            1
+        );
+        assert!(!kinds.iter().any(|(_, n)| *n == "out"));
+    }
+
+    #[test]
+    fn test_check_graph_reports_unlinked_geometry_input() {
+        let mut unlinked = node("set", "GeometryNodeSetPosition", &[]);
+        unlinked.inputs.insert(
+            2,
+            vec![InputValue {
+                expr: "(0.0000, 0.0000, 1.0000)".to_string(),
+                is_literal: true,
+            }],
+        );
+        let nodes = vec![
+            unlinked,
+            node("linked", "GeometryNodeSetPosition", &["set.outputs[0]"]),
+            node("out", "NodeGroupOutput", &["linked.outputs[0]"]),
+        ];
+        let mut diagnostics = TreeDiagnostics::new("T");
+        check_graph(&nodes, &mut diagnostics);
+
+        let missing: Vec<_> = diagnostics
+            .warnings()
+            .filter(|d| d.kind == DiagnosticKind::MissingInput)
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].node, "set");
+        assert_eq!(
+            missing[0].message,
+            "input 0 ('Geometry', NodeSocketGeometry) is not linked, \
+             so the node works on empty geometry"
+        );
+    }
+
+    #[test]
+    fn test_check_budget_reports_contributors_and_depth() {
+        let nodes = vec![
+            node("a", "ShaderNodeMath", &[]),
+            node("b", "ShaderNodeMath", &["a.outputs[0]"]),
+            node("c", "ShaderNodeMath", &["b.outputs[0]"]),
+            node("v", "ShaderNodeVectorMath", &[]),
+            node("out", "NodeGroupOutput", &["c.outputs[0]", "v.outputs[0]"]),
+        ];
+        assert_eq!(chain_depth(&nodes), 4);
+
+        let mut diagnostics = TreeDiagnostics::new("T");
+        check_budget(&nodes, 4, &mut diagnostics);
+        let errors: Vec<_> = diagnostics.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, DiagnosticKind::NodeBudget);
+        assert_eq!(
+            errors[0].message,
+            "5 nodes exceed the budget of 4 (top: ShaderNodeMath x3, NodeGroupOutput x1, \
+             ShaderNodeVectorMath x1; longest chain: 4 nodes)"
+        );
+
+        let mut diagnostics = TreeDiagnostics::new("T");
+        check_budget(&nodes, 5, &mut diagnostics);
+        assert!(diagnostics.is_empty());
+    }
+}
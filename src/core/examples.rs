@@ -0,0 +1,159 @@
+//! # Bundled Examples
+//!
+//! Parameterized versions of the programs in `examples/`, run by the `ramen-examples` binary:
+//!
+//! ```text
+//! cargo run --bin ramen-examples -- mandelbulb --power 6 --resolution 256
+//! ```
+
+pub mod aizawa;
+pub mod lorenz;
+pub mod mandelbulb;
+
+use crate::core::project::BlenderProject;
+use std::str::FromStr;
+
+/// Parameters that can be overridden by `--flag value` pairs on the command line.
+pub trait FromArgs: Default {
+    /// Flag names (without `--`) and their descriptions.
+    const FLAGS: &'static [(&'static str, &'static str)];
+
+    /// Sets the parameter of `flag` from its raw command-line value.
+    fn set_flag(&mut self, flag: &str, value: &str) -> Result<(), String>;
+
+    /// Starts from `Default` and applies every `--flag value` pair in order.
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut params = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let flag = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("expected a '--flag', got '{}'", arg))?;
+            if !Self::FLAGS.iter().any(|(name, _)| *name == flag) {
+                return Err(format!("unknown flag '--{}'", flag));
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for '--{}'", flag))?;
+            params.set_flag(flag, value)?;
+        }
+        Ok(params)
+    }
+}
+
+/// Parses `value` for `flag`, with an error that names the flag.
+pub fn parse_flag<T>(flag: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| format!("invalid value '{}' for '--{}': {}", value, flag, e))
+}
+
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub flags: &'static [(&'static str, &'static str)],
+    build: fn(&[String]) -> Result<BlenderProject, String>,
+}
+
+impl Example {
+    /// Parses `args` into the example's parameters and builds its project.
+    pub fn build(&self, args: &[String]) -> Result<BlenderProject, String> {
+        (self.build)(args)
+    }
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "mandelbulb",
+        description: "Mandelbulb fractal meshed from a volume grid",
+        flags: mandelbulb::MandelbulbParams::FLAGS,
+        build: |args| mandelbulb::MandelbulbParams::from_args(args).map(|p| mandelbulb::build(&p)),
+    },
+    Example {
+        name: "lorenz",
+        description: "Lorenz attractor traced as a neon wire",
+        flags: lorenz::LorenzParams::FLAGS,
+        build: |args| lorenz::LorenzParams::from_args(args).map(|p| lorenz::build(&p)),
+    },
+    Example {
+        name: "aizawa",
+        description: "Aizawa attractor colored by position",
+        flags: aizawa::AizawaParams::FLAGS,
+        build: |args| aizawa::AizawaParams::from_args(args).map(|p| aizawa::build(&p)),
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|example| example.name == name)
+}
+
+/// Counts the nodes each item of `project` creates, for checking example output.
+#[cfg(test)]
+pub(crate) fn node_counts(project: &BlenderProject) -> Vec<(String, usize)> {
+    project
+        .items()
+        .iter()
+        .map(|item| {
+            (
+                item.name.clone(),
+                item.script.matches(" = tree.nodes.new(").count(),
+            )
+        })
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_from_args_overrides_defaults() {
+        let params =
+            mandelbulb::MandelbulbParams::from_args(&args(&["--power", "6", "--iterations", "3"]))
+                .unwrap();
+        assert_eq!(params.power, 6.0);
+        assert_eq!(params.iterations, 3);
+        assert_eq!(
+            params.resolution,
+            mandelbulb::MandelbulbParams::default().resolution
+        );
+    }
+
+    #[test]
+    fn test_from_args_errors() {
+        let err = lorenz::LorenzParams::from_args(&args(&["--wat", "1"])).unwrap_err();
+        assert_eq!(err, "unknown flag '--wat'");
+
+        let err = lorenz::LorenzParams::from_args(&args(&["--dt"])).unwrap_err();
+        assert_eq!(err, "missing value for '--dt'");
+
+        let err = lorenz::LorenzParams::from_args(&args(&["--dt", "fast"])).unwrap_err();
+        assert!(err.starts_with("invalid value 'fast' for '--dt'"));
+
+        let err = lorenz::LorenzParams::from_args(&args(&["dt", "1"])).unwrap_err();
+        assert_eq!(err, "expected a '--flag', got 'dt'");
+    }
+
+    #[test]
+    fn test_registry_builds_by_name() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        assert!(find("nope").is_none());
+        let example = find("lorenz").unwrap();
+        let project = example.build(&args(&["--iterations", "5"])).unwrap();
+        assert!(project.contains(lorenz::GEO_NAME));
+        assert!(example.build(&args(&["--iterations", "many"])).is_err());
+    }
+}
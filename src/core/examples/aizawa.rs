@@ -0,0 +1,270 @@
+use super::{FromArgs, parse_flag};
+use crate::core::nodes::{
+    CompositorNodeAlphaOver, CompositorNodeGlare, CompositorNodeLensdist, CompositorNodeRLayers,
+    CompositorNodeRgb, CompositorNodeViewer, GeometryNodeCurvePrimitiveCircle,
+    GeometryNodeCurvePrimitiveLine, GeometryNodeCurveToMesh, GeometryNodeInputPosition,
+    GeometryNodeJoinGeometry, GeometryNodeSetMaterial, GeometryNodeStoreNamedAttribute,
+    GeometryNodeStoreNamedAttributeDataType, GeometryNodeTransform, NodeGroupOutput,
+    ShaderNodeAttribute, ShaderNodeCombineXyz, ShaderNodeEmission, ShaderNodeOutputMaterial,
+    ShaderNodeSeparateXyz,
+};
+use crate::core::project::BlenderProject;
+use crate::core::types::{NodeSocket, Vector};
+use crate::core::zone::repeat_zone;
+use ramen_macros::ramen_math;
+
+// ==========================================
+// Params (Design)
+// ==========================================
+const WIRE_RADIUS: f32 = 0.008;
+const WIRE_RESOLUTION: i32 = 6;
+const NEON_STRENGTH: f32 = 5.0;
+const TRANSFORM_SCALE: f32 = 3.0;
+const TRANSFORM_Z_OFFSET: f32 = -1.5;
+
+// Compositor
+const GLARE_FADE: f32 = 0.8;
+const BG_COLOR: (f32, f32, f32, f32) = (0.01, 0.01, 0.02, 1.0);
+const LENS_DISPERSION: f32 = 0.04;
+
+// ==========================================
+// Names
+// ==========================================
+pub const GEO_NAME: &str = "AizawaAttractorGeo";
+pub const MAT_NEON: &str = "NeonMat";
+pub const COMP_NAME: &str = "AizawaComp";
+pub const POS_ATTR_NAME: &str = "PosAttr";
+
+/// Parameters of the integration (math).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AizawaParams {
+    pub iterations: i32,
+    pub dt: f32,
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+    pub initial_pos: (f32, f32, f32),
+}
+
+impl Default for AizawaParams {
+    fn default() -> Self {
+        Self {
+            iterations: 50000,
+            dt: 0.01,
+            a: 0.95,
+            b: 0.7,
+            c: 0.6,
+            d: 3.5,
+            e: 0.25,
+            f: 0.1,
+            initial_pos: (0.1, 0.0, 0.0),
+        }
+    }
+}
+
+impl FromArgs for AizawaParams {
+    const FLAGS: &'static [(&'static str, &'static str)] = &[
+        ("iterations", "number of integration steps"),
+        ("dt", "time step"),
+        ("a", "coefficient a"),
+        ("b", "coefficient b"),
+        ("c", "coefficient c"),
+        ("d", "coefficient d"),
+        ("e", "coefficient e"),
+        ("f", "coefficient f"),
+    ];
+
+    fn set_flag(&mut self, flag: &str, value: &str) -> Result<(), String> {
+        match flag {
+            "iterations" => self.iterations = parse_flag(flag, value)?,
+            "dt" => self.dt = parse_flag(flag, value)?,
+            "a" => self.a = parse_flag(flag, value)?,
+            "b" => self.b = parse_flag(flag, value)?,
+            "c" => self.c = parse_flag(flag, value)?,
+            "d" => self.d = parse_flag(flag, value)?,
+            "e" => self.e = parse_flag(flag, value)?,
+            "f" => self.f = parse_flag(flag, value)?,
+            _ => return Err(format!("unknown flag '--{}'", flag)),
+        }
+        Ok(())
+    }
+}
+
+//noinspection DuplicatedCode
+pub fn build(params: &AizawaParams) -> BlenderProject {
+    let AizawaParams {
+        iterations,
+        dt,
+        a,
+        b,
+        c,
+        d,
+        e,
+        f,
+        initial_pos,
+    } = params.clone();
+
+    BlenderProject::new()
+        .add_shader_tree(MAT_NEON, || {
+            let attr = ShaderNodeAttribute::new().with_attribute_name(POS_ATTR_NAME);
+            let sep = ShaderNodeSeparateXyz::new().with_vector(attr.out_vector());
+
+            let x = sep.out_x();
+            let y = sep.out_y();
+            let z = sep.out_z();
+
+            let r = ramen_math!(z * 0.8 + 0.2);
+            let g = ramen_math!(abs(x) * 1.5);
+            let b = ramen_math!(1.0 - abs(y));
+
+            let color = ShaderNodeCombineXyz::new()
+                .with_x(r)
+                .with_y(g)
+                .with_z(b)
+                .out_vector();
+
+            let emission = ShaderNodeEmission::new()
+                .set_input(ShaderNodeEmission::PIN_COLOR, color)
+                .with_strength(NEON_STRENGTH);
+
+            ShaderNodeOutputMaterial::new().with_surface(emission.out_emission());
+        })
+        .add_geometry_tree(GEO_NAME, || {
+            let initial_pos_socket = NodeSocket::<Vector>::from(initial_pos);
+            let initial_geo = GeometryNodeCurvePrimitiveLine::new()
+                .with_start(NodeSocket::<Vector>::from(initial_pos))
+                .with_end(NodeSocket::<Vector>::from(initial_pos))
+                .out_curve();
+
+            // Aizawa Attractor creation loop
+            let (_final_pos, final_geo) = repeat_zone(
+                iterations,
+                (initial_pos_socket, initial_geo),
+                |(pos, geo)| {
+                    let sep = ShaderNodeSeparateXyz::new().with_vector(pos);
+                    let x = sep.out_x();
+                    let y = sep.out_y();
+                    let z = sep.out_z();
+
+                    let dx = ramen_math!(((z - b) * x - d * y) * dt);
+                    let dy = ramen_math!((d * x + (z - b) * y) * dt);
+                    let dz = ramen_math!(
+                        (c + a * z
+                            - pow(z, 3.0) / 3.0
+                            - (pow(x, 2.0) + pow(y, 2.0)) * (1.0 + e * z)
+                            + f * z * pow(x, 3.0))
+                            * dt
+                    );
+
+                    let delta = ShaderNodeCombineXyz::new()
+                        .with_x(dx)
+                        .with_y(dy)
+                        .with_z(dz)
+                        .out_vector();
+
+                    let next_pos = ramen_math!(pos + delta);
+
+                    let segment = GeometryNodeCurvePrimitiveLine::new()
+                        .with_start(pos)
+                        .with_end(next_pos);
+
+                    let joined = GeometryNodeJoinGeometry::new()
+                        .append_geometry(geo)
+                        .append_geometry(segment.out_curve())
+                        .out_geometry();
+                    (next_pos, joined)
+                },
+            );
+
+            // line materialization
+            let profile_circle = GeometryNodeCurvePrimitiveCircle::new()
+                .with_radius(WIRE_RADIUS)
+                .with_resolution(WIRE_RESOLUTION);
+
+            let mesh = GeometryNodeCurveToMesh::new()
+                .with_curve(final_geo)
+                .with_profile_curve(profile_circle.out_curve());
+
+            let with_mat = GeometryNodeSetMaterial::new()
+                .with_geometry(mesh.out_mesh())
+                .with_material(MAT_NEON);
+
+            let store_pos = GeometryNodeStoreNamedAttribute::new()
+                .with_geometry(with_mat.out_geometry())
+                .with_name(POS_ATTR_NAME)
+                .with_data_type(GeometryNodeStoreNamedAttributeDataType::FloatVector)
+                .set_input(
+                    GeometryNodeStoreNamedAttribute::PIN_VALUE,
+                    GeometryNodeInputPosition::new().out_position(),
+                );
+
+            let transform = GeometryNodeTransform::new()
+                .with_geometry(store_pos.out_geometry())
+                .with_scale(NodeSocket::<Vector>::from((
+                    TRANSFORM_SCALE,
+                    TRANSFORM_SCALE,
+                    TRANSFORM_SCALE,
+                )))
+                .with_translation(NodeSocket::<Vector>::from((0.0, 0.0, TRANSFORM_Z_OFFSET)));
+
+            NodeGroupOutput::new().set_input(0, transform.out_geometry());
+        })
+        .add_compositor_tree(COMP_NAME, || {
+            let render_layers = CompositorNodeRLayers::new();
+
+            let glare = CompositorNodeGlare::new()
+                .with_fade(GLARE_FADE)
+                .set_input(CompositorNodeGlare::PIN_IMAGE, render_layers.out_image());
+
+            let bg_color = CompositorNodeRgb::new().default_color(BG_COLOR);
+
+            let alpha_over = CompositorNodeAlphaOver::new()
+                .set_input(
+                    CompositorNodeAlphaOver::PIN_BACKGROUND,
+                    bg_color.out_color(),
+                )
+                .set_input(CompositorNodeAlphaOver::PIN_FOREGROUND, glare.out_image());
+
+            let lens_dist = CompositorNodeLensdist::new()
+                .set_input(CompositorNodeLensdist::PIN_IMAGE, alpha_over.out_image())
+                .set_input(
+                    CompositorNodeLensdist::PIN_DISPERSION,
+                    NodeSocket::from(LENS_DISPERSION),
+                );
+
+            NodeGroupOutput::new().set_input(0, lens_dist.out_image());
+            CompositorNodeViewer::new()
+                .set_input(CompositorNodeViewer::PIN_IMAGE, lens_dist.out_image());
+        })
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::examples::node_counts;
+
+    #[test]
+    fn test_build_small_aizawa() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let project = build(&AizawaParams {
+            iterations: 3,
+            ..AizawaParams::default()
+        });
+        assert_eq!(
+            node_counts(&project),
+            vec![
+                (MAT_NEON.to_string(), 11),
+                (GEO_NAME.to_string(), 42),
+                (COMP_NAME.to_string(), 7),
+            ]
+        );
+    }
+}
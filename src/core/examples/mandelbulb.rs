@@ -0,0 +1,259 @@
+use super::{FromArgs, parse_flag};
+use crate::core::nodes::{
+    CompositorNodeGlare, CompositorNodeLensdist, CompositorNodeRLayers, CompositorNodeViewer,
+    GeometryNodeInputPosition, GeometryNodeSetMaterial, NodeGroupInput, NodeGroupOutput,
+    ShaderNodeAddShader, ShaderNodeAmbientOcclusion, ShaderNodeBsdfDiffuse, ShaderNodeEmission,
+    ShaderNodeOutputMaterial, ShaderNodeSeparateXyz,
+};
+use crate::core::project::BlenderProject;
+use crate::core::tree::{NodeTree, call_geometry_group};
+use crate::core::types::{Float, GeometryNodeGroupExt, NodeGroupInputExt, NodeSocket, Vector};
+use crate::core::volume::{self, MeshResolution, Resolution};
+use crate::core::zone::repeat_zone;
+use ramen_macros::ramen_math;
+
+// ==========================================
+// Names
+// ==========================================
+pub const SUB_NAME: &str = "MandelbulbStep";
+pub const MAIN_TREE_NAME: &str = "MandelbulbGeo";
+pub const MAT_NAME: &str = "MandelbulbMat";
+pub const COMP_NAME: &str = "MandelbulbComp";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MandelbulbParams {
+    /// Exponent of the Mandelbulb
+    pub power: f32,
+    /// Iteration count (detail)
+    pub iterations: i32,
+    /// VolumeCube resolution
+    pub resolution: i32,
+    /// Calculation bound
+    pub bound_extent: f32,
+    /// Meshing threshold
+    pub threshold: f32,
+}
+
+impl Default for MandelbulbParams {
+    fn default() -> Self {
+        Self {
+            power: 8.0,
+            iterations: 10,
+            resolution: 512,
+            bound_extent: 1.2,
+            threshold: 0.01,
+        }
+    }
+}
+
+impl FromArgs for MandelbulbParams {
+    const FLAGS: &'static [(&'static str, &'static str)] = &[
+        ("power", "exponent of the Mandelbulb"),
+        ("iterations", "iteration count (detail)"),
+        ("resolution", "VolumeCube resolution"),
+        ("bound-extent", "calculation bound"),
+        ("threshold", "meshing threshold"),
+    ];
+
+    fn set_flag(&mut self, flag: &str, value: &str) -> Result<(), String> {
+        match flag {
+            "power" => self.power = parse_flag(flag, value)?,
+            "iterations" => self.iterations = parse_flag(flag, value)?,
+            "resolution" => self.resolution = parse_flag(flag, value)?,
+            "bound-extent" => self.bound_extent = parse_flag(flag, value)?,
+            "threshold" => self.threshold = parse_flag(flag, value)?,
+            _ => return Err(format!("unknown flag '--{}'", flag)),
+        }
+        Ok(())
+    }
+}
+
+mod sub_sockets {
+    pub const IN_X: usize = 0;
+    pub const IN_Y: usize = 1;
+    pub const IN_Z: usize = 2;
+    pub const IN_CX: usize = 3;
+    pub const IN_CY: usize = 4;
+    pub const IN_CZ: usize = 5;
+
+    pub const OUT_X: usize = 0;
+    pub const OUT_Y: usize = 1;
+    pub const OUT_Z: usize = 2;
+}
+
+//noinspection DuplicatedCode
+pub fn build(params: &MandelbulbParams) -> BlenderProject {
+    let subtree = NodeTree::new_geometry_group(SUB_NAME)
+        .with_input::<Float>("X")
+        .with_input::<Float>("Y")
+        .with_input::<Float>("Z")
+        .with_input::<Float>("CX")
+        .with_input::<Float>("CY")
+        .with_input::<Float>("CZ")
+        .with_output::<Float>("OutX")
+        .with_output::<Float>("OutY")
+        .with_output::<Float>("OutZ")
+        .build(|| {
+            let group_in = NodeGroupInput::new();
+            let x = group_in.socket::<Float>("X");
+            let y = group_in.socket::<Float>("Y");
+            let z = group_in.socket::<Float>("Z");
+            let cx = group_in.socket::<Float>("CX");
+            let cy = group_in.socket::<Float>("CY");
+            let cz = group_in.socket::<Float>("CZ");
+
+            let p = NodeSocket::<Float>::from(params.power);
+
+            let r = ramen_math!(sqrt(pow(x, 2.0) + pow(y, 2.0) + pow(z, 2.0)));
+            let phi = ramen_math!(atan2(y, x));
+            let theta = ramen_math!(atan2(sqrt(pow(x, 2.0) + pow(y, 2.0)), z));
+            let vr = ramen_math!(pow(min(r, 2.0), p)); // min: to prevent NaN pollution
+
+            let n_theta = ramen_math!(p * theta);
+            let n_phi = ramen_math!(p * phi);
+
+            let bulb = ShaderNodeSeparateXyz::new()
+                .with_vector(NodeSocket::<Vector>::from_spherical(vr, n_theta, n_phi));
+            let out_x = ramen_math!(bulb.out_x() + cx);
+            let out_y = ramen_math!(bulb.out_y() + cy);
+            let out_z = ramen_math!(bulb.out_z() + cz);
+
+            NodeGroupOutput::new()
+                .set_input(sub_sockets::OUT_X, out_x)
+                .set_input(sub_sockets::OUT_Y, out_y)
+                .set_input(sub_sockets::OUT_Z, out_z);
+        });
+
+    BlenderProject::new()
+        .add_subtree(SUB_NAME, &subtree)
+        .add_shader_tree(MAT_NAME, || {
+            let ao = ShaderNodeAmbientOcclusion::new().with_samples(16);
+
+            // want the value to be larger the lower the AO
+            let crevice_mask = ramen_math!(pow(1.0 - ao.out_ao(), 3.0) * 20.0);
+
+            // base texture
+            let diffuse = ShaderNodeBsdfDiffuse::new().with_color((0.02, 0.02, 0.03, 1.0)); // dark blue gray
+
+            // cyan light in the valley
+            let emission = ShaderNodeEmission::new()
+                .with_color((0.0, 0.8, 1.0, 1.0))
+                .set_input(ShaderNodeEmission::PIN_STRENGTH, crevice_mask);
+
+            // additive composition of Diffuse and Emission
+            let add_shader = ShaderNodeAddShader::new()
+                .set_input(ShaderNodeAddShader::PIN_SHADER, diffuse.out_bsdf())
+                .set_input(ShaderNodeAddShader::PIN_SHADER_0, emission.out_emission());
+
+            ShaderNodeOutputMaterial::new().with_surface(add_shader.out_shader());
+        })
+        .add_geometry_tree(MAIN_TREE_NAME, || {
+            let pos = GeometryNodeInputPosition::new().out_position();
+            let sep_pos = ShaderNodeSeparateXyz::new().with_vector(pos);
+
+            let cur_x = sep_pos.out_x();
+            let cur_y = sep_pos.out_y();
+            let cur_z = sep_pos.out_z();
+            let cx = sep_pos.out_x();
+            let cy = sep_pos.out_y();
+            let cz = sep_pos.out_z();
+
+            let initial_state = (cur_x, cur_y, cur_z);
+
+            let (final_x, final_y, final_z) =
+                repeat_zone(params.iterations, initial_state, |(x, y, z)| {
+                    let step = call_geometry_group(SUB_NAME)
+                        .set_input(sub_sockets::IN_X, x)
+                        .set_input(sub_sockets::IN_Y, y)
+                        .set_input(sub_sockets::IN_Z, z)
+                        .set_input(sub_sockets::IN_CX, cx)
+                        .set_input(sub_sockets::IN_CY, cy)
+                        .set_input(sub_sockets::IN_CZ, cz);
+
+                    (
+                        step.out_socket::<Float>("OutX"),
+                        step.out_socket::<Float>("OutY"),
+                        step.out_socket::<Float>("OutZ"),
+                    )
+                });
+
+            let r_final = ramen_math!(sqrt(
+                pow(final_x, 2.0) + pow(final_y, 2.0) + pow(final_z, 2.0)
+            ));
+
+            let density = ramen_math!(2.0 - r_final);
+
+            let extent = params.bound_extent;
+            let grid = volume::cube(
+                density,
+                ((-extent, -extent, -extent), (extent, extent, extent)),
+                Resolution::Uniform(params.resolution),
+            );
+            let mesh = volume::to_mesh(grid, params.threshold, MeshResolution::Grid);
+
+            let set_mat = GeometryNodeSetMaterial::new()
+                .with_geometry(mesh)
+                .with_material(MAT_NAME);
+
+            NodeGroupOutput::new().set_input(0, set_mat.out_geometry());
+        })
+        .add_compositor_tree(COMP_NAME, || {
+            let render_layers = CompositorNodeRLayers::new();
+
+            // Glare (Fog Glow)
+            let glare = CompositorNodeGlare::new()
+                .set_input(CompositorNodeGlare::PIN_IMAGE, render_layers.out_image());
+
+            // scaling by dispersion
+            let lens_dist = CompositorNodeLensdist::new()
+                .set_input(CompositorNodeLensdist::PIN_IMAGE, glare.out_image())
+                .set_input(
+                    CompositorNodeLensdist::PIN_DISTORTION,
+                    NodeSocket::from(0.02_f32),
+                )
+                .set_input(
+                    CompositorNodeLensdist::PIN_DISPERSION,
+                    NodeSocket::from(0.15_f32),
+                );
+
+            NodeGroupOutput::new().set_input(0, lens_dist.out_image());
+            CompositorNodeViewer::new()
+                .set_input(CompositorNodeViewer::PIN_IMAGE, lens_dist.out_image());
+        })
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::examples::node_counts;
+
+    #[test]
+    fn test_build_small_mandelbulb() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let project = build(&MandelbulbParams {
+            power: 6.0,
+            iterations: 2,
+            resolution: 16,
+            ..MandelbulbParams::default()
+        });
+        assert_eq!(
+            node_counts(&project),
+            vec![
+                (SUB_NAME.to_string(), 31),
+                (MAT_NAME.to_string(), 8),
+                (MAIN_TREE_NAME.to_string(), 16),
+                (COMP_NAME.to_string(), 5),
+            ]
+        );
+
+        let step = &project.get_item(SUB_NAME).unwrap().script;
+        assert!(step.contains("].default_value = 6.0000\n"));
+        let main = &project.get_item(MAIN_TREE_NAME).unwrap().script;
+        assert!(main.contains("].default_value = 2\n"));
+    }
+}
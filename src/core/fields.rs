@@ -0,0 +1,189 @@
+//! # Index Fields
+//!
+//! Selections and gradients from the `Index` field, built with integer math so that large
+//! indices stay exact:
+//!
+//! ```ignore
+//! let thirds = fields::every_nth(3, 0);
+//! GeometryNodeDeleteGeometry::new().with_geometry(grid).with_selection(thirds);
+//! ```
+
+use crate::core::inputs::index;
+use crate::core::nodes::{
+    FunctionNodeCompare, FunctionNodeCompareDataType, FunctionNodeCompareOperation,
+    FunctionNodeIntegerMath, FunctionNodeIntegerMathOperation, ShaderNodeMath,
+    ShaderNodeMathOperation,
+};
+use crate::core::types::{Bool, Float, Int, NodeSocket};
+
+fn int_math(
+    operation: FunctionNodeIntegerMathOperation,
+    a: NodeSocket<Int>,
+    b: NodeSocket<Int>,
+) -> NodeSocket<Int> {
+    FunctionNodeIntegerMath::new()
+        .with_operation(operation)
+        .set_input(FunctionNodeIntegerMath::PIN_VALUE, a)
+        .set_input(FunctionNodeIntegerMath::PIN_VALUE_0, b)
+        .out_value()
+}
+
+/// `value == 0` (`FunctionNodeCompare` INT EQUAL).
+fn is_zero(value: NodeSocket<Int>) -> NodeSocket<Bool> {
+    FunctionNodeCompare::new()
+        .with_data_type(FunctionNodeCompareDataType::Int)
+        .with_operation(FunctionNodeCompareOperation::Equal)
+        .set_input(FunctionNodeCompare::PIN_A_0, value)
+        .set_input(FunctionNodeCompare::PIN_B_0, NodeSocket::<Int>::from(0))
+        .out_result()
+}
+
+/// Selects every `n`th element, starting at index `offset`.
+pub fn every_nth(
+    n: impl Into<NodeSocket<Int>>,
+    offset: impl Into<NodeSocket<Int>>,
+) -> NodeSocket<Bool> {
+    let shifted = int_math(
+        FunctionNodeIntegerMathOperation::Subtract,
+        index(),
+        offset.into(),
+    );
+    // Floored, so elements before `offset` keep the pattern.
+    let phase = int_math(
+        FunctionNodeIntegerMathOperation::FlooredModulo,
+        shifted,
+        n.into(),
+    );
+    is_zero(phase)
+}
+
+/// Selects alternating cells of elements laid out in rows of `row_length`, starting with
+/// index 0. Mesh Grid is x-major: its points come in rows of `Vertices Y`, its faces in rows
+/// of `Vertices Y - 1`.
+pub fn checkerboard(row_length: impl Into<NodeSocket<Int>>) -> NodeSocket<Bool> {
+    let row_length = row_length.into();
+    let column = int_math(
+        FunctionNodeIntegerMathOperation::FlooredModulo,
+        index(),
+        row_length,
+    );
+    let row = int_math(
+        FunctionNodeIntegerMathOperation::DivideFloor,
+        index(),
+        row_length,
+    );
+    let cell = int_math(FunctionNodeIntegerMathOperation::Add, column, row);
+    let parity = int_math(
+        FunctionNodeIntegerMathOperation::FlooredModulo,
+        cell,
+        NodeSocket::from(2),
+    );
+    is_zero(parity)
+}
+
+/// `index / (count - 1)`: 0.0 on the first of `count` elements, 1.0 on the last.
+pub fn index_normalized(count: impl Into<NodeSocket<Int>>) -> NodeSocket<Float> {
+    let last = int_math(
+        FunctionNodeIntegerMathOperation::Subtract,
+        count.into(),
+        NodeSocket::from(1),
+    );
+    ShaderNodeMath::new()
+        .with_operation(ShaderNodeMathOperation::Divide)
+        .set_input(0, index())
+        .set_input(1, last)
+        .out_value()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::{self, NodeData, test_utils::GLOBAL_TEST_LOCK};
+
+    fn operations(nodes: &[NodeData], bl_idname: &str) -> Vec<String> {
+        nodes
+            .iter()
+            .filter(|n| n.bl_idname == bl_idname)
+            .map(|n| n.properties["operation"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_nth_uses_integer_math() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+        let selection = every_nth(3, 1);
+        let nodes = context::exit_zone();
+
+        assert!(!nodes.iter().any(|n| n.bl_idname == "ShaderNodeMath"));
+        assert_eq!(
+            operations(&nodes, "FunctionNodeIntegerMath"),
+            ["\"SUBTRACT\"", "\"FLOORED_MODULO\""]
+        );
+        let compare = nodes
+            .iter()
+            .find(|n| n.bl_idname == "FunctionNodeCompare")
+            .unwrap();
+        assert_eq!(compare.properties["data_type"], "\"INT\"");
+        assert_eq!(compare.properties["operation"], "\"EQUAL\"");
+        assert_eq!(compare.inputs[&FunctionNodeCompare::PIN_B_0][0].expr, "0");
+        assert!(selection.python_expr().starts_with(&compare.name));
+
+        let modulo = nodes
+            .iter()
+            .find(|n| {
+                n.properties.get("operation").map(String::as_str) == Some("\"FLOORED_MODULO\"")
+            })
+            .unwrap();
+        assert_eq!(
+            modulo.inputs[&FunctionNodeIntegerMath::PIN_VALUE_0][0].expr,
+            "3"
+        );
+    }
+
+    #[test]
+    fn test_checkerboard_and_index_normalized() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+        let _ = checkerboard(10);
+        let nodes = context::exit_zone();
+
+        // One shared Index node feeds both the column and the row.
+        assert_eq!(
+            nodes
+                .iter()
+                .filter(|n| n.bl_idname == "GeometryNodeInputIndex")
+                .count(),
+            1
+        );
+        let mut ops = operations(&nodes, "FunctionNodeIntegerMath");
+        ops.sort();
+        assert_eq!(
+            ops,
+            [
+                "\"ADD\"",
+                "\"DIVIDE_FLOOR\"",
+                "\"FLOORED_MODULO\"",
+                "\"FLOORED_MODULO\""
+            ]
+        );
+        assert!(!nodes.iter().any(|n| n.bl_idname == "ShaderNodeMath"));
+
+        context::enter_zone();
+        let t = index_normalized(5);
+        let nodes = context::exit_zone();
+        let divide = nodes
+            .iter()
+            .find(|n| n.bl_idname == "ShaderNodeMath")
+            .unwrap();
+        assert_eq!(divide.properties["operation"], "\"DIVIDE\"");
+        assert_eq!(
+            operations(&nodes, "FunctionNodeIntegerMath"),
+            ["\"SUBTRACT\""]
+        );
+        assert!(t.python_expr().starts_with(&divide.name));
+    }
+}
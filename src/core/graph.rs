@@ -0,0 +1,113 @@
+//! # Node Graph Export
+//!
+//! A serializable description of a built tree (nodes, properties and links), produced by
+//! `NodeTree::build_to_graph`. Meant for visualizing or diffing generated graphs outside Blender.
+
+use crate::core::context::NodeData;
+use crate::core::diagnostics::output_refs;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeGraph {
+    pub tree: String,
+    /// Nodes in creation order.
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    pub name: String,
+    pub bl_idname: String,
+    /// Property values as emitted Python expressions.
+    pub properties: BTreeMap<String, String>,
+    /// Literal input values by pin index, as emitted Python expressions.
+    pub defaults: BTreeMap<usize, String>,
+}
+
+/// A link from an output socket to an input pin.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    pub from_node: String,
+    /// Output socket name, or its index for links by position.
+    pub from_socket: String,
+    pub to_node: String,
+    pub to_input: usize,
+}
+
+impl NodeGraph {
+    /// Derives the graph from collected nodes. Links are taken from the non-literal inputs;
+    /// `custom_links_script` (dynamic zone sockets) is not included.
+    pub fn from_nodes(tree: &str, nodes: &[NodeData]) -> Self {
+        let mut graph_nodes = Vec::with_capacity(nodes.len());
+        let mut edges = Vec::new();
+
+        for node in nodes {
+            let mut defaults = BTreeMap::new();
+            let mut inputs: Vec<_> = node.inputs.iter().collect();
+            inputs.sort_by_key(|(index, _)| **index);
+
+            for (&index, values) in inputs {
+                for value in values {
+                    if value.is_literal {
+                        defaults.entry(index).or_insert_with(|| value.expr.clone());
+                        continue;
+                    }
+                    for r in output_refs(&value.expr) {
+                        edges.push(GraphEdge {
+                            from_node: r.node,
+                            from_socket: socket_key(&r.key),
+                            to_node: node.name.clone(),
+                            to_input: index,
+                        });
+                    }
+                }
+            }
+
+            graph_nodes.push(GraphNode {
+                name: node.name.clone(),
+                bl_idname: node.bl_idname.clone(),
+                properties: node
+                    .properties
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+                defaults,
+            });
+        }
+
+        Self {
+            tree: tree.to_string(),
+            nodes: graph_nodes,
+            edges,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("NodeGraph is always serializable")
+    }
+}
+
+/// Turns a raw Python subscript (`"Value"` or `0`) into a plain socket key.
+fn socket_key(key: &str) -> String {
+    match key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        Some(name) => name.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => key.to_string(),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_key() {
+        assert_eq!(socket_key("\"Value\""), "Value");
+        assert_eq!(socket_key(r#""Say \"hi\"""#), "Say \"hi\"");
+        assert_eq!(socket_key("2"), "2");
+    }
+}
@@ -0,0 +1,1351 @@
+//! # Helpers Module
+//!
+//! Convenience functions for common operations that need more than one Blender node,
+//! or a single node with a specific configuration that is tedious to spell out by hand.
+
+use crate::core::context::{update_post_creation, update_property};
+use crate::core::nodes::{
+    CompositorNodeOutputFile, FunctionNodeCompare, FunctionNodeCompareDataType,
+    FunctionNodeCompareOperation, GeometryNodeAttributeStatistic,
+    GeometryNodeAttributeStatisticDataType, GeometryNodeAttributeStatisticDomain,
+    GeometryNodeCaptureAttribute, GeometryNodeCaptureAttributeDomain, GeometryNodeCurveLength,
+    GeometryNodeCurveToMesh, GeometryNodeCurveToPoints, GeometryNodeCurveToPointsMode,
+    GeometryNodeInputNamedAttribute, GeometryNodeInputNamedAttributeDataType,
+    GeometryNodeInstanceOnPoints, GeometryNodeJoinGeometry, GeometryNodeMeshLine,
+    GeometryNodeMeshToPoints, GeometryNodeMeshToPointsMode, GeometryNodePointsToVertices,
+    GeometryNodeRaycast, GeometryNodeRaycastDataType, GeometryNodeRealizeInstances,
+    GeometryNodeSampleIndex, GeometryNodeSampleIndexDataType, GeometryNodeSampleIndexDomain,
+    GeometryNodeSampleNearest, GeometryNodeSampleNearestSurface,
+    GeometryNodeSampleNearestSurfaceDataType, GeometryNodeSeparateGeometry,
+    GeometryNodeSeparateGeometryDomain, GeometryNodeSetMaterial, GeometryNodeSetPosition,
+    GeometryNodeSplineLength, GeometryNodeSplineParameter, GeometryNodeStoreNamedAttribute,
+    GeometryNodeStoreNamedAttributeDataType, GeometryNodeStoreNamedAttributeDomain,
+    ShaderNodeVectorMath, ShaderNodeVectorMathOperation,
+};
+use crate::core::types::{
+    Bool, Color, Float, Geo, Int, Material, Matrix, NodeSocket, Rotation, SocketDef, Vector,
+    python_string_literal,
+};
+use crate::core::zone::add_custom_link;
+use std::fmt::Write;
+use std::marker::PhantomData;
+
+/// The geometry domain a field is evaluated on.
+pub type FieldDomain = GeometryNodeAttributeStatisticDomain;
+
+// vector geometry ======================================================================
+
+/// Projects `vector` onto the plane through the origin with the given `plane_normal`.
+///
+/// Computed as `v - n * dot(v, n)`, so `plane_normal` is expected to be unit length
+/// (as the `Normal` input already is).
+pub fn project_to_plane(
+    vector: NodeSocket<Vector>,
+    plane_normal: NodeSocket<Vector>,
+) -> NodeSocket<Vector> {
+    let dot = ShaderNodeVectorMath::new()
+        .with_operation(ShaderNodeVectorMathOperation::DotProduct)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR, vector)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR_0, plane_normal)
+        .out_value();
+
+    let along_normal = ShaderNodeVectorMath::new()
+        .with_operation(ShaderNodeVectorMathOperation::Scale)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR, plane_normal)
+        .set_input(ShaderNodeVectorMath::PIN_SCALE, dot)
+        .out_vector();
+
+    vector - along_normal
+}
+
+/// Reflects `vector` about `normal` (`ShaderNodeVectorMath` REFLECT).
+pub fn reflect(vector: NodeSocket<Vector>, normal: NodeSocket<Vector>) -> NodeSocket<Vector> {
+    ShaderNodeVectorMath::new()
+        .with_operation(ShaderNodeVectorMathOperation::Reflect)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR, vector)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR_0, normal)
+        .out_vector()
+}
+
+/// Refracts `vector` through a surface with `normal` and index of refraction `ior`
+/// (`ShaderNodeVectorMath` REFRACT, `ior` goes to the Scale pin).
+pub fn refract(
+    vector: NodeSocket<Vector>,
+    normal: NodeSocket<Vector>,
+    ior: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Vector> {
+    ShaderNodeVectorMath::new()
+        .with_operation(ShaderNodeVectorMathOperation::Refract)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR, vector)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR_0, normal)
+        .set_input(ShaderNodeVectorMath::PIN_SCALE, ior.into())
+        .out_vector()
+}
+
+/// Orients `vector` to point away from the surface: returns `vector` if
+/// `dot(incident, reference) < 0`, otherwise `-vector` (`ShaderNodeVectorMath` FACEFORWARD).
+pub fn face_forward(
+    vector: NodeSocket<Vector>,
+    incident: NodeSocket<Vector>,
+    reference: NodeSocket<Vector>,
+) -> NodeSocket<Vector> {
+    ShaderNodeVectorMath::new()
+        .with_operation(ShaderNodeVectorMathOperation::Faceforward)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR, vector)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR_0, incident)
+        .set_input(ShaderNodeVectorMath::PIN_VECTOR_1, reference)
+        .out_vector()
+}
+
+// attribute statistics =================================================================
+
+/// All outputs of an `Attribute Statistic` node for one data type.
+pub struct AttributeStatisticOutputs<T> {
+    pub mean: NodeSocket<T>,
+    pub median: NodeSocket<T>,
+    pub sum: NodeSocket<T>,
+    pub min: NodeSocket<T>,
+    pub max: NodeSocket<T>,
+    pub range: NodeSocket<T>,
+    pub standard_deviation: NodeSocket<T>,
+    pub variance: NodeSocket<T>,
+}
+
+/// Socket types `GeometryNodeAttributeStatistic` can aggregate.
+///
+/// The node carries one attribute input and one block of 8 outputs per data type;
+/// only the block matching `data_type` is available.
+pub trait AttributeStatisticType: SocketDef + Sized {
+    const DATA_TYPE: GeometryNodeAttributeStatisticDataType;
+    const ATTRIBUTE_PIN: usize;
+    /// Index of the first output (`Mean`) of this type's block.
+    const FIRST_OUTPUT: usize;
+}
+
+impl AttributeStatisticType for Float {
+    const DATA_TYPE: GeometryNodeAttributeStatisticDataType =
+        GeometryNodeAttributeStatisticDataType::Float;
+    const ATTRIBUTE_PIN: usize = GeometryNodeAttributeStatistic::PIN_ATTRIBUTE;
+    const FIRST_OUTPUT: usize = 0;
+}
+
+impl AttributeStatisticType for Vector {
+    const DATA_TYPE: GeometryNodeAttributeStatisticDataType =
+        GeometryNodeAttributeStatisticDataType::FloatVector;
+    const ATTRIBUTE_PIN: usize = GeometryNodeAttributeStatistic::PIN_ATTRIBUTE_0;
+    const FIRST_OUTPUT: usize = 8;
+}
+
+/// Computes mean, median, sum, min, max, range, standard deviation and variance of
+/// `attribute` over `domain` of `geo`.
+///
+/// Float and vector outputs share socket names ("Mean", ...), so outputs are
+/// addressed by index to pick the block that matches `T`.
+pub fn attribute_statistic<T: AttributeStatisticType>(
+    geo: NodeSocket<Geo>,
+    attribute: NodeSocket<T>,
+    domain: FieldDomain,
+) -> AttributeStatisticOutputs<T> {
+    let node = GeometryNodeAttributeStatistic::new()
+        .with_data_type(T::DATA_TYPE)
+        .with_domain(domain)
+        .with_geometry(geo)
+        .set_input(T::ATTRIBUTE_PIN, attribute);
+
+    let output = |i: usize| {
+        NodeSocket::<T>::new_output(format!("{}.outputs[{}]", node.name, T::FIRST_OUTPUT + i))
+    };
+
+    AttributeStatisticOutputs {
+        mean: output(0),
+        median: output(1),
+        sum: output(2),
+        min: output(3),
+        max: output(4),
+        range: output(5),
+        standard_deviation: output(6),
+        variance: output(7),
+    }
+}
+
+// attribute transfer ===================================================================
+
+/// How `transfer_attribute` picks the source element for each target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMode {
+    /// Interpolated value at the closest point on the source mesh surface
+    /// (`GeometryNodeSampleNearestSurface`).
+    NearestSurface,
+    /// Value of the closest source point (`GeometryNodeSampleNearest` + `GeometryNodeSampleIndex`).
+    Nearest,
+    /// Value of the source point with the same index (`GeometryNodeSampleIndex`).
+    Index,
+}
+
+/// Socket types the sampling nodes can transfer.
+pub trait TransferType: SocketDef {
+    const SURFACE_DATA_TYPE: GeometryNodeSampleNearestSurfaceDataType;
+    const INDEX_DATA_TYPE: GeometryNodeSampleIndexDataType;
+    const RAYCAST_DATA_TYPE: GeometryNodeRaycastDataType;
+}
+
+macro_rules! impl_transfer_type {
+    ($type:ident, $variant:ident) => {
+        impl TransferType for $type {
+            const SURFACE_DATA_TYPE: GeometryNodeSampleNearestSurfaceDataType =
+                GeometryNodeSampleNearestSurfaceDataType::$variant;
+            const INDEX_DATA_TYPE: GeometryNodeSampleIndexDataType =
+                GeometryNodeSampleIndexDataType::$variant;
+            const RAYCAST_DATA_TYPE: GeometryNodeRaycastDataType =
+                GeometryNodeRaycastDataType::$variant;
+        }
+    };
+}
+
+impl_transfer_type!(Float, Float);
+impl_transfer_type!(Int, Int);
+impl_transfer_type!(Vector, FloatVector);
+impl_transfer_type!(Color, FloatColor);
+impl_transfer_type!(Bool, Boolean);
+
+/// Samples `attribute` (a field on `source_geo`) for every target, located at `target_positions`.
+///
+/// `TransferMode::Index` matches elements by index, so `target_positions` is not used there.
+/// The point domain is sampled for `Nearest` and `Index`.
+/// The Value pins take the socket type of `data_type`, so they are set by index.
+pub fn transfer_attribute<T: TransferType>(
+    source_geo: NodeSocket<Geo>,
+    attribute: NodeSocket<T>,
+    target_positions: NodeSocket<Vector>,
+    transfer_mode: TransferMode,
+) -> NodeSocket<T> {
+    let sample_index = |index: Option<NodeSocket<Int>>| {
+        let node = GeometryNodeSampleIndex::new()
+            .with_data_type(T::INDEX_DATA_TYPE)
+            .with_domain(GeometryNodeSampleIndexDomain::Point)
+            .with_geometry(source_geo)
+            .set_input(GeometryNodeSampleIndex::PIN_VALUE, attribute);
+        match index {
+            Some(index) => node.with_index(index),
+            None => node,
+        }
+        .out_value()
+        .cast::<T>()
+    };
+
+    match transfer_mode {
+        TransferMode::NearestSurface => GeometryNodeSampleNearestSurface::new()
+            .with_data_type(T::SURFACE_DATA_TYPE)
+            .with_mesh(source_geo)
+            .set_input(GeometryNodeSampleNearestSurface::PIN_VALUE, attribute)
+            .with_sample_position(target_positions)
+            .out_value()
+            .cast::<T>(),
+        TransferMode::Nearest => {
+            let nearest = GeometryNodeSampleNearest::new()
+                .with_geometry(source_geo)
+                .with_sample_position(target_positions)
+                .out_index();
+            sample_index(Some(nearest))
+        }
+        TransferMode::Index => sample_index(None),
+    }
+}
+
+// raycast ==============================================================================
+
+/// Outputs of a `Raycast` node whose attribute has type `T`.
+#[derive(Clone, Debug)]
+pub struct Raycast<T> {
+    pub node: GeometryNodeRaycast,
+    _marker: PhantomData<T>,
+}
+
+impl<T: TransferType> Raycast<T> {
+    pub fn is_hit(&self) -> NodeSocket<Bool> {
+        self.node.out_is_hit()
+    }
+
+    pub fn hit_position(&self) -> NodeSocket<Vector> {
+        self.node.out_hit_position()
+    }
+
+    pub fn hit_normal(&self) -> NodeSocket<Vector> {
+        self.node.out_hit_normal()
+    }
+
+    pub fn hit_distance(&self) -> NodeSocket<Float> {
+        self.node.out_hit_distance()
+    }
+
+    /// `attribute` of `target` interpolated at the hit point.
+    pub fn attribute(&self) -> NodeSocket<T> {
+        self.node.out_attribute().cast::<T>()
+    }
+}
+
+/// Casts rays from `ray_start` along `ray_direction` onto `target`, sampling `attribute`
+/// (a field on `target`) where they hit. `data_type` is inferred from `T`.
+pub fn raycast<T: TransferType>(
+    target: NodeSocket<Geo>,
+    attribute: NodeSocket<T>,
+    ray_start: NodeSocket<Vector>,
+    ray_direction: NodeSocket<Vector>,
+    ray_length: impl Into<NodeSocket<Float>>,
+) -> Raycast<T> {
+    // The Attribute pin takes the socket type of `data_type`, so it is set by index.
+    let node = GeometryNodeRaycast::new()
+        .with_data_type(T::RAYCAST_DATA_TYPE)
+        .with_target_geometry(target)
+        .set_input(GeometryNodeRaycast::PIN_ATTRIBUTE, attribute)
+        .with_source_position(ray_start)
+        .with_ray_direction(ray_direction)
+        .with_ray_length(ray_length.into());
+    Raycast {
+        node,
+        _marker: PhantomData,
+    }
+}
+
+// attribute capture ====================================================================
+
+/// Socket types `capture_attribute` can capture (the capture item types of the node).
+pub trait CaptureType: SocketDef {}
+
+impl CaptureType for Float {}
+impl CaptureType for Int {}
+impl CaptureType for Vector {}
+impl CaptureType for Color {}
+impl CaptureType for Bool {}
+impl CaptureType for Rotation {}
+impl CaptureType for Matrix {}
+
+/// Captures `value` on `domain` of `geometry`, returning the geometry and the captured field.
+///
+/// The node has no `data_type` property since Blender 4.2; its single capture item is
+/// created with the socket type of `T` (`capture_items.new`), so value pins are set by index.
+pub fn capture_attribute<T: CaptureType>(
+    geometry: NodeSocket<Geo>,
+    value: NodeSocket<T>,
+    domain: GeometryNodeCaptureAttributeDomain,
+) -> (NodeSocket<Geo>, NodeSocket<T>) {
+    let node = GeometryNodeCaptureAttribute::new()
+        .with_domain(domain)
+        .with_geometry(geometry);
+
+    let mut post_code = String::new();
+    let _ = writeln!(&mut post_code, "{}.capture_items.clear()", node.name);
+    let _ = writeln!(
+        &mut post_code,
+        "{}.capture_items.new('{}', '{}')",
+        node.name,
+        T::socket_type(),
+        T::default_name()
+    );
+    update_post_creation(&node.name, post_code);
+
+    add_custom_link(&value, &node.name, 1);
+
+    (
+        node.out_geometry(),
+        NodeSocket::new_output(format!("{}.outputs[1]", node.name)),
+    )
+}
+
+// geometry =============================================================================
+
+/// Joins a runtime-sized list of geometries with a single `GeometryNodeJoinGeometry`.
+///
+/// An empty list yields an empty mesh (`GeometryNodeMeshLine` with count 0).
+pub fn join_geometry_vec(geos: Vec<NodeSocket<Geo>>) -> NodeSocket<Geo> {
+    if geos.is_empty() {
+        return GeometryNodeMeshLine::new().with_count(0).out_mesh();
+    }
+
+    geos.into_iter()
+        .fold(GeometryNodeJoinGeometry::new(), |join, geo| {
+            join.append_geometry(geo)
+        })
+        .out_geometry()
+}
+
+/// Turns mesh elements of the given `mode` (vertices, edges, faces or corners) into a point cloud.
+pub fn mesh_to_points(
+    mesh: NodeSocket<Geo>,
+    mode: GeometryNodeMeshToPointsMode,
+) -> NodeSocket<Geo> {
+    GeometryNodeMeshToPoints::new()
+        .with_mode(mode)
+        .with_mesh(mesh)
+        .out_points()
+}
+
+/// Turns a point cloud into a mesh of loose vertices.
+pub fn points_to_vertices(points: NodeSocket<Geo>) -> NodeSocket<Geo> {
+    GeometryNodePointsToVertices::new()
+        .with_points(points)
+        .out_mesh()
+}
+
+/// Moves every point of `geo` by `offset`, leaving the Position pin at its implicit default.
+pub fn displace(geo: NodeSocket<Geo>, offset: NodeSocket<Vector>) -> NodeSocket<Geo> {
+    set_position_offset(geo, offset, None)
+}
+
+/// Moves the selected points (all if `selection` is `None`) of `geo` to `position`.
+pub fn set_position(
+    geo: NodeSocket<Geo>,
+    position: NodeSocket<Vector>,
+    selection: Option<NodeSocket<Bool>>,
+) -> NodeSocket<Geo> {
+    let node = GeometryNodeSetPosition::new()
+        .with_geometry(geo)
+        .with_position(position);
+    match selection {
+        Some(selection) => node.with_selection(selection),
+        None => node,
+    }
+    .out_geometry()
+}
+
+/// Moves the selected points (all if `selection` is `None`) of `geo` by `offset`.
+pub fn set_position_offset(
+    geo: NodeSocket<Geo>,
+    offset: NodeSocket<Vector>,
+    selection: Option<NodeSocket<Bool>>,
+) -> NodeSocket<Geo> {
+    let node = GeometryNodeSetPosition::new()
+        .with_geometry(geo)
+        .with_offset(offset);
+    match selection {
+        Some(selection) => node.with_selection(selection),
+        None => node,
+    }
+    .out_geometry()
+}
+
+/// Assigns `material` (e.g. a material name) to the selected faces (all if `selection` is
+/// `None`) of `geo`, for multi-material meshes.
+pub fn set_material(
+    geo: NodeSocket<Geo>,
+    material: impl Into<NodeSocket<Material>>,
+    selection: Option<NodeSocket<Bool>>,
+) -> NodeSocket<Geo> {
+    let node = GeometryNodeSetMaterial::new()
+        .with_geometry(geo)
+        .with_material(material.into());
+    match selection {
+        Some(selection) => node.with_selection(selection),
+        None => node,
+    }
+    .out_geometry()
+}
+
+// color attributes =====================================================================
+
+/// Stores `color` as a point-domain color attribute named `attr_name` (vertex colors).
+pub fn vertex_color_attribute(
+    geo: NodeSocket<Geo>,
+    color: NodeSocket<Color>,
+    attr_name: &str,
+) -> NodeSocket<Geo> {
+    store_color_attribute(
+        geo,
+        color,
+        attr_name,
+        GeometryNodeStoreNamedAttributeDomain::Point,
+    )
+}
+
+/// Stores `color` as a face-domain color attribute named `attr_name`.
+pub fn face_color_attribute(
+    geo: NodeSocket<Geo>,
+    color: NodeSocket<Color>,
+    attr_name: &str,
+) -> NodeSocket<Geo> {
+    store_color_attribute(
+        geo,
+        color,
+        attr_name,
+        GeometryNodeStoreNamedAttributeDomain::Face,
+    )
+}
+
+/// Reads the color attribute `attr_name` (`GeometryNodeInputNamedAttribute`, FLOAT_COLOR).
+pub fn read_color_attribute(attr_name: &str) -> NodeSocket<Color> {
+    GeometryNodeInputNamedAttribute::new()
+        .with_data_type(GeometryNodeInputNamedAttributeDataType::FloatColor)
+        .with_name(attr_name)
+        .out_attribute()
+        .cast::<Color>()
+}
+
+fn store_color_attribute(
+    geo: NodeSocket<Geo>,
+    color: NodeSocket<Color>,
+    attr_name: &str,
+    domain: GeometryNodeStoreNamedAttributeDomain,
+) -> NodeSocket<Geo> {
+    // The Value pin takes the socket type of `data_type`, so it is set by index.
+    GeometryNodeStoreNamedAttribute::new()
+        .with_data_type(GeometryNodeStoreNamedAttributeDataType::FloatColor)
+        .with_domain(domain)
+        .with_geometry(geo)
+        .with_name(attr_name)
+        .set_input(GeometryNodeStoreNamedAttribute::PIN_VALUE, color)
+        .out_geometry()
+}
+
+// selection ============================================================================
+//
+// Selection inputs (e.g. `GeometryNodeDeleteGeometry::with_selection`) take a Bool field.
+// Build it with a comparison (`ramen_math!(x > 0.0)`, `threshold`) rather than a 1.0/0.0 float,
+// and wrap it in `select_mask` where it is handed to a selection pin.
+
+/// Marks `condition` as a selection. Passes the Bool field through unchanged.
+pub fn select_mask(condition: NodeSocket<Bool>) -> NodeSocket<Bool> {
+    condition
+}
+
+/// Selects where `value >= edge` (`FunctionNodeCompare`, Float GREATER_EQUAL).
+pub fn threshold(
+    value: impl Into<NodeSocket<Float>>,
+    edge: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Bool> {
+    FunctionNodeCompare::new()
+        .with_data_type(FunctionNodeCompareDataType::Float)
+        .with_operation(FunctionNodeCompareOperation::GreaterEqual)
+        .with_a(value)
+        .with_b(edge)
+        .out_result()
+}
+
+/// Splits `geo` by `selection` evaluated on `domain`: `(selected, inverted)`
+/// (`GeometryNodeSeparateGeometry`).
+pub fn separate_geometry(
+    geo: NodeSocket<Geo>,
+    selection: NodeSocket<Bool>,
+    domain: GeometryNodeSeparateGeometryDomain,
+) -> (NodeSocket<Geo>, NodeSocket<Geo>) {
+    let node = GeometryNodeSeparateGeometry::new()
+        .with_domain(domain)
+        .with_geometry(geo)
+        .with_selection(selection);
+    (node.out_selection(), node.out_inverted())
+}
+
+// curves ===============================================================================
+
+/// Total length of all splines in `curve`.
+pub fn curve_length(curve: NodeSocket<Geo>) -> NodeSocket<Float> {
+    GeometryNodeCurveLength::new()
+        .with_curve(curve)
+        .out_length()
+}
+
+/// Per-spline `(length, point_count)` field.
+///
+/// `GeometryNodeSplineLength` has no geometry input: like Position, it is evaluated
+/// on whichever curve consumes the field.
+pub fn spline_length() -> (NodeSocket<Float>, NodeSocket<Int>) {
+    let node = GeometryNodeSplineLength::new();
+    (node.out_length(), node.out_point_count())
+}
+
+/// Per-point `(factor, length, index)` along its spline.
+///
+/// `factor` runs from 0.0 to 1.0, `length` is the distance from the spline start.
+pub fn spline_parameter() -> (NodeSocket<Float>, NodeSocket<Float>, NodeSocket<Int>) {
+    let node = GeometryNodeSplineParameter::new();
+    (node.out_factor(), node.out_length(), node.out_index())
+}
+
+/// Places a realized copy of `instance` at the midpoint of every segment of `curve`.
+///
+/// The curve is converted to a mesh so that its edges can be turned into points
+/// (`GeometryNodeMeshToPoints` in EDGES mode). Instances keep their own orientation.
+pub fn instance_on_edges(curve: NodeSocket<Geo>, instance: NodeSocket<Geo>) -> NodeSocket<Geo> {
+    let mesh = GeometryNodeCurveToMesh::new().with_curve(curve).out_mesh();
+    let midpoints = mesh_to_points(mesh, GeometryNodeMeshToPointsMode::Edges);
+    let instances = GeometryNodeInstanceOnPoints::new()
+        .with_points(midpoints)
+        .with_instance(instance)
+        .out_instances();
+
+    GeometryNodeRealizeInstances::new()
+        .with_geometry(instances)
+        .out_geometry()
+}
+
+/// Places a realized copy of `instance` at every evaluated point of `curve`.
+///
+/// `rotation_mode` is either `"CURVE"` (align instances to the curve tangent and normal)
+/// or `"NONE"` (keep the instance orientation).
+pub fn instance_on_curve_points(
+    curve: NodeSocket<Geo>,
+    instance: NodeSocket<Geo>,
+    rotation_mode: &str,
+) -> NodeSocket<Geo> {
+    let points = GeometryNodeCurveToPoints::new()
+        .with_mode(GeometryNodeCurveToPointsMode::Evaluated)
+        .with_curve(curve);
+
+    let on_points = GeometryNodeInstanceOnPoints::new()
+        .with_points(points.out_points())
+        .with_instance(instance);
+    let on_points = match rotation_mode {
+        "CURVE" => on_points.with_rotation(points.out_rotation()),
+        "NONE" => on_points,
+        other => panic!(
+            "instance_on_curve_points: unknown rotation_mode '{}' (expected \"CURVE\" or \"NONE\")",
+            other
+        ),
+    };
+
+    GeometryNodeRealizeInstances::new()
+        .with_geometry(on_points.out_instances())
+        .out_geometry()
+}
+
+// compositor ===========================================================================
+
+/// Writes every pass into a single multilayer EXR at `path` (directory and file name, e.g.
+/// `//render/frame_`).
+///
+/// Creates a `CompositorNodeOutputFile` set to OPEN_EXR_MULTILAYER through the Blender 5.0
+/// item API (`directory` / `file_name` / `file_output_items`), replaces its default item
+/// with one color item per pass (in order) and links each image into its item.
+pub fn multilayer_output(
+    path: &str,
+    passes: &[(&str, NodeSocket<Color>)],
+) -> CompositorNodeOutputFile {
+    let node = CompositorNodeOutputFile::new();
+    let (directory, file_name) = match path.rfind('/') {
+        Some(i) => path.split_at(i + 1),
+        None => ("", path),
+    };
+    update_property(&node.name, "directory", python_string_literal(directory));
+    update_property(&node.name, "file_name", python_string_literal(file_name));
+
+    // The multilayer format is only offered once the media type allows it.
+    let mut post_code = String::new();
+    let _ = writeln!(
+        &mut post_code,
+        "{}.format.media_type = 'MULTI_LAYER_IMAGE'",
+        node.name
+    );
+    let _ = writeln!(
+        &mut post_code,
+        "{}.format.file_format = 'OPEN_EXR_MULTILAYER'",
+        node.name
+    );
+    let _ = writeln!(&mut post_code, "{}.file_output_items.clear()", node.name);
+    for (pass_name, _) in passes {
+        let _ = writeln!(
+            &mut post_code,
+            "{}.file_output_items.new('RGBA', {})",
+            node.name,
+            python_string_literal(pass_name)
+        );
+    }
+    update_post_creation(&node.name, post_code);
+
+    for (i, (_, image)) in passes.iter().enumerate() {
+        add_custom_link(image, &node.name, i);
+    }
+
+    node
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+
+    #[test]
+    fn test_project_to_plane() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let v = NodeSocket::<Vector>::new_output("v_node.outputs[0]");
+        let n = NodeSocket::<Vector>::new_output("n_node.outputs[0]");
+        let _ = project_to_plane(v, n);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        for node in &nodes {
+            assert_eq!(node.bl_idname, "ShaderNodeVectorMath");
+        }
+        assert_eq!(
+            nodes[0].properties.get("operation").unwrap(),
+            "\"DOT_PRODUCT\""
+        );
+        assert_eq!(nodes[1].properties.get("operation").unwrap(), "\"SCALE\"");
+        assert!(
+            nodes[1].inputs.get(&3).unwrap()[0]
+                .expr
+                .contains(".outputs[")
+        );
+        assert_eq!(
+            nodes[2].properties.get("operation").unwrap(),
+            "\"SUBTRACT\""
+        );
+        assert_eq!(nodes[2].inputs.get(&0).unwrap()[0].expr, v.python_expr());
+    }
+
+    #[test]
+    fn test_reflect_and_refract() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let v = NodeSocket::<Vector>::new_output("v_node.outputs[0]");
+        let n = NodeSocket::<Vector>::new_output("n_node.outputs[0]");
+        let _ = reflect(v, n);
+        let _ = refract(v, n, 1.45);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeVectorMath");
+        assert_eq!(nodes[0].properties.get("operation").unwrap(), "\"REFLECT\"");
+        assert_eq!(nodes[0].inputs.get(&1).unwrap()[0].expr, n.python_expr());
+
+        assert_eq!(nodes[1].bl_idname, "ShaderNodeVectorMath");
+        assert_eq!(nodes[1].properties.get("operation").unwrap(), "\"REFRACT\"");
+        assert_eq!(nodes[1].inputs.get(&3).unwrap()[0].expr, "1.4500");
+        assert!(nodes[1].inputs.get(&3).unwrap()[0].is_literal);
+    }
+
+    #[test]
+    fn test_face_forward() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let v = NodeSocket::<Vector>::new_output("v_node.outputs[0]");
+        let i = NodeSocket::<Vector>::new_output("i_node.outputs[0]");
+        let r = NodeSocket::<Vector>::new_output("r_node.outputs[0]");
+        let _ = face_forward(v, i, r);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(
+            nodes[0].properties.get("operation").unwrap(),
+            "\"FACEFORWARD\""
+        );
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, v.python_expr());
+        assert_eq!(nodes[0].inputs.get(&1).unwrap()[0].expr, i.python_expr());
+        assert_eq!(nodes[0].inputs.get(&2).unwrap()[0].expr, r.python_expr());
+    }
+
+    fn statistic_exprs<T>(out: &AttributeStatisticOutputs<T>) -> Vec<String> {
+        vec![
+            out.mean.python_expr(),
+            out.median.python_expr(),
+            out.sum.python_expr(),
+            out.min.python_expr(),
+            out.max.python_expr(),
+            out.range.python_expr(),
+            out.standard_deviation.python_expr(),
+            out.variance.python_expr(),
+        ]
+    }
+
+    #[test]
+    fn test_attribute_statistic_float() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let geo = NodeSocket::<Geo>::new_output("geo_node.outputs[0]");
+        let attr = NodeSocket::<Float>::new_output("attr_node.outputs[0]");
+        let out = attribute_statistic(geo, attr, FieldDomain::Face);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeAttributeStatistic");
+        assert_eq!(node.properties.get("data_type").unwrap(), "\"FLOAT\"");
+        assert_eq!(node.properties.get("domain").unwrap(), "\"FACE\"");
+        assert_eq!(node.inputs.get(&0).unwrap()[0].expr, geo.python_expr());
+        assert_eq!(node.inputs.get(&2).unwrap()[0].expr, attr.python_expr());
+        assert!(!node.inputs.contains_key(&3));
+
+        let expected: Vec<String> = (0..8)
+            .map(|i| format!("{}.outputs[{}]", node.name, i))
+            .collect();
+        assert_eq!(statistic_exprs(&out), expected);
+    }
+
+    #[test]
+    fn test_raycast_infers_data_type_and_exposes_outputs() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let target = NodeSocket::<Geo>::new_output("target_node.outputs[0]");
+        let color = NodeSocket::<Color>::new_output("color_node.outputs[0]");
+        let start = NodeSocket::<Vector>::new_output("start_node.outputs[0]");
+        let down = NodeSocket::<Vector>::from((0.0, 0.0, -1.0));
+        let ray = raycast(target, color, start, down, 10.0);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeRaycast");
+        assert_eq!(node.properties.get("data_type").unwrap(), "\"FLOAT_COLOR\"");
+        assert_eq!(
+            node.inputs[&GeometryNodeRaycast::PIN_ATTRIBUTE][0].expr,
+            color.python_expr()
+        );
+        assert_eq!(
+            node.inputs[&GeometryNodeRaycast::PIN_SOURCE_POSITION][0].expr,
+            start.python_expr()
+        );
+        assert_eq!(
+            node.inputs[&GeometryNodeRaycast::PIN_RAY_LENGTH][0].expr,
+            "10.0000"
+        );
+
+        let outputs = [
+            ray.is_hit().python_expr(),
+            ray.hit_position().python_expr(),
+            ray.hit_normal().python_expr(),
+            ray.hit_distance().python_expr(),
+            ray.attribute().python_expr(),
+        ];
+        let expected: Vec<String> = [
+            "Is Hit",
+            "Hit Position",
+            "Hit Normal",
+            "Hit Distance",
+            "Attribute",
+        ]
+        .iter()
+        .map(|socket| format!("{}.outputs[\"{}\"]", node.name, socket))
+        .collect();
+        assert_eq!(outputs.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_attribute_statistic_vector() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let geo = NodeSocket::<Geo>::new_output("geo_node.outputs[0]");
+        let attr = NodeSocket::<Vector>::new_output("attr_node.outputs[0]");
+        let out = attribute_statistic(geo, attr, FieldDomain::Point);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(
+            node.properties.get("data_type").unwrap(),
+            "\"FLOAT_VECTOR\""
+        );
+        assert_eq!(node.properties.get("domain").unwrap(), "\"POINT\"");
+        assert_eq!(node.inputs.get(&3).unwrap()[0].expr, attr.python_expr());
+        assert!(!node.inputs.contains_key(&2));
+
+        let expected: Vec<String> = (8..16)
+            .map(|i| format!("{}.outputs[{}]", node.name, i))
+            .collect();
+        assert_eq!(statistic_exprs(&out), expected);
+    }
+
+    #[test]
+    fn test_multilayer_output() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let beauty = NodeSocket::<Color>::new_output("rl.outputs[\"Image\"]");
+        let depth = NodeSocket::<Color>::new_output("rl.outputs[\"Depth\"]");
+        let normal = NodeSocket::<Color>::new_output("rl.outputs[\"Normal\"]");
+        let node = multilayer_output(
+            "//render/frame_",
+            &[("Beauty", beauty), ("Depth", depth), ("Normal", normal)],
+        );
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let data = &nodes[0];
+        assert_eq!(data.bl_idname, "CompositorNodeOutputFile");
+        assert_eq!(data.properties.get("directory").unwrap(), "\"//render/\"");
+        assert_eq!(data.properties.get("file_name").unwrap(), "\"frame_\"");
+        assert!(!data.properties.contains_key("base_path"));
+
+        let post = &data.post_creation_script;
+        let media = post
+            .find(".format.media_type = 'MULTI_LAYER_IMAGE'")
+            .unwrap();
+        let format = post
+            .find(".format.file_format = 'OPEN_EXR_MULTILAYER'")
+            .unwrap();
+        assert!(media < format);
+        assert!(!post.contains("file_slots"));
+        assert_eq!(post.matches("file_output_items.clear()").count(), 1);
+        assert_eq!(post.matches("file_output_items.new(").count(), 3);
+        let b = post
+            .find("file_output_items.new('RGBA', \"Beauty\")")
+            .unwrap();
+        let d = post
+            .find("file_output_items.new('RGBA', \"Depth\")")
+            .unwrap();
+        let n = post
+            .find("file_output_items.new('RGBA', \"Normal\")")
+            .unwrap();
+        assert!(post.find("clear()").unwrap() < b && b < d && d < n);
+
+        let links = &data.custom_links_script;
+        assert!(links.contains(&format!(
+            "tree.links.new({}, {}.inputs[0])",
+            beauty.python_expr(),
+            node.name
+        )));
+        assert!(links.contains(&format!(
+            "tree.links.new({}, {}.inputs[2])",
+            normal.python_expr(),
+            node.name
+        )));
+    }
+
+    #[test]
+    fn test_curve_and_spline_lengths() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let curve = NodeSocket::<Geo>::new_output("curve_node.outputs[0]");
+        let total = curve_length(curve);
+        let (length, point_count) = spline_length();
+        let (factor, param_length, index) = spline_parameter();
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodeCurveLength");
+        assert_eq!(
+            nodes[0].inputs.get(&0).unwrap()[0].expr,
+            curve.python_expr()
+        );
+        assert_eq!(
+            total.python_expr(),
+            format!("{}.outputs[\"Length\"]", nodes[0].name)
+        );
+
+        assert_eq!(nodes[1].bl_idname, "GeometryNodeSplineLength");
+        assert!(nodes[1].inputs.is_empty());
+        assert_eq!(
+            length.python_expr(),
+            format!("{}.outputs[\"Length\"]", nodes[1].name)
+        );
+        assert_eq!(
+            point_count.python_expr(),
+            format!("{}.outputs[\"Point Count\"]", nodes[1].name)
+        );
+
+        assert_eq!(nodes[2].bl_idname, "GeometryNodeSplineParameter");
+        assert_eq!(
+            factor.python_expr(),
+            format!("{}.outputs[\"Factor\"]", nodes[2].name)
+        );
+        assert_eq!(
+            param_length.python_expr(),
+            format!("{}.outputs[\"Length\"]", nodes[2].name)
+        );
+        assert_eq!(
+            index.python_expr(),
+            format!("{}.outputs[\"Index\"]", nodes[2].name)
+        );
+    }
+
+    #[test]
+    fn test_separate_geometry_returns_both_parts() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let geo = NodeSocket::<Geo>::new_output("grid.outputs[0]");
+        let mask = NodeSocket::<Bool>::new_output("compare.outputs[0]");
+        let (selected, inverted) =
+            separate_geometry(geo, mask, GeometryNodeSeparateGeometryDomain::Face);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeSeparateGeometry");
+        assert_eq!(node.properties["domain"], "\"FACE\"");
+        assert_eq!(
+            node.inputs[&GeometryNodeSeparateGeometry::PIN_SELECTION][0].expr,
+            "compare.outputs[0]"
+        );
+        assert_eq!(
+            selected.python_expr(),
+            format!("{}.outputs[\"Selection\"]", node.name)
+        );
+        assert_eq!(
+            inverted.python_expr(),
+            format!("{}.outputs[\"Inverted\"]", node.name)
+        );
+    }
+
+    #[test]
+    fn test_threshold_and_select_mask() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let noise = NodeSocket::<Float>::new_output("noise_node.outputs[0]");
+        let selected: NodeSocket<Bool> = select_mask(threshold(noise, 0.5));
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "FunctionNodeCompare");
+        assert_eq!(node.properties.get("data_type").unwrap(), "\"FLOAT\"");
+        assert_eq!(
+            node.properties.get("operation").unwrap(),
+            "\"GREATER_EQUAL\""
+        );
+        assert_eq!(node.inputs.get(&0).unwrap()[0].expr, noise.python_expr());
+        assert_eq!(node.inputs.get(&1).unwrap()[0].expr, "0.5000");
+        assert_eq!(
+            selected.python_expr(),
+            format!("{}.outputs[\"Result\"]", node.name)
+        );
+    }
+
+    #[test]
+    fn test_join_geometry_vec() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let geo = |i: usize| NodeSocket::<Geo>::new_output(format!("geo_{i}.outputs[0]"));
+        let link_count = |nodes: &[context::NodeData]| {
+            nodes
+                .iter()
+                .map(|n| n.links_script().matches("tree.links.new").count())
+                .sum::<usize>()
+        };
+
+        context::enter_zone();
+        let _ = join_geometry_vec(vec![geo(0)]);
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodeJoinGeometry");
+        assert_eq!(link_count(&nodes), 1);
+
+        context::enter_zone();
+        let joined = join_geometry_vec((0..3).map(geo).collect());
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].inputs.get(&0).unwrap().len(), 3);
+        assert_eq!(link_count(&nodes), 3);
+        assert_eq!(
+            joined.python_expr(),
+            format!("{}.outputs[\"Geometry\"]", nodes[0].name)
+        );
+
+        context::enter_zone();
+        let _ = join_geometry_vec(vec![]);
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodeMeshLine");
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, "0");
+        assert_eq!(link_count(&nodes), 0);
+    }
+
+    #[test]
+    fn test_mesh_to_points_and_back() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let mesh = NodeSocket::<Geo>::new_output("grid.outputs[0]");
+        let points = mesh_to_points(mesh, GeometryNodeMeshToPointsMode::Faces);
+        let verts = points_to_vertices(points);
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodeMeshToPoints");
+        assert_eq!(nodes[0].properties.get("mode").unwrap(), "\"FACES\"");
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, mesh.python_expr());
+        assert_eq!(
+            points.python_expr(),
+            format!("{}.outputs[\"Points\"]", nodes[0].name)
+        );
+
+        assert_eq!(nodes[1].bl_idname, "GeometryNodePointsToVertices");
+        assert_eq!(
+            nodes[1].inputs.get(&0).unwrap()[0].expr,
+            points.python_expr()
+        );
+        assert_eq!(
+            verts.python_expr(),
+            format!("{}.outputs[\"Mesh\"]", nodes[1].name)
+        );
+    }
+
+    #[test]
+    fn test_set_position_variants() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let pin = GeometryNodeSetPosition::PIN_POSITION;
+        let offset_pin = GeometryNodeSetPosition::PIN_OFFSET;
+        let selection_pin = GeometryNodeSetPosition::PIN_SELECTION;
+
+        context::enter_zone();
+        let geo = NodeSocket::<Geo>::new_output("mesh.outputs[0]");
+        let offset = NodeSocket::<Vector>::from((0.0, 0.0, 1.0));
+        let mask = NodeSocket::<Bool>::new_output("cmp.outputs[0]");
+        let displaced = displace(geo, offset);
+        let _ = set_position(displaced, Vector::zero(), Some(mask));
+        let _ = set_position_offset(geo, offset, Some(mask));
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 3);
+        for node in &nodes {
+            assert_eq!(node.bl_idname, "GeometryNodeSetPosition");
+        }
+
+        assert_eq!(
+            nodes[0].inputs.get(&offset_pin).unwrap()[0].expr,
+            offset.python_expr()
+        );
+        assert!(!nodes[0].inputs.contains_key(&pin));
+        assert!(!nodes[0].inputs.contains_key(&selection_pin));
+
+        assert!(nodes[1].inputs.contains_key(&pin));
+        assert!(!nodes[1].inputs.contains_key(&offset_pin));
+        assert_eq!(
+            nodes[1].inputs.get(&selection_pin).unwrap()[0].expr,
+            mask.python_expr()
+        );
+
+        assert!(nodes[2].inputs.contains_key(&offset_pin));
+        assert!(!nodes[2].inputs.contains_key(&pin));
+        assert!(nodes[2].inputs.contains_key(&selection_pin));
+    }
+
+    #[test]
+    fn test_set_material_selection() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let selection_pin = GeometryNodeSetMaterial::PIN_SELECTION;
+
+        context::enter_zone();
+        let geo = NodeSocket::<Geo>::new_output("mesh.outputs[0]");
+        let top = NodeSocket::<Bool>::new_output("cmp.outputs[0]");
+        let base = set_material(geo, "Stone", None);
+        let _ = set_material(base, "Moss", Some(top));
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[0].inputs[&GeometryNodeSetMaterial::PIN_MATERIAL][0].expr,
+            "bpy.data.materials.get(\"Stone\")"
+        );
+        assert!(!nodes[0].inputs.contains_key(&selection_pin));
+        assert_eq!(
+            nodes[1].inputs[&GeometryNodeSetMaterial::PIN_GEOMETRY][0].expr,
+            base.python_expr()
+        );
+        assert_eq!(nodes[1].inputs[&selection_pin][0].expr, "cmp.outputs[0]");
+    }
+
+    #[test]
+    fn test_transfer_attribute_modes_and_types() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let source = NodeSocket::<Geo>::new_output("src.outputs[0]");
+        let targets = NodeSocket::<Vector>::new_output("pos.outputs[0]");
+
+        context::enter_zone();
+        let f = NodeSocket::<Float>::new_output("f.outputs[0]");
+        let v = NodeSocket::<Vector>::new_output("v.outputs[0]");
+        let c = NodeSocket::<Color>::new_output("c.outputs[0]");
+        let _ = transfer_attribute(source, f, targets, TransferMode::NearestSurface);
+        let _ = transfer_attribute(source, v, targets, TransferMode::NearestSurface);
+        let sampled = transfer_attribute(source, c, targets, TransferMode::NearestSurface);
+        let nodes = context::exit_zone();
+
+        let data_types: Vec<&str> = nodes
+            .iter()
+            .map(|n| n.properties.get("data_type").unwrap().as_str())
+            .collect();
+        assert_eq!(
+            data_types,
+            vec!["\"FLOAT\"", "\"FLOAT_VECTOR\"", "\"FLOAT_COLOR\""]
+        );
+        assert_eq!(nodes[2].bl_idname, "GeometryNodeSampleNearestSurface");
+        assert_eq!(nodes[2].inputs.get(&1).unwrap()[0].expr, c.python_expr());
+        assert_eq!(
+            nodes[2].inputs.get(&3).unwrap()[0].expr,
+            targets.python_expr()
+        );
+        assert_eq!(
+            sampled.python_expr(),
+            format!("{}.outputs[\"Value\"]", nodes[2].name)
+        );
+
+        context::enter_zone();
+        let v = NodeSocket::<Vector>::new_output("v.outputs[0]");
+        let _ = transfer_attribute(source, v, targets, TransferMode::Nearest);
+        let _ = transfer_attribute(source, v, targets, TransferMode::Index);
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodeSampleNearest");
+        assert_eq!(nodes[1].bl_idname, "GeometryNodeSampleIndex");
+        assert_eq!(
+            nodes[1].properties.get("data_type").unwrap(),
+            "\"FLOAT_VECTOR\""
+        );
+        assert_eq!(
+            nodes[1].inputs.get(&2).unwrap()[0].expr,
+            format!("{}.outputs[\"Index\"]", nodes[0].name)
+        );
+        assert_eq!(nodes[2].bl_idname, "GeometryNodeSampleIndex");
+        assert!(!nodes[2].inputs.contains_key(&2));
+    }
+
+    #[test]
+    fn test_color_attributes() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let geo = NodeSocket::<Geo>::new_output("mesh.outputs[0]");
+        let color = Color::white();
+        let painted = vertex_color_attribute(geo, color, "Col");
+        let _ = face_color_attribute(painted, color, "FaceCol");
+        let read = read_color_attribute("Col");
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 3);
+        for (node, domain) in nodes[..2].iter().zip(["\"POINT\"", "\"FACE\""]) {
+            assert_eq!(node.bl_idname, "GeometryNodeStoreNamedAttribute");
+            assert_eq!(node.properties.get("data_type").unwrap(), "\"FLOAT_COLOR\"");
+            assert_eq!(node.properties.get("domain").unwrap(), domain);
+            assert_eq!(
+                node.inputs
+                    .get(&GeometryNodeStoreNamedAttribute::PIN_VALUE)
+                    .unwrap()[0]
+                    .expr,
+                color.python_expr()
+            );
+        }
+        assert_eq!(nodes[0].inputs.get(&2).unwrap()[0].expr, "\"Col\"");
+        assert_eq!(
+            nodes[1].inputs.get(&0).unwrap()[0].expr,
+            painted.python_expr()
+        );
+
+        assert_eq!(nodes[2].bl_idname, "GeometryNodeInputNamedAttribute");
+        assert_eq!(
+            nodes[2].properties.get("data_type").unwrap(),
+            "\"FLOAT_COLOR\""
+        );
+        assert_eq!(
+            read.python_expr(),
+            format!("{}.outputs[\"Attribute\"]", nodes[2].name)
+        );
+    }
+
+    #[test]
+    fn test_instance_on_curve_points_chain() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let curve = NodeSocket::<Geo>::new_output("curve_node.outputs[0]");
+        let instance = NodeSocket::<Geo>::new_output("cube_node.outputs[0]");
+        let _ = instance_on_curve_points(curve, instance, "CURVE");
+        let _ = instance_on_curve_points(curve, instance, "NONE");
+
+        let nodes = context::exit_zone();
+        let ids: Vec<&str> = nodes.iter().map(|n| n.bl_idname.as_str()).collect();
+        assert_eq!(
+            ids[..3],
+            [
+                "GeometryNodeCurveToPoints",
+                "GeometryNodeInstanceOnPoints",
+                "GeometryNodeRealizeInstances"
+            ]
+        );
+        assert_eq!(ids.len(), 6);
+
+        let (to_points, on_points, realize) = (&nodes[0], &nodes[1], &nodes[2]);
+        assert_eq!(to_points.properties.get("mode").unwrap(), "\"EVALUATED\"");
+        assert_eq!(
+            to_points.inputs.get(&0).unwrap()[0].expr,
+            curve.python_expr()
+        );
+        assert!(
+            on_points.inputs.get(&0).unwrap()[0]
+                .expr
+                .starts_with(&to_points.name)
+        );
+        assert_eq!(
+            on_points.inputs.get(&2).unwrap()[0].expr,
+            instance.python_expr()
+        );
+        assert!(
+            on_points.inputs.get(&5).unwrap()[0]
+                .expr
+                .starts_with(&to_points.name)
+        );
+        assert!(
+            realize.inputs.get(&0).unwrap()[0]
+                .expr
+                .starts_with(&on_points.name)
+        );
+
+        // "NONE" leaves the rotation pin unset.
+        assert!(!nodes[4].inputs.contains_key(&5));
+    }
+
+    #[test]
+    fn test_instance_on_edges_chain() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let curve = NodeSocket::<Geo>::new_output("curve_node.outputs[0]");
+        let instance = NodeSocket::<Geo>::new_output("cube_node.outputs[0]");
+        let _ = instance_on_edges(curve, instance);
+
+        let nodes = context::exit_zone();
+        let ids: Vec<&str> = nodes.iter().map(|n| n.bl_idname.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "GeometryNodeCurveToMesh",
+                "GeometryNodeMeshToPoints",
+                "GeometryNodeInstanceOnPoints",
+                "GeometryNodeRealizeInstances"
+            ]
+        );
+        assert_eq!(nodes[1].properties.get("mode").unwrap(), "\"EDGES\"");
+    }
+
+    #[test]
+    fn test_capture_attribute() {
+        use crate::core::nodes::GeometryNodeCaptureAttributeDomain;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let geo = NodeSocket::<Geo>::new_output("mesh.outputs[0]");
+        let normal = NodeSocket::<Vector>::new_output("normal.outputs[0]");
+        let (captured_geo, captured_normal) =
+            capture_attribute(geo, normal, GeometryNodeCaptureAttributeDomain::Face);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeCaptureAttribute");
+        assert_eq!(node.properties.get("domain").unwrap(), "\"FACE\"");
+        assert!(node.post_creation_script.contains(&format!(
+            "{}.capture_items.new('VECTOR', 'Vector')",
+            node.name
+        )));
+        assert!(node.custom_links_script.contains(&format!(
+            "tree.links.new({}, {}.inputs[1])",
+            normal.python_expr(),
+            node.name
+        )));
+
+        assert!(captured_geo.python_expr().starts_with(&node.name));
+        assert_eq!(
+            captured_normal.python_expr(),
+            format!("{}.outputs[1]", node.name)
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! # Shared Field Inputs
+//!
+//! Field input nodes such as Position or Normal carry no state of their own: they evaluate on
+//! whatever geometry consumes them. Creating a new node for every read therefore only clutters
+//! the tree, so the helpers here reuse a single node per scope.
+//!
+//! **[Scope Rule]**
+//!
+//! The cache is tied to the scope stack (`enter_zone`/`exit_zone`), i.e. one cache per tree
+//! being built. Reuse across a `repeat_zone` body is intentional and safe, because a field input
+//! is evaluated where it is consumed, not where it is created. A nested scope never sees nodes
+//! from its parent, since those nodes are emitted into a different tree script.
+//!
+//! Call the generated `::new()` constructors directly when a separate node is wanted.
+//!
+//! `collection_info` and `object_as_geometry` read a data-block by name instead, and
+//! `string_value` holds a constant; they create a node per call.
+
+use crate::core::context::{cache_input, cached_input};
+use crate::core::nodes::{
+    FunctionNodeInputString, GeometryNodeCollectionInfo, GeometryNodeCollectionInfoTransformSpace,
+    GeometryNodeInputId, GeometryNodeInputIndex, GeometryNodeInputNormal,
+    GeometryNodeInputPosition, GeometryNodeObjectInfo,
+};
+use crate::core::types::{Collection, Geo, Int, NodeSocket, Object, StringType, Vector};
+
+macro_rules! cached_input_node {
+    ($Node:ident, $bl_idname:expr) => {
+        match cached_input($bl_idname) {
+            Some(name) => $Node { name },
+            None => {
+                let node = $Node::new();
+                cache_input($bl_idname, &node.name);
+                node
+            }
+        }
+    };
+}
+
+/// `Position` field of the current scope's shared `GeometryNodeInputPosition`.
+pub fn position() -> NodeSocket<Vector> {
+    cached_input_node!(GeometryNodeInputPosition, "GeometryNodeInputPosition").out_position()
+}
+
+/// `Normal` field of the current scope's shared `GeometryNodeInputNormal`.
+pub fn normal() -> NodeSocket<Vector> {
+    cached_input_node!(GeometryNodeInputNormal, "GeometryNodeInputNormal").out_normal()
+}
+
+/// `Index` field of the current scope's shared `GeometryNodeInputIndex`.
+pub fn index() -> NodeSocket<Int> {
+    cached_input_node!(GeometryNodeInputIndex, "GeometryNodeInputIndex").out_index()
+}
+
+/// `ID` field of the current scope's shared `GeometryNodeInputID`.
+pub fn id() -> NodeSocket<Int> {
+    cached_input_node!(GeometryNodeInputId, "GeometryNodeInputID").out_id()
+}
+
+/// `transform_space` of Collection Info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Space {
+    /// Keep the collection's own transforms.
+    Original,
+    /// Place the collection relative to the object the modifier is on.
+    Relative,
+}
+
+/// A `GeometryNodeCollectionInfo` reading a collection by name; see `collection_info`.
+#[derive(Clone, Debug)]
+pub struct CollectionInfo {
+    pub node: GeometryNodeCollectionInfo,
+}
+
+impl CollectionInfo {
+    /// One instance per child collection or object instead of a single instance.
+    pub fn separate_children(self, separate: bool) -> Self {
+        let node = self.node.with_separate_children(separate);
+        Self { node }
+    }
+
+    /// Place the children at the origin, dropping their own transforms.
+    pub fn reset_children(self, reset: bool) -> Self {
+        let node = self.node.with_reset_children(reset);
+        Self { node }
+    }
+
+    pub fn instances(&self) -> NodeSocket<Geo> {
+        self.node.out_instances()
+    }
+}
+
+impl From<CollectionInfo> for NodeSocket<Geo> {
+    fn from(info: CollectionInfo) -> Self {
+        info.instances()
+    }
+}
+
+/// Collection Info for the collection `name` (`bpy.data.collections.get(name)`),
+/// e.g. `collection_info("Rocks", Space::Relative).separate_children(true).instances()`.
+pub fn collection_info(name: &str, space: Space) -> CollectionInfo {
+    let space = match space {
+        Space::Original => GeometryNodeCollectionInfoTransformSpace::Original,
+        Space::Relative => GeometryNodeCollectionInfoTransformSpace::Relative,
+    };
+    let node = GeometryNodeCollectionInfo::new()
+        .with_transform_space(space)
+        .with_collection(NodeSocket::<Collection>::from(name));
+    CollectionInfo { node }
+}
+
+/// Geometry of the object `name` from Object Info, as a single instance if `as_instance`.
+pub fn object_as_geometry(name: &str, as_instance: bool) -> NodeSocket<Geo> {
+    GeometryNodeObjectInfo::new()
+        .with_object(NodeSocket::<Object>::from(name))
+        .with_as_instance(as_instance)
+        .out_geometry()
+}
+
+/// `s` as a `FunctionNodeInputString` node, for a string that should be visible and linkable
+/// in the tree. `NodeSocket::<StringType>::from(s)` writes it into the consuming socket instead.
+pub fn string_value(s: &str) -> NodeSocket<StringType> {
+    FunctionNodeInputString::new().with_string(s).out_string()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+
+    #[test]
+    fn test_position_is_shared_within_scope() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let a = position();
+        let b = position();
+        let c = position();
+
+        let nodes = context::exit_zone();
+        let count = nodes
+            .iter()
+            .filter(|n| n.bl_idname == "GeometryNodeInputPosition")
+            .count();
+        assert_eq!(count, 1);
+        assert_eq!(a.python_expr(), b.python_expr());
+        assert_eq!(b.python_expr(), c.python_expr());
+    }
+
+    #[test]
+    fn test_distinct_inputs_are_cached_separately() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let _ = (position(), normal(), index(), id());
+        let _ = (position(), normal(), index(), id());
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 4);
+        for bl_idname in [
+            "GeometryNodeInputPosition",
+            "GeometryNodeInputNormal",
+            "GeometryNodeInputIndex",
+            "GeometryNodeInputID",
+        ] {
+            assert_eq!(nodes.iter().filter(|n| n.bl_idname == bl_idname).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_nested_scope_does_not_reuse_parent_node() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let outer = position();
+
+        context::enter_zone();
+        let inner = position();
+        let inner_nodes = context::exit_zone();
+
+        let outer_again = position();
+        let outer_nodes = context::exit_zone();
+
+        assert_eq!(inner_nodes.len(), 1);
+        assert_eq!(outer_nodes.len(), 1);
+        assert_ne!(outer.python_expr(), inner.python_expr());
+        assert_eq!(outer.python_expr(), outer_again.python_expr());
+    }
+
+    #[test]
+    fn test_explicit_new_still_duplicates() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let _ = position();
+        let _ = GeometryNodeInputPosition::new().out_position();
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+    }
+
+    #[test]
+    fn test_collection_info_properties_and_literal() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let rocks: NodeSocket<Geo> = collection_info("Rocks", Space::Relative)
+            .separate_children(true)
+            .reset_children(true)
+            .into();
+        let _ = collection_info("Rocks", Space::Original);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2, "collection info is not shared");
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeCollectionInfo");
+        assert_eq!(node.properties["transform_space"], "\"RELATIVE\"");
+        let pin = |idx: usize| node.inputs[&idx][0].expr.as_str();
+        assert_eq!(
+            pin(GeometryNodeCollectionInfo::PIN_COLLECTION),
+            "bpy.data.collections.get(\"Rocks\")"
+        );
+        assert_eq!(
+            pin(GeometryNodeCollectionInfo::PIN_SEPARATE_CHILDREN),
+            "True"
+        );
+        assert_eq!(pin(GeometryNodeCollectionInfo::PIN_RESET_CHILDREN), "True");
+        assert!(rocks.python_expr().starts_with(&node.name));
+        assert_eq!(nodes[1].properties["transform_space"], "\"ORIGINAL\"");
+    }
+
+    #[test]
+    fn test_object_as_geometry() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let tree = object_as_geometry("Tree", true);
+
+        let nodes = context::exit_zone();
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "GeometryNodeObjectInfo");
+        assert_eq!(
+            node.inputs[&GeometryNodeObjectInfo::PIN_OBJECT][0].expr,
+            "bpy.data.objects.get(\"Tree\")"
+        );
+        assert_eq!(
+            node.inputs[&GeometryNodeObjectInfo::PIN_AS_INSTANCE][0].expr,
+            "True"
+        );
+        assert_eq!(
+            tree.python_expr(),
+            format!("{}.outputs[\"Geometry\"]", node.name)
+        );
+    }
+
+    #[test]
+    fn test_string_value_is_input_string_node() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let name = string_value("rock \"a\"");
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "FunctionNodeInputString");
+        assert_eq!(nodes[0].properties["string"], "\"rock \\\"a\\\"\"");
+        assert!(!name.is_literal);
+        assert_eq!(
+            name.python_expr(),
+            format!("{}.outputs[\"String\"]", nodes[0].name)
+        );
+    }
+}
@@ -2,35 +2,92 @@ use std::io::{Read, Write};
 use std::net::{Shutdown, TcpStream};
 use std::time::Duration;
 
-const LIVE_LINK_ADDR: &str = "127.0.0.1:8080";
+pub(crate) const LIVE_LINK_ADDR: &str = "127.0.0.1:8080";
+
+/// Bytes written per `Progress` event.
+const SEND_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Progress of sending a project to Blender, reported by `BlenderProject::send_with_progress`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendEvent {
+    /// Ordering the project items by their dependencies.
+    Resolving,
+    /// Item names in the order their scripts are sent.
+    Resolved(Vec<String>),
+    /// Connected; about to transfer a script of this many bytes.
+    Sending(usize),
+    /// This many bytes of the script have been written so far.
+    Progress(usize),
+    /// Blender executed the script and replied with this message.
+    Response(String),
+    /// Dependency resolution, the connection or the script execution failed.
+    Failed(String),
+}
 
 /// Sends the generated Python script to the Blender Live-Link server.
 pub fn send_to_blender(script: &str) {
     println!("🍜 Blender Ramen: Sending script via Live-Link...");
+    let _ = transmit(LIVE_LINK_ADDR, script, &report_event);
+}
 
-    let target = LIVE_LINK_ADDR.parse().unwrap();
-    match TcpStream::connect_timeout(&target, Duration::from_secs(2)) {
-        Ok(mut stream) => {
-            if let Err(e) = stream.write_all(script.as_bytes()) {
-                eprintln!("❌ Failed to transfer the script: {}", e);
-                return;
-            }
-            let _ = stream.shutdown(Shutdown::Write);
-            stream.set_read_timeout(Some(Duration::from_secs(10))).ok();
-            let mut response = String::new();
-            if stream.read_to_string(&mut response).is_ok() {
-                if response.starts_with("ERROR") {
-                    eprintln!("❌ Python Execution Failed in Blender:\n{}", response);
-                } else {
-                    println!("✅ Live-Link successful! Transferred the node tree to Blender!");
-                }
-            } else {
-                eprintln!("⚠️ Script sent, but failed to read response from Blender.");
-            }
-        }
-        Err(e) => {
-            eprintln!("❌ Could not connect to Blender: {}", e);
-            eprintln!("💡 Hint: Is the Live-Link server (Python script) running in Blender?");
+/// Prints an event the way `send_to_blender` always has.
+pub(crate) fn report_event(event: SendEvent) {
+    match event {
+        SendEvent::Resolving
+        | SendEvent::Resolved(_)
+        | SendEvent::Sending(_)
+        | SendEvent::Progress(_) => {}
+        SendEvent::Response(_) => {
+            println!("✅ Live-Link successful! Transferred the node tree to Blender!");
         }
+        SendEvent::Failed(err) => eprintln!("❌ {}", err),
+    }
+}
+
+/// Sends `script` to the server at `addr`, reporting `Sending` and `Progress`, then `Response`
+/// or `Failed`. Returns Blender's response, or the message of the `Failed` event.
+pub(crate) fn transmit(
+    addr: &str,
+    script: &str,
+    on_event: &dyn Fn(SendEvent),
+) -> Result<String, String> {
+    let result = try_transmit(addr, script, on_event);
+    match &result {
+        Ok(response) => on_event(SendEvent::Response(response.clone())),
+        Err(err) => on_event(SendEvent::Failed(err.clone())),
+    }
+    result
+}
+
+fn try_transmit(addr: &str, script: &str, on_event: &dyn Fn(SendEvent)) -> Result<String, String> {
+    let target = addr
+        .parse()
+        .map_err(|e| format!("Invalid Live-Link address '{}': {}", addr, e))?;
+    let mut stream = TcpStream::connect_timeout(&target, Duration::from_secs(2)).map_err(|e| {
+        format!(
+            "Could not connect to Blender: {}\n💡 Hint: Is the Live-Link server (Python script) running in Blender?",
+            e
+        )
+    })?;
+
+    on_event(SendEvent::Sending(script.len()));
+    let mut sent = 0;
+    for chunk in script.as_bytes().chunks(SEND_CHUNK_SIZE) {
+        stream
+            .write_all(chunk)
+            .map_err(|e| format!("Failed to transfer the script: {}", e))?;
+        sent += chunk.len();
+        on_event(SendEvent::Progress(sent));
+    }
+    let _ = stream.shutdown(Shutdown::Write);
+    stream.set_read_timeout(Some(Duration::from_secs(10))).ok();
+
+    let mut response = String::new();
+    if stream.read_to_string(&mut response).is_err() {
+        return Err("Script sent, but failed to read response from Blender.".to_string());
+    }
+    if response.starts_with("ERROR") {
+        return Err(format!("Python Execution Failed in Blender:\n{}", response));
     }
+    Ok(response)
 }
@@ -0,0 +1,198 @@
+//! # Material Helpers
+//!
+//! Surface detail from a procedural height, and a Principled preset that takes it:
+//!
+//! ```ignore
+//! let height = textures::noise(Dimensions::D3 { vector: uv }, 5.0, 2.0, 0.5).fac;
+//! let surface = materials::pbr(color, 0.0, 0.4, Some(NormalInput::Bump {
+//!     height, strength: 0.5.into(), distance: 0.1.into(),
+//! }));
+//! ShaderNodeOutputMaterial::new()
+//!     .with_surface(surface)
+//!     .with_displacement(materials::displace(height, 0.5, 0.1));
+//! ```
+//!
+//! `bump` only shades the surface as if it were displaced. `displace` moves the geometry,
+//! which Blender does only when the material's `displacement_method` is `DISPLACEMENT`
+//! (the default, `BUMP`, falls back to bump mapping); its node sets that on the material
+//! using the tree. Inside shader groups there is no such material, so set it on the
+//! materials calling the group.
+
+use crate::core::nodes::{
+    ShaderNodeBsdfPrincipled, ShaderNodeBump, ShaderNodeDisplacement, ShaderNodeNormalMap,
+};
+use crate::core::types::{Color, Float, NodeSocket, Shader, Vector};
+
+/// Script setting true displacement on the material whose node tree is being built.
+const DISPLACEMENT_METHOD_SCRIPT: &str = r#"for material in bpy.data.materials:
+    if material.node_tree == tree:
+        material.displacement_method = 'DISPLACEMENT'"#;
+
+/// Where the shading normal of `pbr` comes from.
+#[derive(Clone, Copy)]
+pub enum NormalInput {
+    /// Bump mapping of a height; see `bump`.
+    Bump {
+        height: NodeSocket<Float>,
+        strength: NodeSocket<Float>,
+        distance: NodeSocket<Float>,
+    },
+    /// A tangent space normal map color.
+    NormalMap {
+        color: NodeSocket<Color>,
+        strength: NodeSocket<Float>,
+    },
+}
+
+impl NormalInput {
+    fn normal(self) -> NodeSocket<Vector> {
+        match self {
+            NormalInput::Bump {
+                height,
+                strength,
+                distance,
+            } => bump(height, strength, distance),
+            NormalInput::NormalMap { color, strength } => ShaderNodeNormalMap::new()
+                .with_color(color)
+                .with_strength(strength)
+                .out_normal(),
+        }
+    }
+}
+
+/// Displacement vector of `height` for the Displacement pin of `ShaderNodeOutputMaterial`.
+/// Also switches the material to true displacement (see the module docs).
+pub fn displace(
+    height: impl Into<NodeSocket<Float>>,
+    midlevel: impl Into<NodeSocket<Float>>,
+    scale: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Vector> {
+    let node = ShaderNodeDisplacement::new()
+        .with_height(height.into())
+        .with_midlevel(midlevel.into())
+        .with_scale(scale.into());
+    crate::core::context::update_post_creation(&node.name, DISPLACEMENT_METHOD_SCRIPT);
+    node.out_displacement()
+}
+
+/// Normal of the surface bumped by `height`, for the Normal pins of shaders.
+pub fn bump(
+    height: impl Into<NodeSocket<Float>>,
+    strength: impl Into<NodeSocket<Float>>,
+    distance: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Vector> {
+    ShaderNodeBump::new()
+        .with_height(height.into())
+        .with_strength(strength.into())
+        .with_distance(distance.into())
+        .out_normal()
+}
+
+/// Principled BSDF with the usual PBR inputs, shading with `normal` if given.
+pub fn pbr(
+    base_color: impl Into<NodeSocket<Color>>,
+    metallic: impl Into<NodeSocket<Float>>,
+    roughness: impl Into<NodeSocket<Float>>,
+    normal: Option<NormalInput>,
+) -> NodeSocket<Shader> {
+    let normal = normal.map(NormalInput::normal);
+    let mut node = ShaderNodeBsdfPrincipled::new()
+        .with_base_color(base_color.into())
+        .with_metallic(metallic.into())
+        .with_roughness(roughness.into());
+    if let Some(normal) = normal {
+        node = node.with_normal(normal);
+    }
+    node.out_bsdf()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::{self, test_utils::GLOBAL_TEST_LOCK};
+    use crate::core::nodes::ShaderNodeOutputMaterial;
+
+    fn height() -> NodeSocket<Float> {
+        NodeSocket::new_output("noise.outputs[0]")
+    }
+
+    #[test]
+    fn test_displace_feeds_output_displacement() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let displacement = displace(height(), 0.5, 0.1);
+        ShaderNodeOutputMaterial::new().with_displacement(displacement);
+        let nodes = context::exit_zone();
+
+        let (disp, output) = (&nodes[0], &nodes[1]);
+        assert_eq!(disp.bl_idname, "ShaderNodeDisplacement");
+        assert_eq!(
+            disp.inputs[&ShaderNodeDisplacement::PIN_HEIGHT][0].expr,
+            "noise.outputs[0]"
+        );
+        assert_eq!(
+            output.inputs[&ShaderNodeOutputMaterial::PIN_DISPLACEMENT][0].expr,
+            format!("{}.outputs[\"Displacement\"]", disp.name)
+        );
+        assert!(
+            disp.post_creation_script
+                .contains("material.displacement_method = 'DISPLACEMENT'")
+        );
+    }
+
+    #[test]
+    fn test_pbr_normal_inputs() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let _ = pbr(
+            (0.8, 0.8, 0.8, 1.0),
+            0.0,
+            0.4,
+            Some(NormalInput::Bump {
+                height: height(),
+                strength: 0.5.into(),
+                distance: 0.1.into(),
+            }),
+        );
+        let nodes = context::exit_zone();
+        let (bump, bsdf) = (&nodes[0], &nodes[1]);
+        assert_eq!(bump.bl_idname, "ShaderNodeBump");
+        assert!(bump.post_creation_script.is_empty());
+        assert_eq!(
+            bsdf.inputs[&ShaderNodeBsdfPrincipled::PIN_NORMAL][0].expr,
+            format!("{}.outputs[\"Normal\"]", bump.name)
+        );
+
+        context::enter_zone();
+        let _ = pbr(
+            (0.8, 0.8, 0.8, 1.0),
+            0.0,
+            0.4,
+            Some(NormalInput::NormalMap {
+                color: NodeSocket::new_output("tex.outputs[0]"),
+                strength: 1.0.into(),
+            }),
+        );
+        let nodes = context::exit_zone();
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeNormalMap");
+        assert_eq!(
+            nodes[0].inputs[&ShaderNodeNormalMap::PIN_COLOR][0].expr,
+            "tex.outputs[0]"
+        );
+
+        context::enter_zone();
+        let _ = pbr((0.8, 0.8, 0.8, 1.0), 0.0, 0.4, None);
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert!(
+            !nodes[0]
+                .inputs
+                .contains_key(&ShaderNodeBsdfPrincipled::PIN_NORMAL)
+        );
+    }
+}
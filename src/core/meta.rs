@@ -0,0 +1,208 @@
+//! # Node Index
+//!
+//! The nodes generated into `core::nodes`, as data: `build.rs` writes an index of every
+//! node's sockets and properties next to the generated code, and `node_index` reads it back.
+//! Handy for finding which struct wraps a Blender node and what its pins are called:
+//!
+//! ```text
+//! cargo run --bin ramen-nodes -- search lens
+//! cargo run --bin ramen-nodes -- show CompositorNodeLensdist
+//! ```
+//!
+//! Build checks use it to name sockets in their messages (`input 3 ('Scale', NodeSocketFloat)`).
+//! Nodes missing from the index are checked less and described by index only.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+const NODES_INDEX_JSON: &str = include_str!(concat!(env!("OUT_DIR"), "/nodes_index.json"));
+
+static NODE_INDEX: LazyLock<Vec<NodeEntry>> = LazyLock::new(|| {
+    serde_json::from_str(NODES_INDEX_JSON).expect("nodes_index.json written by build.rs is valid")
+});
+
+static BY_BL_IDNAME: LazyLock<HashMap<&'static str, &'static NodeEntry>> = LazyLock::new(|| {
+    node_index()
+        .iter()
+        .map(|entry| (entry.bl_idname.as_str(), entry))
+        .collect()
+});
+
+/// Socket type of the placeholder that item-based nodes (zones, captures, group outputs) grow
+/// their sockets from.
+const VIRTUAL_SOCKET: &str = "NodeSocketVirtual";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SocketEntry {
+    pub name: String,
+    pub identifier: String,
+    /// Blender socket type, e.g. `NodeSocketFloat`.
+    pub socket_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PropertyEntry {
+    pub identifier: String,
+    pub name: String,
+    /// Blender property type, e.g. `ENUM` or `FLOAT`.
+    pub property_type: String,
+    /// Identifiers of the choices of an `ENUM` property; empty otherwise.
+    pub enum_items: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NodeEntry {
+    /// Name of the generated struct in `core::nodes`.
+    pub struct_name: String,
+    pub bl_idname: String,
+    pub bl_label: String,
+    /// Tree types the node is available in, e.g. `GeometryNodes`.
+    pub categories: Vec<String>,
+    /// In pin order: `inputs[i]` is the socket at `PIN_*` index `i`.
+    pub inputs: Vec<SocketEntry>,
+    pub outputs: Vec<SocketEntry>,
+    pub properties: Vec<PropertyEntry>,
+}
+
+impl NodeEntry {
+    pub fn input(&self, name: &str) -> Option<&SocketEntry> {
+        self.inputs.iter().find(|socket| socket.name == name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<&SocketEntry> {
+        self.outputs.iter().find(|socket| socket.name == name)
+    }
+
+    /// Number of inputs if they are fixed; `None` for nodes whose inputs come from items or a
+    /// group interface.
+    pub fn fixed_input_count(&self) -> Option<usize> {
+        let is_dynamic = self.inputs.is_empty()
+            || self
+                .inputs
+                .iter()
+                .any(|socket| socket.socket_type == VIRTUAL_SOCKET);
+        (!is_dynamic).then_some(self.inputs.len())
+    }
+}
+
+/// Every generated node, sorted by struct name.
+pub fn node_index() -> &'static [NodeEntry] {
+    &NODE_INDEX
+}
+
+/// The node whose struct name or `bl_idname` is `name`.
+pub fn find(name: &str) -> Option<&'static NodeEntry> {
+    node_index()
+        .iter()
+        .find(|entry| entry.struct_name == name || entry.bl_idname == name)
+}
+
+/// The node with this `bl_idname`.
+pub fn by_bl_idname(bl_idname: &str) -> Option<&'static NodeEntry> {
+    BY_BL_IDNAME.get(bl_idname).copied()
+}
+
+/// `input 3 ('Scale', NodeSocketFloat)` for input `index` of a `bl_idname` node, or just
+/// `input 3` if the index doesn't know the socket.
+pub fn describe_input(bl_idname: &str, index: usize) -> String {
+    match by_bl_idname(bl_idname).and_then(|entry| entry.inputs.get(index)) {
+        Some(socket) if socket.socket_type != VIRTUAL_SOCKET => format!(
+            "input {} ('{}', {})",
+            index, socket.name, socket.socket_type
+        ),
+        _ => format!("input {}", index),
+    }
+}
+
+/// Nodes whose struct name, `bl_idname` or label contains `query`, ignoring case.
+pub fn search(query: &str) -> Vec<&'static NodeEntry> {
+    let query = query.to_lowercase();
+    node_index()
+        .iter()
+        .filter(|entry| {
+            [&entry.struct_name, &entry.bl_idname, &entry.bl_label]
+                .iter()
+                .any(|field| field.to_lowercase().contains(&query))
+        })
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::nodes::{GeometryNodeMeshCube, ShaderNodeTexNoise};
+
+    #[test]
+    fn test_find_known_node_inputs() {
+        let cube = find("GeometryNodeMeshCube").unwrap();
+        assert_eq!(cube.categories, ["GeometryNodes"]);
+        let names: Vec<&str> = cube.inputs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["Size", "Vertices X", "Vertices Y", "Vertices Z"]);
+        assert_eq!(
+            cube.inputs[GeometryNodeMeshCube::PIN_SIZE].socket_type,
+            "NodeSocketVectorTranslation"
+        );
+        assert!(cube.output("Mesh").is_some());
+
+        let noise = find("ShaderNodeTexNoise").unwrap();
+        let dimensions = noise
+            .properties
+            .iter()
+            .find(|prop| prop.identifier == "noise_dimensions")
+            .unwrap();
+        assert_eq!(dimensions.enum_items, ["1D", "2D", "3D", "4D"]);
+        assert_eq!(
+            noise.input("Scale").map(|s| s.identifier.as_str()),
+            Some("Scale")
+        );
+        assert_eq!(noise.inputs[ShaderNodeTexNoise::PIN_SCALE].name, "Scale");
+        assert_eq!(
+            noise.categories,
+            ["GeometryNodes", "ShaderNodes", "CompositorNodes"]
+        );
+    }
+
+    #[test]
+    fn test_describe_input() {
+        assert_eq!(
+            describe_input("ShaderNodeTexNoise", ShaderNodeTexNoise::PIN_SCALE),
+            format!(
+                "input {} ('Scale', NodeSocketFloat)",
+                ShaderNodeTexNoise::PIN_SCALE
+            )
+        );
+        // Unknown nodes, indices past the end and item placeholders degrade to the index.
+        assert_eq!(describe_input("NoSuchNode", 3), "input 3");
+        assert_eq!(describe_input("GeometryNodeMeshCube", 40), "input 40");
+        assert_eq!(describe_input("NodeGroupOutput", 0), "input 0");
+
+        assert_eq!(
+            by_bl_idname("GeometryNodeMeshCube")
+                .unwrap()
+                .fixed_input_count(),
+            Some(4)
+        );
+        for dynamic in ["GeometryNodeGroup", "GeometryNodeRepeatOutput"] {
+            assert_eq!(by_bl_idname(dynamic).unwrap().fixed_input_count(), None);
+        }
+    }
+
+    #[test]
+    fn test_search_is_case_insensitive() {
+        let hits = search("LENS");
+        assert!(
+            hits.iter()
+                .any(|entry| entry.struct_name == "CompositorNodeLensdist")
+        );
+        assert!(search("no such node").is_empty());
+        assert!(
+            node_index()
+                .windows(2)
+                .all(|w| w[0].struct_name < w[1].struct_name)
+        );
+    }
+}
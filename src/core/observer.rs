@@ -0,0 +1,61 @@
+//! # Project Observers
+//!
+//! Hooks for tools that embed the crate and want to follow a project from building to
+//! Blender's reply, installed with `BlenderProject::with_observer`. Every hook has an empty
+//! default, so an observer only implements the events it cares about.
+
+use crate::core::live_link::{SendEvent, report_event};
+
+/// Size of a built project item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStats {
+    pub node_count: usize,
+    pub script_bytes: usize,
+}
+
+impl ItemStats {
+    pub(crate) fn of_script(script: &str) -> Self {
+        Self {
+            node_count: script.matches(" = tree.nodes.new(").count(),
+            script_bytes: script.len(),
+        }
+    }
+}
+
+/// `Send + Sync` so that projects holding one can still move between threads (e.g. a watcher).
+pub trait ProjectObserver: Send + Sync {
+    /// An item was added to the project (including finished scripts from `add_subtree`).
+    fn on_item_built(&self, _name: &str, _stats: ItemStats) {}
+    /// Item names in the order their scripts are sent.
+    fn on_resolve(&self, _order: &[String]) {}
+    /// Connected to Blender; about to transfer a script of `total_bytes`.
+    fn on_send_start(&self, _total_bytes: usize) {}
+    /// `sent` bytes of the script have been written so far.
+    fn on_send_progress(&self, _sent: usize) {}
+    /// Blender's reply, or why the send failed (dependency resolution included).
+    fn on_response(&self, _result: &Result<String, String>) {}
+}
+
+/// What a project reports without an observer: the result line `send_to_blender` prints.
+pub struct ConsoleObserver;
+
+impl ProjectObserver for ConsoleObserver {
+    fn on_response(&self, result: &Result<String, String>) {
+        match result {
+            Ok(response) => report_event(SendEvent::Response(response.clone())),
+            Err(err) => report_event(SendEvent::Failed(err.clone())),
+        }
+    }
+}
+
+/// Forwards a send event to the matching hook of `observer`.
+pub(crate) fn notify(observer: &dyn ProjectObserver, event: SendEvent) {
+    match event {
+        SendEvent::Resolving => {}
+        SendEvent::Resolved(order) => observer.on_resolve(&order),
+        SendEvent::Sending(total_bytes) => observer.on_send_start(total_bytes),
+        SendEvent::Progress(sent) => observer.on_send_progress(sent),
+        SendEvent::Response(response) => observer.on_response(&Ok(response)),
+        SendEvent::Failed(err) => observer.on_response(&Err(err)),
+    }
+}
@@ -1,6 +1,6 @@
 //! # Math Operations Module
 //!
-//! This module defines the standard arithmetic operations (`+`, `-`, `*`, `/`) for `NodeSocket`s.
+//! This module defines the standard arithmetic operations (`+`, `-`, `*`, `/`, `%`) for `NodeSocket`s.
 //!
 //! **[Important Design Consideration]**
 //!
@@ -10,9 +10,12 @@
 //! To eliminate this vulnerability, our core operational logic adopts a robust design that explicitly targets pins by their physical, immutable indices using `.set_input(0, ...)`.
 
 use crate::core::nodes::{
-    ShaderNodeMath, ShaderNodeMathOperation, ShaderNodeVectorMath, ShaderNodeVectorMathOperation,
+    FunctionNodeCompare, FunctionNodeCompareDataType, FunctionNodeCompareOperation,
+    ShaderNodeClamp, ShaderNodeCombineXyz, ShaderNodeMath, ShaderNodeMathOperation,
+    ShaderNodeSeparateXyz, ShaderNodeVectorMath, ShaderNodeVectorMathOperation,
 };
-use crate::core::types::{Float, NodeSocket, Vector};
+use crate::core::rotation::Axis;
+use crate::core::types::{Bool, Color, Float, Int, NodeSocket, Vector};
 
 macro_rules! impl_node_op {
     ($Trait:ident, $method:ident, $Node:ident, $op_enum:expr, $out:ident, $Type:ident) => {
@@ -62,6 +65,15 @@ impl_node_op!(
     out_value,
     Float
 );
+// MODULO truncates like Rust's `%` (the result takes the sign of the dividend).
+impl_node_op!(
+    Rem,
+    rem,
+    ShaderNodeMath,
+    ShaderNodeMathOperation::Modulo,
+    out_value,
+    Float
+);
 
 // Vector (ShaderNodeVectorMath)
 impl_node_op!(
@@ -154,6 +166,24 @@ impl_scalar_op!(Add, add);
 impl_scalar_op!(Sub, sub);
 impl_scalar_op!(Mul, mul);
 impl_scalar_op!(Div, div);
+impl_scalar_op!(Rem, rem);
+
+// iter::Sum / iter::Product ------------------------------------------------------
+impl std::iter::Sum for NodeSocket<Float> {
+    /// Folds via `Add`; an empty iterator yields the literal `0.0`.
+    fn sum<I: Iterator<Item = NodeSocket<Float>>>(iter: I) -> Self {
+        iter.reduce(|acc, x| acc + x)
+            .unwrap_or_else(|| NodeSocket::<Float>::from(0.0))
+    }
+}
+
+impl std::iter::Product for NodeSocket<Float> {
+    /// Folds via `Mul`; an empty iterator yields the literal `1.0`.
+    fn product<I: Iterator<Item = NodeSocket<Float>>>(iter: I) -> Self {
+        iter.reduce(|acc, x| acc * x)
+            .unwrap_or_else(|| NodeSocket::<Float>::from(1.0))
+    }
+}
 
 // op(Vector, f32) -----------------------------------------------------------------
 macro_rules! impl_vector_scalar_op {
@@ -263,6 +293,402 @@ impl_vector2d_scalar_op!(Sub, sub);
 impl_vector2d_scalar_op!(Mul, mul);
 impl_vector2d_scalar_op!(Div, div);
 
+// op(Vector2D, (f32, f32)) -------------------------------------------------------
+macro_rules! impl_vector2d_tuple_op {
+    ($Trait:ident, $method:ident) => {
+        // Vector2D op (f32, f32)
+        impl std::ops::$Trait<(f32, f32)> for NodeSocket<Vector2D> {
+            type Output = NodeSocket<Vector2D>;
+            fn $method(self, rhs: (f32, f32)) -> Self::Output {
+                self.$method(NodeSocket::<Vector2D>::from(rhs))
+            }
+        }
+        // (f32, f32) op Vector2D
+        impl std::ops::$Trait<NodeSocket<Vector2D>> for (f32, f32) {
+            type Output = NodeSocket<Vector2D>;
+            fn $method(self, rhs: NodeSocket<Vector2D>) -> Self::Output {
+                NodeSocket::<Vector2D>::from(self).$method(rhs)
+            }
+        }
+    };
+}
+
+impl_vector2d_tuple_op!(Add, add);
+impl_vector2d_tuple_op!(Sub, sub);
+impl_vector2d_tuple_op!(Mul, mul);
+impl_vector2d_tuple_op!(Div, div);
+
+// Vector2D helpers ---------------------------------------------------------------
+impl NodeSocket<Vector2D> {
+    /// Dot product (`ShaderNodeVectorMath` DOT_PRODUCT).
+    pub fn dot(self, other: impl Into<NodeSocket<Vector2D>>) -> NodeSocket<Float> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::DotProduct)
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR, self)
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR_0, other.into())
+            .out_value()
+    }
+
+    /// Euclidean length (`ShaderNodeVectorMath` LENGTH).
+    pub fn length(self) -> NodeSocket<Float> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Length)
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR, self)
+            .out_value()
+    }
+
+    /// Unit-length copy (`ShaderNodeVectorMath` NORMALIZE).
+    pub fn normalize(self) -> NodeSocket<Vector2D> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Normalize)
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR, self)
+            .out_vector()
+            .cast::<Vector2D>() // downcast
+    }
+
+    /// Rotates counter-clockwise by `angle` (radians) around the origin.
+    ///
+    /// Built as `(x cos - y sin, x sin + y cos)` from Sine/Cosine math nodes and a `ShaderNodeCombineXyz`
+    /// whose Z is left at 0 and dropped by the downcast.
+    pub fn rotate_2d(self, angle: impl Into<NodeSocket<Float>>) -> NodeSocket<Vector2D> {
+        let angle = angle.into();
+        let xyz = ShaderNodeSeparateXyz::new().set_input(ShaderNodeSeparateXyz::PIN_VECTOR, self);
+        let (x, y) = (xyz.out_x(), xyz.out_y());
+        let sin = ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::Sine)
+            .set_input(0, angle)
+            .out_value();
+        let cos = ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::Cosine)
+            .set_input(0, angle)
+            .out_value();
+
+        let rx = x * cos - y * sin;
+        let ry = x * sin + y * cos;
+        ShaderNodeCombineXyz::new()
+            .with_x(rx)
+            .with_y(ry)
+            .out_vector()
+            .cast::<Vector2D>() // downcast
+    }
+}
+
+// smooth min / max ---------------------------------------------------------------
+impl NodeSocket<Float> {
+    /// Smooth minimum with smoothing distance `k` (`ShaderNodeMath` SMOOTH_MIN), e.g. for SDF unions.
+    pub fn smooth_min(
+        self,
+        other: impl Into<NodeSocket<Float>>,
+        k: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Float> {
+        ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::SmoothMin)
+            .set_input(0, self)
+            .set_input(1, other.into())
+            .set_input(2, k.into())
+            .out_value()
+    }
+
+    /// Smooth maximum with smoothing distance `k` (`ShaderNodeMath` SMOOTH_MAX), e.g. for SDF intersections.
+    pub fn smooth_max(
+        self,
+        other: impl Into<NodeSocket<Float>>,
+        k: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Float> {
+        ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::SmoothMax)
+            .set_input(0, self)
+            .set_input(1, other.into())
+            .set_input(2, k.into())
+            .out_value()
+    }
+
+    /// 1.0 if `self` and `other` differ by at most `epsilon`, else 0.0 (`ShaderNodeMath` COMPARE).
+    pub fn approx_eq(
+        self,
+        other: impl Into<NodeSocket<Float>>,
+        epsilon: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Float> {
+        ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::Compare)
+            .set_input(0, self)
+            .set_input(1, other.into())
+            .set_input(2, epsilon.into())
+            .out_value()
+    }
+
+    /// `self` limited to `min..=max` in a single node (`ShaderNodeClamp`, MINMAX).
+    pub fn clamp(
+        self,
+        min: impl Into<NodeSocket<Float>>,
+        max: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Float> {
+        ShaderNodeClamp::new()
+            .with_value(self)
+            .with_min(min.into())
+            .with_max(max.into())
+            .out_result()
+    }
+
+    /// `self * mul + add` in a single node (`ShaderNodeMath` MULTIPLY_ADD), like `f32::mul_add`.
+    pub fn mul_add(
+        self,
+        mul: impl Into<NodeSocket<Float>>,
+        add: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Float> {
+        ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::MultiplyAdd)
+            .set_input(0, self)
+            .set_input(1, mul.into())
+            .set_input(2, add.into())
+            .out_value()
+    }
+}
+
+// guarded division ----------------------------------------------------------------
+/// Smallest magnitude `safe_div` divides by. Literals are emitted with 4 decimals, so this is
+/// the smallest epsilon that survives into the script (`0.000001` is emitted as `0.0000`).
+pub const SAFE_DIV_EPSILON: f32 = 1e-4;
+
+impl NodeSocket<Float> {
+    /// `self / denom`, with `denom` pushed away from zero to at least `SAFE_DIV_EPSILON` in
+    /// magnitude, keeping its sign (zero counts as positive). For SDF and fractal math where
+    /// a near-zero denominator would blow up, instead of adding an epsilon by hand.
+    pub fn safe_div(self, denom: impl Into<NodeSocket<Float>>) -> NodeSocket<Float> {
+        let denom = denom.into();
+        let abs = ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::Absolute)
+            .set_input(0, denom)
+            .out_value();
+        let magnitude =
+            math_binary(ShaderNodeMathOperation::Maximum, abs, SAFE_DIV_EPSILON).out_value();
+        // 1 - 2 * (denom < 0): SIGN would be 0 at zero and cancel the guard.
+        let sign = math_binary(ShaderNodeMathOperation::LessThan, denom, 0.0)
+            .out_value()
+            .mul_add(-2.0, 1.0);
+        math_div(self, magnitude * sign).out_value()
+    }
+}
+
+// Int scaling ---------------------------------------------------------------------
+// `Int * f32` is deliberately not an operator: the result type would be unclear.
+impl NodeSocket<Int> {
+    /// `self * factor` as a float (`ShaderNodeMath` MULTIPLY; the int converts implicitly).
+    pub fn scaled(self, factor: f32) -> NodeSocket<Float> {
+        ShaderNodeMath::new()
+            .with_operation(ShaderNodeMathOperation::Multiply)
+            .set_input(0, self)
+            .set_input(1, NodeSocket::<Float>::from(factor))
+            .out_value()
+    }
+
+    /// `self != 0` (`FunctionNodeCompare` INT NOT_EQUAL).
+    pub fn is_nonzero(self) -> NodeSocket<Bool> {
+        FunctionNodeCompare::new()
+            .with_data_type(FunctionNodeCompareDataType::Int)
+            .with_operation(FunctionNodeCompareOperation::NotEqual)
+            .set_input(FunctionNodeCompare::PIN_A_0, self)
+            .set_input(FunctionNodeCompare::PIN_B_0, NodeSocket::<Int>::from(0))
+            .out_result()
+    }
+}
+
+// grayscale broadcasts -----------------------------------------------------------
+impl NodeSocket<Float> {
+    /// Gray color `(v, v, v, 1.0)`: `as_vector`, linked into color inputs through Blender's
+    /// implicit vector -> color conversion, so it works in every tree type.
+    pub fn as_color(self) -> NodeSocket<Color> {
+        self.as_vector().cast()
+    }
+
+    /// Vector `(v, v, v)` (`ShaderNodeCombineXyz`).
+    pub fn as_vector(self) -> NodeSocket<Vector> {
+        ShaderNodeCombineXyz::new()
+            .with_x(self)
+            .with_y(self)
+            .with_z(self)
+            .out_vector()
+    }
+}
+
+// componentwise Vector ops -------------------------------------------------------
+impl NodeSocket<Vector> {
+    /// Componentwise absolute value (`ShaderNodeVectorMath` ABSOLUTE).
+    pub fn abs(self) -> NodeSocket<Vector> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Absolute)
+            .set_input(0, self)
+            .out_vector()
+    }
+
+    /// Componentwise minimum (`ShaderNodeVectorMath` MINIMUM).
+    pub fn min(self, other: impl Into<NodeSocket<Vector>>) -> NodeSocket<Vector> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Minimum)
+            .set_input(0, self)
+            .set_input(1, other.into())
+            .out_vector()
+    }
+
+    /// Componentwise maximum (`ShaderNodeVectorMath` MAXIMUM).
+    pub fn max(self, other: impl Into<NodeSocket<Vector>>) -> NodeSocket<Vector> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Maximum)
+            .set_input(0, self)
+            .set_input(1, other.into())
+            .out_vector()
+    }
+
+    /// Projection of this vector onto `onto` (`ShaderNodeVectorMath` PROJECT).
+    pub fn project(self, onto: impl Into<NodeSocket<Vector>>) -> NodeSocket<Vector> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Project)
+            .set_input(0, self)
+            .set_input(1, onto.into())
+            .out_vector()
+    }
+
+    /// This vector or its negation, `dot(incident, reference) < 0 ? self : -self`
+    /// (`ShaderNodeVectorMath` FACEFORWARD).
+    ///
+    /// The operation reads all three vector pins: `self` on 0, `incident` on 1 and `reference` on 2.
+    pub fn faceforward(
+        self,
+        incident: impl Into<NodeSocket<Vector>>,
+        reference: impl Into<NodeSocket<Vector>>,
+    ) -> NodeSocket<Vector> {
+        ShaderNodeVectorMath::new()
+            .with_operation(ShaderNodeVectorMathOperation::Faceforward)
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR, self)
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR_0, incident.into())
+            .set_input(ShaderNodeVectorMath::PIN_VECTOR_1, reference.into())
+            .out_vector()
+    }
+
+    /// Cartesian vector from spherical coordinates: radius `r`, polar angle `theta` (radians,
+    /// from +Z) and azimuth `phi` (radians, from +X), i.e.
+    /// `(r sin(theta) cos(phi), r sin(theta) sin(phi), r cos(theta))`.
+    ///
+    /// Built from Sine/Cosine of each angle, four Multiply math nodes and a `ShaderNodeCombineXyz`.
+    pub fn from_spherical(
+        r: impl Into<NodeSocket<Float>>,
+        theta: impl Into<NodeSocket<Float>>,
+        phi: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Vector> {
+        let trig = |operation, angle: NodeSocket<Float>| {
+            ShaderNodeMath::new()
+                .with_operation(operation)
+                .set_input(0, angle)
+                .out_value()
+        };
+        let (r, theta, phi) = (r.into(), theta.into(), phi.into());
+        let sin_theta = trig(ShaderNodeMathOperation::Sine, theta);
+        let cos_theta = trig(ShaderNodeMathOperation::Cosine, theta);
+        let sin_phi = trig(ShaderNodeMathOperation::Sine, phi);
+        let cos_phi = trig(ShaderNodeMathOperation::Cosine, phi);
+
+        let planar = r * sin_theta;
+        let (x, y, z) = (planar * cos_phi, planar * sin_phi, r * cos_theta);
+        ShaderNodeCombineXyz::new()
+            .with_x(x)
+            .with_y(y)
+            .with_z(z)
+            .out_vector()
+    }
+
+    /// Copy of this vector with the `axis` component replaced by `value` (`ShaderNodeCombineXyz`).
+    ///
+    /// The other components come from a `ShaderNodeSeparateXyz` of `self`, or are written as
+    /// literals when `self` is one, e.g. `Vector::zero().with_component(Axis::Z, h)`.
+    pub fn with_component(
+        self,
+        axis: Axis,
+        value: impl Into<NodeSocket<Float>>,
+    ) -> NodeSocket<Vector> {
+        let [x, y, z] = match literal_components(&self) {
+            Some(components) => components,
+            None => {
+                let sep = ShaderNodeSeparateXyz::new().with_vector(self);
+                [sep.out_x(), sep.out_y(), sep.out_z()]
+            }
+        };
+        let value = value.into();
+        let (x, y, z) = match axis {
+            Axis::X => (value, y, z),
+            Axis::Y => (x, value, z),
+            Axis::Z => (x, y, value),
+        };
+        ShaderNodeCombineXyz::new()
+            .with_x(x)
+            .with_y(y)
+            .with_z(z)
+            .out_vector()
+    }
+}
+
+/// Splits a `(x, y, z)` vector literal into its component literals.
+fn literal_components(v: &NodeSocket<Vector>) -> Option<[NodeSocket<Float>; 3]> {
+    if !v.is_literal {
+        return None;
+    }
+    let expr = v.python_expr();
+    let inner = expr.strip_prefix('(')?.strip_suffix(')')?;
+    let mut parts = inner.split(", ").map(NodeSocket::<Float>::new_literal);
+    let components = [parts.next()?, parts.next()?, parts.next()?];
+    parts.next().is_none().then_some(components)
+}
+
+// node-returning Float math -------------------------------------------------------
+// Operators return only the output socket. These keep the `ShaderNodeMath` handle so its
+// properties can still be set, e.g. `math_add(a, b).with_clamp(true).out_value()`.
+
+/// Binary `ShaderNodeMath` with `a` and `b` on pins 0 and 1.
+pub fn math_binary(
+    operation: ShaderNodeMathOperation,
+    a: impl Into<NodeSocket<Float>>,
+    b: impl Into<NodeSocket<Float>>,
+) -> ShaderNodeMath {
+    ShaderNodeMath::new()
+        .with_operation(operation)
+        .set_input(0, a.into())
+        .set_input(1, b.into())
+}
+
+pub fn math_add(
+    a: impl Into<NodeSocket<Float>>,
+    b: impl Into<NodeSocket<Float>>,
+) -> ShaderNodeMath {
+    math_binary(ShaderNodeMathOperation::Add, a, b)
+}
+
+pub fn math_sub(
+    a: impl Into<NodeSocket<Float>>,
+    b: impl Into<NodeSocket<Float>>,
+) -> ShaderNodeMath {
+    math_binary(ShaderNodeMathOperation::Subtract, a, b)
+}
+
+pub fn math_mul(
+    a: impl Into<NodeSocket<Float>>,
+    b: impl Into<NodeSocket<Float>>,
+) -> ShaderNodeMath {
+    math_binary(ShaderNodeMathOperation::Multiply, a, b)
+}
+
+pub fn math_div(
+    a: impl Into<NodeSocket<Float>>,
+    b: impl Into<NodeSocket<Float>>,
+) -> ShaderNodeMath {
+    math_binary(ShaderNodeMathOperation::Divide, a, b)
+}
+
+impl ShaderNodeMath {
+    /// Clamps the result to [0, 1] (`use_clamp`).
+    pub fn with_clamp(self, clamp: bool) -> Self {
+        self.with_use_clamp(clamp)
+    }
+}
+
 // ----------------------------------------------------------------------------
 // unittest
 // ----------------------------------------------------------------------------
@@ -319,6 +745,92 @@ mod tests {
         assert_eq!(nodes[3].properties.get("operation").unwrap(), "\"DIVIDE\"");
     }
 
+    #[test]
+    fn test_with_operation_enum_and_str_forms() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        ShaderNodeMath::new().with_operation(ShaderNodeMathOperation::Sine);
+        ShaderNodeMath::new().with_operation_str("SINE");
+        let nodes = context::exit_zone();
+
+        assert_eq!(
+            nodes[0].properties.get("operation"),
+            nodes[1].properties.get("operation")
+        );
+        assert_eq!(nodes[1].properties.get("operation").unwrap(), "\"SINE\"");
+
+        assert_eq!(
+            "FLOORED_MODULO".parse::<ShaderNodeMathOperation>(),
+            Ok(ShaderNodeMathOperation::FlooredModulo)
+        );
+        let err = "Sine".parse::<ShaderNodeMathOperation>().unwrap_err();
+        assert!(err.contains("unknown ShaderNodeMathOperation 'Sine'"));
+        assert!(err.contains("SINE"));
+    }
+
+    #[test]
+    fn test_float_rem() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let a = NodeSocket::<Float>::from(7.0);
+        let b = NodeSocket::<Float>::from(2.0);
+
+        let _ = a % b;
+        let _ = a % 3.0;
+        let _ = 9.0 % b;
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        for node in &nodes {
+            assert_eq!(node.bl_idname, "ShaderNodeMath");
+            assert_eq!(node.properties.get("operation").unwrap(), "\"MODULO\"");
+        }
+        assert_eq!(nodes[1].inputs.get(&1).unwrap()[0].expr, "3.0000");
+        assert_eq!(nodes[2].inputs.get(&0).unwrap()[0].expr, "9.0000");
+    }
+
+    #[test]
+    fn test_operator_results_on_literals_are_links() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let a = NodeSocket::<Float>::from(0.5);
+        let b = NodeSocket::<Float>::from(2.0);
+        assert!(a.is_literal && b.is_literal);
+
+        let sum = a + b;
+        let results = [
+            sum,
+            a - b,
+            a * b,
+            a / b,
+            a % b,
+            a + 1.0,
+            3.0 * b,
+            [a, b].into_iter().sum(),
+        ];
+        for r in &results {
+            assert!(!r.is_literal, "{} should be a node output", r.python_expr());
+        }
+
+        let v = NodeSocket::<Vector>::from((1.0, 2.0, 3.0));
+        assert!(!(v + v).is_literal);
+        assert!(!(v * a).is_literal);
+        let uv = NodeSocket::<Vector2D>::from((1.0, 2.0));
+        assert!(!(uv - uv).is_literal);
+        assert!(!uv.length().is_literal);
+
+        // The result links into the next node instead of being written as a default value.
+        let _ = sum * 4.0;
+        let nodes = context::exit_zone();
+        let last = nodes.last().unwrap();
+        let input = &last.inputs.get(&0).unwrap()[0];
+        assert!(!input.is_literal);
+        assert_eq!(input.expr, sum.python_expr());
+    }
+
     #[test]
     fn test_scalar_operations_and_order() {
         let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
@@ -348,6 +860,50 @@ mod tests {
         assert!(nodes[1].inputs.get(&1).unwrap()[0].is_literal);
     }
 
+    #[test]
+    fn test_float_sum_and_product() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let sockets = vec![
+            NodeSocket::<Float>::from(1.0),
+            NodeSocket::<Float>::from(2.0),
+            NodeSocket::<Float>::from(3.0),
+        ];
+
+        let total: NodeSocket<Float> = sockets.clone().into_iter().sum();
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        for node in &nodes {
+            assert_eq!(node.properties.get("operation").unwrap(), "\"ADD\"");
+        }
+        assert!(!total.is_literal);
+
+        context::enter_zone();
+        let _: NodeSocket<Float> = sockets.into_iter().product();
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        for node in &nodes {
+            assert_eq!(node.properties.get("operation").unwrap(), "\"MULTIPLY\"");
+        }
+    }
+
+    #[test]
+    fn test_float_sum_and_product_empty() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let sum: NodeSocket<Float> = std::iter::empty().sum();
+        let product: NodeSocket<Float> = std::iter::empty().product();
+        let nodes = context::exit_zone();
+
+        assert!(nodes.is_empty());
+        assert!(sum.is_literal);
+        assert_eq!(sum.python_expr(), "0.0000");
+        assert!(product.is_literal);
+        assert_eq!(product.python_expr(), "1.0000");
+    }
+
     #[test]
     fn test_vector_math_operations() {
         let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
@@ -380,6 +936,79 @@ mod tests {
         assert_eq!(nodes[3].properties.get("operation").unwrap(), "\"DIVIDE\"");
     }
 
+    #[test]
+    fn test_vector_project_and_faceforward() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Vector>::from((1.0, 2.0, 3.0));
+        let onto = NodeSocket::<Vector>::new_output("axis.outputs[0]");
+        let projected = v.project(onto);
+        let facing = v.faceforward((0.0, 0.0, -1.0), (0.0, 0.0, 1.0));
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].properties["operation"], "\"PROJECT\"");
+        assert_eq!(nodes[0].inputs[&0][0].expr, "(1.0000, 2.0000, 3.0000)");
+        assert_eq!(nodes[0].inputs[&1][0].expr, "axis.outputs[0]");
+        assert_eq!(
+            projected.python_expr(),
+            format!("{}.outputs[\"Vector\"]", nodes[0].name)
+        );
+
+        assert_eq!(nodes[1].properties["operation"], "\"FACEFORWARD\"");
+        assert_eq!(nodes[1].inputs[&0][0].expr, "(1.0000, 2.0000, 3.0000)");
+        assert_eq!(nodes[1].inputs[&1][0].expr, "(0.0000, 0.0000, -1.0000)");
+        assert_eq!(nodes[1].inputs[&2][0].expr, "(0.0000, 0.0000, 1.0000)");
+        assert_eq!(
+            facing.python_expr(),
+            format!("{}.outputs[\"Vector\"]", nodes[1].name)
+        );
+    }
+
+    #[test]
+    fn test_vector_from_spherical() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let r = NodeSocket::<Float>::new_output("radius.outputs[0]");
+        let v = NodeSocket::<Vector>::from_spherical(r, 0.5, 1.5);
+        let nodes = context::exit_zone();
+
+        let ops: Vec<&str> = nodes
+            .iter()
+            .filter_map(|n| n.properties.get("operation").map(String::as_str))
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                "\"SINE\"",
+                "\"COSINE\"",
+                "\"SINE\"",
+                "\"COSINE\"",
+                "\"MULTIPLY\"",
+                "\"MULTIPLY\"",
+                "\"MULTIPLY\"",
+                "\"MULTIPLY\"",
+            ]
+        );
+        assert_eq!(nodes[0].inputs[&0][0].expr, "0.5000");
+        assert_eq!(nodes[2].inputs[&0][0].expr, "1.5000");
+
+        // r * sin(theta), shared by x and y; z is r * cos(theta).
+        let (planar, z) = (&nodes[4], &nodes[7]);
+        assert_eq!(planar.inputs[&0][0].expr, "radius.outputs[0]");
+        assert!(planar.inputs[&1][0].expr.starts_with(&nodes[0].name));
+        assert!(z.inputs[&1][0].expr.starts_with(&nodes[1].name));
+
+        let combine = nodes.last().unwrap();
+        assert_eq!(combine.bl_idname, "ShaderNodeCombineXyz");
+        assert!(combine.inputs[&0][0].expr.starts_with(&nodes[5].name));
+        assert!(combine.inputs[&1][0].expr.starts_with(&nodes[6].name));
+        assert!(combine.inputs[&2][0].expr.starts_with(&z.name));
+        assert!(v.python_expr().starts_with(&combine.name));
+    }
+
     #[test]
     fn test_vector_scalar_operations() {
         let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
@@ -521,4 +1150,477 @@ mod tests {
             "(10.0000, 10.0000)"
         );
     }
+
+    #[test]
+    fn test_vector2d_tuple_operations() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Vector2D>::from((1.0, 2.0));
+
+        let _ = v + (0.5, -1.0);
+        let _ = (3.0, 4.0) - v;
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+
+        assert_eq!(nodes[0].properties.get("operation").unwrap(), "\"ADD\"");
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, v.python_expr());
+        assert_eq!(
+            nodes[0].inputs.get(&1).unwrap()[0].expr,
+            "(0.5000, -1.0000)"
+        );
+
+        assert_eq!(
+            nodes[1].properties.get("operation").unwrap(),
+            "\"SUBTRACT\""
+        );
+        assert_eq!(nodes[1].inputs.get(&0).unwrap()[0].expr, "(3.0000, 4.0000)");
+        assert_eq!(nodes[1].inputs.get(&1).unwrap()[0].expr, v.python_expr());
+    }
+
+    #[test]
+    fn test_vector2d_dot_length_normalize() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Vector2D>::new_output("uv.outputs[0]");
+
+        let d = v.dot((0.0, 1.0));
+        let l = v.length();
+        let _ = v.normalize();
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(
+            nodes[0].properties.get("operation").unwrap(),
+            "\"DOT_PRODUCT\""
+        );
+        assert_eq!(nodes[0].inputs.get(&1).unwrap()[0].expr, "(0.0000, 1.0000)");
+        assert_eq!(
+            d.python_expr(),
+            format!("{}.outputs[\"Value\"]", nodes[0].name)
+        );
+        assert_eq!(nodes[1].properties.get("operation").unwrap(), "\"LENGTH\"");
+        assert_eq!(
+            l.python_expr(),
+            format!("{}.outputs[\"Value\"]", nodes[1].name)
+        );
+        assert_eq!(
+            nodes[2].properties.get("operation").unwrap(),
+            "\"NORMALIZE\""
+        );
+    }
+
+    #[test]
+    fn test_vector2d_rotate_2d() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Vector2D>::new_output("uv.outputs[0]");
+        let angle = NodeSocket::<Float>::new_output("angle.outputs[0]");
+
+        let _ = v.rotate_2d(angle);
+
+        let nodes = context::exit_zone();
+        let kinds: Vec<_> = nodes
+            .iter()
+            .map(|n| {
+                let op = n.properties.get("operation").map_or("", |s| s.as_str());
+                (n.bl_idname.as_str(), op)
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("ShaderNodeSeparateXyz", ""),
+                ("ShaderNodeMath", "\"SINE\""),
+                ("ShaderNodeMath", "\"COSINE\""),
+                ("ShaderNodeMath", "\"MULTIPLY\""),
+                ("ShaderNodeMath", "\"MULTIPLY\""),
+                ("ShaderNodeMath", "\"SUBTRACT\""),
+                ("ShaderNodeMath", "\"MULTIPLY\""),
+                ("ShaderNodeMath", "\"MULTIPLY\""),
+                ("ShaderNodeMath", "\"ADD\""),
+                ("ShaderNodeCombineXyz", ""),
+            ]
+        );
+
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, v.python_expr());
+        assert_eq!(
+            nodes[1].inputs.get(&0).unwrap()[0].expr,
+            angle.python_expr()
+        );
+        let combine = &nodes[9];
+        assert_eq!(
+            combine.inputs.get(&0).unwrap()[0].expr,
+            format!("{}.outputs[\"Value\"]", nodes[5].name)
+        );
+        assert_eq!(
+            combine.inputs.get(&1).unwrap()[0].expr,
+            format!("{}.outputs[\"Value\"]", nodes[8].name)
+        );
+        assert!(!combine.inputs.contains_key(&2));
+    }
+
+    #[test]
+    fn test_vector_componentwise_ops() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Vector>::new_output("pos.outputs[0]");
+
+        // Box fold: clamp(v, -1, 1) * 2 - v
+        let clamped = v.max((-1.0, -1.0, -1.0)).min((1.0, 1.0, 1.0));
+        let _ = clamped.abs();
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        for node in &nodes {
+            assert_eq!(node.bl_idname, "ShaderNodeVectorMath");
+        }
+        assert_eq!(nodes[0].properties.get("operation").unwrap(), "\"MAXIMUM\"");
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, v.python_expr());
+        assert_eq!(
+            nodes[0].inputs.get(&1).unwrap()[0].expr,
+            "(-1.0000, -1.0000, -1.0000)"
+        );
+        assert_eq!(nodes[1].properties.get("operation").unwrap(), "\"MINIMUM\"");
+        assert_eq!(
+            nodes[1].inputs.get(&1).unwrap()[0].expr,
+            "(1.0000, 1.0000, 1.0000)"
+        );
+        assert_eq!(
+            nodes[2].properties.get("operation").unwrap(),
+            "\"ABSOLUTE\""
+        );
+        assert_eq!(
+            nodes[2].inputs.get(&0).unwrap()[0].expr,
+            clamped.python_expr()
+        );
+        assert!(!nodes[2].inputs.contains_key(&1));
+    }
+
+    #[test]
+    fn test_float_smooth_min_max() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let a = NodeSocket::<Float>::new_output("sdf_a.outputs[0]");
+        let b = NodeSocket::<Float>::new_output("sdf_b.outputs[0]");
+
+        let _ = a.smooth_min(b, 0.2);
+        let _ = a.smooth_max(0.5, b);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[0].properties.get("operation").unwrap(),
+            "\"SMOOTH_MIN\""
+        );
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, a.python_expr());
+        assert_eq!(nodes[0].inputs.get(&1).unwrap()[0].expr, b.python_expr());
+        assert_eq!(nodes[0].inputs.get(&2).unwrap()[0].expr, "0.2000");
+
+        assert_eq!(
+            nodes[1].properties.get("operation").unwrap(),
+            "\"SMOOTH_MAX\""
+        );
+        assert_eq!(nodes[1].inputs.len(), 3);
+        assert_eq!(nodes[1].inputs.get(&1).unwrap()[0].expr, "0.5000");
+        assert_eq!(nodes[1].inputs.get(&2).unwrap()[0].expr, b.python_expr());
+    }
+
+    #[test]
+    fn test_math_add_keeps_node_handle() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let a = NodeSocket::<Float>::new_output("a.outputs[0]");
+        let b = NodeSocket::<Float>::new_output("b.outputs[0]");
+
+        let sum = math_add(a, b).with_clamp(true).out_value();
+        let _ = math_div(sum, 2.0);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].properties.get("operation").unwrap(), "\"ADD\"");
+        assert_eq!(nodes[0].properties.get("use_clamp").unwrap(), "True");
+        assert_eq!(nodes[0].inputs.get(&1).unwrap()[0].expr, b.python_expr());
+
+        assert_eq!(nodes[1].properties.get("operation").unwrap(), "\"DIVIDE\"");
+        assert!(!nodes[1].properties.contains_key("use_clamp"));
+        assert_eq!(nodes[1].inputs.get(&0).unwrap()[0].expr, sum.python_expr());
+    }
+
+    #[test]
+    fn test_vector_with_component() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Float>::new_output("height.outputs[0]");
+        let offset = Vector::zero().with_component(Axis::Z, v);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeCombineXyz");
+        assert!(offset.python_expr().starts_with(&nodes[0].name));
+        let input = |i: usize| nodes[0].inputs.get(&i).unwrap()[0].clone();
+        assert_eq!(input(0).expr, "0.0000");
+        assert!(input(0).is_literal);
+        assert_eq!(input(1).expr, "0.0000");
+        assert_eq!(input(2).expr, v.python_expr());
+        assert!(!input(2).is_literal);
+
+        context::enter_zone();
+        let base = NodeSocket::<Vector>::new_output("pos.outputs[0]");
+        let _ = base.with_component(Axis::X, 1.0);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeSeparateXyz");
+        assert_eq!(nodes[1].inputs.get(&0).unwrap()[0].expr, "1.0000");
+        assert!(
+            nodes[1].inputs.get(&2).unwrap()[0]
+                .expr
+                .starts_with(&nodes[0].name)
+        );
+    }
+
+    #[test]
+    fn test_float_grayscale_broadcasts() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let v = NodeSocket::<Float>::new_output("noise.outputs[0]");
+        let color = v.as_color();
+        let vector = v.as_vector();
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        // No geometry-only node: both go through CombineXYZ.
+        for (node, socket) in nodes
+            .iter()
+            .zip([color.python_expr(), vector.python_expr()])
+        {
+            assert_eq!(node.bl_idname, "ShaderNodeCombineXyz");
+            assert!(socket.starts_with(&node.name));
+            assert!((0..3).all(|i| node.inputs.get(&i).unwrap()[0].expr == v.python_expr()));
+        }
+    }
+
+    #[test]
+    fn test_ramen_math_reuses_method_call_socket() {
+        use ramen_macros::ramen_math;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let sep = ShaderNodeSeparateXyz::new().with_vector(Vector::zero());
+        let _ = ramen_math!(sep.out_x() * sep.out_x() + sqrt(sep.out_x()));
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 4);
+        let x = sep.out_x().python_expr();
+        let mul = &nodes[1];
+        assert_eq!(mul.inputs[&0][0].expr, x);
+        assert_eq!(mul.inputs[&1][0].expr, x);
+    }
+
+    #[test]
+    fn test_ramen_math_ease_in_out_nodes() {
+        use ramen_macros::ramen_math;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let t = NodeSocket::<Float>::new_output("t.outputs[0]");
+        let eased = ramen_math!(ease_in_out(t * 0.5));
+
+        let nodes = context::exit_zone();
+        // t * 0.5 once, then t^2 * (3 - 2t); the outer node is created before its inputs.
+        assert_eq!(nodes.len(), 4);
+        let half = nodes[0].name.clone();
+        let (product, square, slope) = (&nodes[1], &nodes[2], &nodes[3]);
+        assert_eq!(square.properties["operation"], "\"MULTIPLY\"");
+        assert!(square.inputs[&0][0].expr.starts_with(&half));
+        assert!(square.inputs[&1][0].expr.starts_with(&half));
+        assert_eq!(slope.properties["operation"], "\"MULTIPLY_ADD\"");
+        assert_eq!(slope.inputs[&1][0].expr, "-2.0000");
+        assert_eq!(slope.inputs[&2][0].expr, "3.0000");
+        assert!(product.inputs[&0][0].expr.starts_with(&square.name));
+        assert!(product.inputs[&1][0].expr.starts_with(&slope.name));
+        assert!(eased.python_expr().starts_with(&product.name));
+    }
+
+    #[test]
+    fn test_ramen_math_in_range_nodes() {
+        use ramen_macros::ramen_math;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let x = NodeSocket::<Float>::new_output("x.outputs[0]");
+        let inside = ramen_math!(in_range(x, 0.0, 1.0));
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        let (and, low, high) = (&nodes[0], &nodes[1], &nodes[2]);
+        assert_eq!(and.bl_idname, "FunctionNodeBooleanMath");
+        assert_eq!(and.properties["operation"], "\"AND\"");
+        assert_eq!(low.properties["operation"], "\"LESS_EQUAL\"");
+        assert_eq!(low.inputs[&0][0].expr, "0.0000");
+        assert_eq!(low.inputs[&1][0].expr, "x.outputs[0]");
+        assert_eq!(high.inputs[&0][0].expr, "x.outputs[0]");
+        assert_eq!(high.inputs[&1][0].expr, "1.0000");
+        assert!(inside.python_expr().starts_with(&and.name));
+    }
+
+    #[test]
+    fn test_float_approx_eq() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let a = NodeSocket::<Float>::new_output("a.outputs[0]");
+        let b = NodeSocket::<Float>::new_output("b.outputs[0]");
+        let result = a.approx_eq(b, 0.01);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeMath");
+        assert_eq!(nodes[0].properties["operation"], "\"COMPARE\"");
+        assert_eq!(nodes[0].inputs[&0][0].expr, "a.outputs[0]");
+        assert_eq!(nodes[0].inputs[&1][0].expr, "b.outputs[0]");
+        assert_eq!(nodes[0].inputs[&2][0].expr, "0.0100");
+        assert!(nodes[0].inputs[&2][0].is_literal);
+        assert!(result.python_expr().starts_with(&nodes[0].name));
+    }
+
+    #[test]
+    fn test_float_clamp_is_one_node() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let x = NodeSocket::<Float>::new_output("x.outputs[0]");
+        let upper = NodeSocket::<Float>::new_output("upper.outputs[0]");
+        let _ = x.clamp(0.0, 1.0);
+        let _ = x.clamp(-1.0, upper);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes.iter().all(|n| n.bl_idname == "ShaderNodeClamp"));
+        assert_eq!(
+            nodes[0].inputs[&ShaderNodeClamp::PIN_VALUE][0].expr,
+            "x.outputs[0]"
+        );
+        assert_eq!(nodes[0].inputs[&ShaderNodeClamp::PIN_MIN][0].expr, "0.0000");
+        assert_eq!(nodes[0].inputs[&ShaderNodeClamp::PIN_MAX][0].expr, "1.0000");
+        assert_eq!(
+            nodes[1].inputs[&ShaderNodeClamp::PIN_MIN][0].expr,
+            "-1.0000"
+        );
+        assert_eq!(
+            nodes[1].inputs[&ShaderNodeClamp::PIN_MAX][0].expr,
+            "upper.outputs[0]"
+        );
+    }
+
+    #[test]
+    fn test_int_scaled_is_float_multiply() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let index = NodeSocket::<Int>::new_output("index.outputs[0]");
+        let scaled: NodeSocket<Float> = index.scaled(0.005);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeMath");
+        assert_eq!(nodes[0].properties["operation"], "\"MULTIPLY\"");
+        assert_eq!(nodes[0].inputs[&0][0].expr, "index.outputs[0]");
+        assert_eq!(nodes[0].inputs[&1][0].expr, "0.0050");
+        assert!(scaled.python_expr().starts_with(&nodes[0].name));
+    }
+
+    #[test]
+    fn test_int_is_nonzero_compares_ints() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let count = NodeSocket::<Int>::new_output("count.outputs[0]");
+        let nonzero: NodeSocket<Bool> = count.is_nonzero();
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "FunctionNodeCompare");
+        assert_eq!(nodes[0].properties["data_type"], "\"INT\"");
+        assert_eq!(nodes[0].properties["operation"], "\"NOT_EQUAL\"");
+        assert_eq!(
+            nodes[0].inputs[&FunctionNodeCompare::PIN_A_0][0].expr,
+            "count.outputs[0]"
+        );
+        assert_eq!(nodes[0].inputs[&FunctionNodeCompare::PIN_B_0][0].expr, "0");
+        assert!(!nodes[0].inputs.contains_key(&FunctionNodeCompare::PIN_A));
+        assert_eq!(
+            nonzero.python_expr(),
+            format!("{}.outputs[\"Result\"]", nodes[0].name)
+        );
+    }
+
+    #[test]
+    fn test_float_mul_add() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let a = NodeSocket::<Float>::new_output("a.outputs[0]");
+        let b = NodeSocket::<Float>::new_output("b.outputs[0]");
+        let result = a.mul_add(b, 0.5);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "ShaderNodeMath");
+        assert_eq!(nodes[0].properties["operation"], "\"MULTIPLY_ADD\"");
+        assert_eq!(nodes[0].inputs[&0][0].expr, "a.outputs[0]");
+        assert_eq!(nodes[0].inputs[&1][0].expr, "b.outputs[0]");
+        assert_eq!(nodes[0].inputs[&2][0].expr, "0.5000");
+        assert!(result.python_expr().starts_with(&nodes[0].name));
+    }
+
+    #[test]
+    fn test_float_safe_div_guards_denominator() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let z = NodeSocket::<Float>::new_output("z.outputs[0]");
+        let r = NodeSocket::<Float>::new_output("r.outputs[0]");
+        let result = z.safe_div(r);
+
+        let nodes = context::exit_zone();
+        let ops: Vec<&str> = nodes
+            .iter()
+            .map(|n| n.properties["operation"].as_str())
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                "\"ABSOLUTE\"",
+                "\"MAXIMUM\"",
+                "\"LESS_THAN\"",
+                "\"MULTIPLY_ADD\"",
+                "\"MULTIPLY\"",
+                "\"DIVIDE\""
+            ]
+        );
+        let output = |i: usize| format!("{}.outputs[\"Value\"]", nodes[i].name);
+        let input = |i: usize, pin: usize| nodes[i].inputs[&pin][0].expr.clone();
+        assert_eq!(input(0, 0), "r.outputs[0]");
+        assert_eq!(input(1, 0), output(0));
+        assert_eq!(input(1, 1), "0.0001");
+        assert_eq!(input(2, 0), "r.outputs[0]");
+        assert_eq!(input(2, 1), "0.0000");
+        assert_eq!(input(3, 1), "-2.0000");
+        assert_eq!(input(3, 2), "1.0000");
+        assert_eq!(input(4, 0), output(1));
+        assert_eq!(input(4, 1), output(3));
+        assert_eq!(input(5, 0), "z.outputs[0]");
+        assert_eq!(input(5, 1), output(4));
+        assert_eq!(result.python_expr(), output(5));
+    }
 }
@@ -0,0 +1,218 @@
+//! # Project Parameters
+//!
+//! A `Param` is a named constant that the tree using it exposes for tweaking in Blender,
+//! instead of baking it into the script as a literal:
+//!
+//! ```ignore
+//! let power = project.param("Power", 8.0_f32);
+//! project.add_geometry_tree("Bulb", move || {
+//!     let r = ramen_math!(radius ** power);
+//!     // ...
+//! })
+//! ```
+//!
+//! Where the tree reads it decides what it becomes:
+//! - **Geometry trees and groups**: an interface input with the param's default, read through
+//!   the scope's shared `NodeGroupInput`. On geometry trees the default is also set on the
+//!   modifier.
+//! - **Shader trees**: a custom property of the material, read by a Value node whose output
+//!   is driven by it. Only float params can be driven this way.
+//! - **World and compositor trees** (and other types in shader trees): the default as a
+//!   literal, with a build warning.
+//!
+//! A tree declares each param it reads once, however often it is used.
+
+use crate::core::context::{
+    ParamTarget, ParamUse, cache_input, cached_input, update_post_creation, use_param, warn,
+};
+use crate::core::diagnostics::DiagnosticKind;
+use crate::core::nodes::{NodeGroupInput, ShaderNodeValue};
+use crate::core::project::BlenderProject;
+use crate::core::types::{Float, NodeGroupInputExt, NodeSocket, SocketDef, python_string_literal};
+
+/// A named constant exposed by the trees that use it; see the module docs.
+pub struct Param<T> {
+    name: &'static str,
+    default: NodeSocket<T>,
+}
+
+impl<T> Copy for Param<T> {}
+
+impl<T> Clone for Param<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: SocketDef> Param<T> {
+    pub fn new(name: &'static str, default: impl Into<NodeSocket<T>>) -> Self {
+        let default = default.into();
+        assert!(
+            default.is_literal,
+            "param '{}' expects a literal default, not a linked socket expression",
+            name
+        );
+        Self { name, default }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn default_value(&self) -> NodeSocket<T> {
+        self.default
+    }
+
+    /// Socket reading the param in the tree being built.
+    pub fn socket(&self) -> NodeSocket<T> {
+        let target = use_param(ParamUse {
+            name: self.name.to_string(),
+            blender_type: T::blender_socket_type().to_string(),
+            default_expr: self.default.python_expr(),
+        });
+        match target {
+            ParamTarget::GroupInput => {
+                let group_input = match cached_input("NodeGroupInput") {
+                    Some(name) => NodeGroupInput { name },
+                    None => {
+                        let node = NodeGroupInput::new();
+                        cache_input("NodeGroupInput", &node.name);
+                        node
+                    }
+                };
+                group_input.socket(self.name)
+            }
+            ParamTarget::MaterialDriver if T::blender_socket_type() == "NodeSocketFloat" => {
+                self.driven_value().cast()
+            }
+            ParamTarget::MaterialDriver | ParamTarget::Literal => {
+                warn(
+                    DiagnosticKind::InterfaceMismatch,
+                    format!(
+                        "param '{}' can't be exposed by this tree, its default is used as is",
+                        self.name
+                    ),
+                );
+                self.default
+            }
+        }
+    }
+
+    /// Value node of the current scope whose output is driven by the material property.
+    fn driven_value(&self) -> NodeSocket<Float> {
+        let key = format!("param:{}", self.name);
+        if let Some(name) = cached_input(&key) {
+            return ShaderNodeValue { name }.out_value();
+        }
+
+        let node = ShaderNodeValue::new().default_value(self.default.cast::<Float>());
+        cache_input(&key, &node.name);
+        let prop = python_string_literal(self.name);
+        update_post_creation(
+            &node.name,
+            format!(
+                r#"if {prop} not in mat:
+    mat[{prop}] = {default}
+fcurve = {node}.outputs[0].driver_add("default_value")
+fcurve.driver.type = 'AVERAGE'
+var = fcurve.driver.variables.new()
+var.type = 'SINGLE_PROP'
+var.targets[0].id_type = 'MATERIAL'
+var.targets[0].id = mat
+var.targets[0].data_path = {path}"#,
+                prop = prop,
+                default = self.default.python_expr(),
+                node = node.name,
+                path = python_string_literal(&format!("[{}]", prop)),
+            ),
+        );
+        node.out_value()
+    }
+}
+
+impl<T: SocketDef> From<Param<T>> for NodeSocket<T> {
+    fn from(param: Param<T>) -> Self {
+        param.socket()
+    }
+}
+
+impl BlenderProject {
+    /// A param named `name` for the trees of this project; see `core::param`.
+    pub fn param<T: SocketDef>(
+        &self,
+        name: &'static str,
+        default: impl Into<NodeSocket<T>>,
+    ) -> Param<T> {
+        Param::new(name, default)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::nodes::{
+        NodeGroupOutput, ShaderNodeEmission, ShaderNodeMath, ShaderNodeOutputMaterial,
+    };
+    use crate::core::tree::NodeTree;
+
+    #[test]
+    fn test_param_used_twice_declares_one_input() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let power = BlenderProject::new().param::<Float>("Power", 8.0);
+
+        let script = NodeTree::new_geometry("Bulb").build(|| {
+            let squared = ShaderNodeMath::new()
+                .with_value(power)
+                .with_value_1(power)
+                .out_value();
+            NodeGroupOutput::new().set_input(1, squared);
+        });
+
+        assert_eq!(
+            script
+                .matches("tree.interface.new_socket(\"Power\"")
+                .count(),
+            1
+        );
+        assert_eq!(
+            script
+                .matches(" = tree.nodes.new('NodeGroupInput')")
+                .count(),
+            1
+        );
+        assert_eq!(script.matches(".outputs[\"Power\"]").count(), 2);
+        assert!(script.contains("sock.default_value = 8.0000\n"));
+        assert!(script.contains("mod[sock.identifier] = 8.0000\n"));
+    }
+
+    #[test]
+    fn test_param_drives_material_value() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let strength = Param::<Float>::new("Glow", 2.5);
+
+        let script = NodeTree::new_shader("Lamp").build(|| {
+            let doubled = ShaderNodeMath::new()
+                .with_value(strength)
+                .with_value_1(strength)
+                .out_value();
+            let emission = ShaderNodeEmission::new()
+                .with_strength(doubled)
+                .out_emission();
+            ShaderNodeOutputMaterial::new().with_surface(emission);
+        });
+
+        assert!(!script.contains("new_socket"));
+        assert_eq!(
+            script
+                .matches(" = tree.nodes.new('ShaderNodeValue')")
+                .count(),
+            1
+        );
+        assert!(script.contains("    mat[\"Glow\"] = 2.5000\n"));
+        assert!(script.contains("var.targets[0].data_path = \"[\\\"Glow\\\"]\"\n"));
+    }
+}
@@ -1,6 +1,12 @@
-use crate::core::live_link::send_to_blender;
-use crate::core::tree::{NodeTree, generate_script_header};
-use std::collections::{HashMap, HashSet};
+use crate::core::context::{GroupCall, record_group_calls};
+use crate::core::debug::strip_debug_lines;
+use crate::core::diagnostics::{DiagnosticKind, TreeDiagnostics, near_matches};
+use crate::core::live_link::{LIVE_LINK_ADDR, SendEvent, transmit};
+use crate::core::observer::{ConsoleObserver, ItemStats, ProjectObserver, notify};
+use crate::core::snapshot::{ProjectDiff, SendSnapshot, SnapshotItem, normalize_script};
+use crate::core::tree::{NodeTree, generate_script_header_with, panic_message};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::panic::{AssertUnwindSafe, catch_unwind};
 
 #[derive(Clone)]
 pub struct ProjectItem {
@@ -10,8 +16,17 @@ pub struct ProjectItem {
 }
 
 pub struct BlenderProject {
-    header: String,
+    /// Modules the item scripts import, moved out of them into the header (see `push_item`).
+    imports: BTreeSet<String>,
     items: Vec<ProjectItem>,
+    /// Diagnostics of every tree built through this project, in insertion order, with the
+    /// group calls of the tree (checked against the final items by `try_build_all`).
+    diagnostics: Vec<(TreeDiagnostics, Vec<GroupCall>)>,
+    /// Strip `core::debug` statements from the assembled script.
+    release: bool,
+    /// Turn panicking tree builders into diagnostics instead of unwinding.
+    catch_build_panics: bool,
+    observer: Box<dyn ProjectObserver>,
 }
 
 impl Default for BlenderProject {
@@ -23,52 +38,102 @@ impl Default for BlenderProject {
 impl BlenderProject {
     pub fn new() -> Self {
         Self {
-            header: generate_script_header(),
+            imports: BTreeSet::new(),
             items: Vec::new(),
+            diagnostics: Vec::new(),
+            release: false,
+            catch_build_panics: false,
+            observer: Box::new(ConsoleObserver),
         }
     }
 
-    pub fn add_shader_tree<F>(mut self, tree_name: &str, builder: F) -> Self
+    /// Reports building and sending to `observer` instead of the console.
+    /// Install it before adding items to get their `on_item_built` events.
+    pub fn with_observer(mut self, observer: impl ProjectObserver + 'static) -> Self {
+        self.observer = Box::new(observer);
+        self
+    }
+
+    /// Leaves the `core::debug` checks and prints out of the assembled script.
+    pub fn release_scripts(mut self) -> Self {
+        self.release = true;
+        self
+    }
+
+    /// Reports a panicking tree builder as an error diagnostic of its tree (see
+    /// `try_build_all`) and leaves the tree out, instead of unwinding out of `add_*_tree`.
+    /// Such a project is not sent (see `try_send`).
+    pub fn catch_build_panics(mut self) -> Self {
+        self.catch_build_panics = true;
+        self
+    }
+
+    pub fn add_shader_tree<F>(self, tree_name: &str, builder: F) -> Self
     where
         F: FnOnce(),
     {
-        let script = NodeTree::new_shader(tree_name).build(builder);
-        self.items.push(ProjectItem {
-            name: tree_name.to_string(),
-            script,
-            dependencies: vec![],
-        });
-        self
+        self.add_tree(tree_name, NodeTree::new_shader(tree_name), builder)
     }
 
-    pub fn add_geometry_tree<F>(mut self, tree_name: &str, builder: F) -> Self
+    pub fn add_geometry_tree<F>(self, tree_name: &str, builder: F) -> Self
     where
         F: FnOnce(),
     {
-        let script = NodeTree::new_geometry(tree_name).build(builder);
-        self.items.push(ProjectItem {
-            name: tree_name.to_string(),
-            script,
-            dependencies: vec![],
-        });
-        self
+        self.add_tree(tree_name, NodeTree::new_geometry(tree_name), builder)
     }
 
-    pub fn add_compositor_tree<F>(mut self, tree_name: &str, builder: F) -> Self
+    /// Like `add_geometry_tree`, but attaches the modifier to the object named `object_name`
+    /// instead of the active object.
+    pub fn add_geometry_tree_at_object<F>(
+        self,
+        tree_name: &str,
+        object_name: &str,
+        builder: F,
+    ) -> Self
     where
         F: FnOnce(),
     {
-        let script = NodeTree::new_compositor(tree_name).build(builder);
-        self.items.push(ProjectItem {
-            name: tree_name.to_string(),
-            script,
-            dependencies: vec![],
-        });
+        self.add_tree(
+            tree_name,
+            NodeTree::new_geometry(tree_name).at_object(object_name),
+            builder,
+        )
+    }
+
+    /// Adds one geometry tree per object, named `{tree_name_prefix}_{object_name}`.
+    /// `builder` receives the object name of the tree it builds.
+    pub fn add_geometry_trees_at_objects<F>(
+        mut self,
+        tree_name_prefix: &str,
+        object_names: &[&str],
+        builder: F,
+    ) -> Self
+    where
+        F: Fn(&str) + Clone,
+    {
+        for &object_name in object_names {
+            let tree_name = format!("{}_{}", tree_name_prefix, object_name);
+            let build = builder.clone();
+            self = self
+                .add_geometry_tree_at_object(&tree_name, object_name, move || build(object_name));
+        }
         self
     }
 
+    pub fn add_compositor_tree<F>(self, tree_name: &str, builder: F) -> Self
+    where
+        F: FnOnce(),
+    {
+        self.add_tree(tree_name, NodeTree::new_compositor(tree_name), builder)
+    }
+
+    /// Adds a finished script as the item `name`, e.g. a group built with `NodeTree::build`.
+    /// Items whose scripts refer to `name` are assembled after it, whatever the order they
+    /// were added in. Adding the same script under the same name again is a no-op, so shared
+    /// groups can be added by every tree that calls them; another script under that name is
+    /// dropped with a warning (see `diagnostics`).
     pub fn add_subtree(mut self, name: &str, script: &str) -> Self {
-        self.items.push(ProjectItem {
+        self.push_item(ProjectItem {
             name: name.to_string(),
             script: script.to_string(),
             dependencies: vec![],
@@ -76,27 +141,284 @@ impl BlenderProject {
         self
     }
 
-    pub fn send(&self) {
-        let mut final_script = self.header.clone();
+    /// Items in insertion order (not dependency order).
+    pub fn items(&self) -> &[ProjectItem] {
+        &self.items
+    }
+
+    pub fn get_item(&self, name: &str) -> Option<&ProjectItem> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get_item(name).is_some()
+    }
+
+    /// Normalized item scripts, to compare against the next build with `diff`.
+    pub fn snapshot(&self) -> SendSnapshot {
+        SendSnapshot {
+            items: self
+                .items
+                .iter()
+                .map(|item| SnapshotItem::new(&item.name, &item.script))
+                .collect(),
+        }
+    }
 
-        let sorted_items = match resolve_dependencies(&self.items) {
-            Ok(items) => items,
+    /// What changed since `previous` was taken, without sending anything.
+    pub fn diff(&self, previous: &SendSnapshot) -> ProjectDiff {
+        previous.diff(&self.snapshot())
+    }
+
+    /// Diagnostics of the trees built through this project and of conflicting items (see
+    /// `add_subtree`), in insertion order, with group calls checked against every item.
+    /// Trees without any are left out.
+    pub fn diagnostics(&self) -> Vec<TreeDiagnostics> {
+        self.diagnostics
+            .iter()
+            .map(|(diagnostics, calls)| {
+                let mut diagnostics = diagnostics.clone();
+                self.check_group_calls(calls, &mut diagnostics);
+                diagnostics
+            })
+            .filter(|d| !d.is_empty())
+            .collect()
+    }
+
+    /// Assembles the final script like `send`, but fails with the diagnostics of every tree
+    /// that reported an error (warnings of those trees included).
+    ///
+    /// Group calls are checked here, against every item of the project, so callers may be
+    /// added before the groups they call.
+    ///
+    /// Trees added via `add_subtree` come as finished scripts and are not checked.
+    pub fn try_build_all(&self) -> Result<String, Vec<TreeDiagnostics>> {
+        let mut failed: Vec<TreeDiagnostics> = self
+            .diagnostics()
+            .into_iter()
+            .filter(|d| d.has_errors())
+            .collect();
+
+        match self.assemble() {
+            Ok(script) if failed.is_empty() => Ok(script),
+            Ok(_) => Err(failed),
             Err(err) => {
-                eprintln!("❌ Dependency resolution failed: {}", err);
-                return;
+                let mut project = TreeDiagnostics::new("<project>");
+                project.error(DiagnosticKind::Dependency, "", err);
+                failed.push(project);
+                Err(failed)
+            }
+        }
+    }
+
+    /// Builds `tree` and adds it as the item `tree_name`. Like `add_subtree`, a tree named like
+    /// an existing item is only kept if it's the first one (see `push_item`); the diagnostics
+    /// of every build are kept either way.
+    fn add_tree<F>(mut self, tree_name: &str, tree: NodeTree, builder: F) -> Self
+    where
+        F: FnOnce(),
+    {
+        let (script, diagnostics, calls) = self.build_tree(tree_name, tree, builder);
+        self.diagnostics.push((diagnostics, calls));
+        if let Some(script) = script {
+            self.push_item(ProjectItem {
+                name: tree_name.to_string(),
+                script,
+                dependencies: vec![],
+            });
+        }
+        self
+    }
+
+    /// Builds `tree`, recording its group calls. The script is `None` if the builder panicked
+    /// and `catch_build_panics` is set.
+    fn build_tree<F>(
+        &self,
+        tree_name: &str,
+        tree: NodeTree,
+        builder: F,
+    ) -> (Option<String>, TreeDiagnostics, Vec<GroupCall>)
+    where
+        F: FnOnce(),
+    {
+        /// Stops recording group calls, also when the builder panics.
+        struct GroupCallsGuard;
+
+        impl Drop for GroupCallsGuard {
+            fn drop(&mut self) {
+                let _ = record_group_calls(false);
+            }
+        }
+
+        let _ = record_group_calls(true);
+        let _guard = GroupCallsGuard;
+        let (script, diagnostics) = if !self.catch_build_panics {
+            let (script, diagnostics) = tree.build_with_diagnostics(builder);
+            (Some(script), diagnostics)
+        } else {
+            match catch_unwind(AssertUnwindSafe(|| tree.build_with_diagnostics(builder))) {
+                Ok((script, diagnostics)) => (Some(script), diagnostics),
+                Err(payload) => {
+                    let mut diagnostics = TreeDiagnostics::new(tree_name);
+                    diagnostics.error(
+                        DiagnosticKind::BuilderPanic,
+                        "",
+                        panic_message(payload.as_ref()),
+                    );
+                    (None, diagnostics)
+                }
             }
         };
+        (script, diagnostics, record_group_calls(false))
+    }
+
+    /// Reports an `UnknownGroup` error for each of `calls` to a group that is not an item.
+    fn check_group_calls(&self, calls: &[GroupCall], diagnostics: &mut TreeDiagnostics) {
+        for call in calls {
+            if self.contains(&call.group) {
+                continue;
+            }
+            let near = near_matches(
+                &call.group,
+                self.items.iter().map(|item| item.name.as_str()),
+            );
+            let hint = if near.is_empty() {
+                String::new()
+            } else {
+                format!(" (did you mean {}?)", near.join(", "))
+            };
+            diagnostics.error(
+                DiagnosticKind::UnknownGroup,
+                &call.node,
+                format!(
+                    "group '{}' is not a tree of this project{}; use call_external_group for groups built elsewhere",
+                    call.group, hint
+                ),
+            );
+        }
+    }
 
-        for item in sorted_items {
-            final_script.push_str(&item.script);
+    /// Adds `item` unless an item of that name exists. Same script: emitted once. Different
+    /// script: the first definition is kept and the conflict is a `DuplicateItem` warning of
+    /// that item (see `diagnostics`); the build doesn't fail over it.
+    /// The `import` lines heading its script (see `NodeTree::build`) move into the header.
+    fn push_item(&mut self, mut item: ProjectItem) {
+        let mut imports = BTreeSet::new();
+        item.script = hoist_imports(&item.script, &mut imports);
+        if let Some(existing) = self.get_item(&item.name) {
+            // Node names carry a random suffix, so a rebuilt tree is compared without them.
+            if normalize_script(&existing.script) != normalize_script(&item.script) {
+                let mut diagnostics = TreeDiagnostics::new(&item.name);
+                diagnostics.warning(
+                    DiagnosticKind::DuplicateItem,
+                    "",
+                    "added again with a different script, the first definition is kept",
+                );
+                self.diagnostics.push((diagnostics, Vec::new()));
+            }
+            return;
         }
+        self.imports.extend(imports);
+        self.observer
+            .on_item_built(&item.name, ItemStats::of_script(&item.script));
+        self.items.push(item);
+    }
+
+    /// Header plus every item script in dependency order.
+    fn assemble(&self) -> Result<String, String> {
+        self.assemble_ordered().map(|(_, script)| script)
+    }
 
+    /// `assemble`, along with the item names in the order they were assembled.
+    fn assemble_ordered(&self) -> Result<(Vec<String>, String), String> {
+        let mut final_script = generate_script_header_with(&self.imports);
+        let mut order = Vec::new();
+        for item in resolve_dependencies(&self.items)? {
+            if self.release {
+                final_script.push_str(&strip_debug_lines(&item.script));
+            } else {
+                final_script.push_str(&item.script);
+            }
+            order.push(item.name.clone());
+        }
+        Ok((order, final_script))
+    }
+
+    pub fn send(&self) {
         #[cfg(debug_assertions)]
-        eprintln!("{}", final_script);
-        send_to_blender(&final_script);
+        if let Ok(script) = self.assemble() {
+            eprintln!("{}", script);
+        }
+        println!("🍜 Blender Ramen: Sending script via Live-Link...");
+        let _ = self.try_send();
+    }
+
+    /// Sends like `send` without the banner, returning Blender's response or the failure.
+    /// Both report to the project observer (`with_observer`). Fails without sending when a
+    /// tree has errors, e.g. a caught builder panic or a call to an unknown group.
+    pub fn try_send(&self) -> Result<String, String> {
+        self.try_send_to(LIVE_LINK_ADDR)
+    }
+
+    fn try_send_to(&self, addr: &str) -> Result<String, String> {
+        self.send_to(addr, &|event| notify(self.observer.as_ref(), event))
+    }
+
+    /// Prints what changed since `previous`, then sends like `send`.
+    /// Returns the snapshot of this send, to pass to the next one.
+    pub fn send_verbose(&self, previous: &SendSnapshot) -> SendSnapshot {
+        let snapshot = self.snapshot();
+        print!("{}", previous.diff(&snapshot));
+        self.send();
+        snapshot
+    }
+
+    /// Like `send`, but reports progress to `on_event` instead of printing it (or notifying
+    /// the observer), for frontends that show their own status.
+    pub fn send_with_progress(&self, on_event: impl Fn(SendEvent)) {
+        let _ = self.send_to(LIVE_LINK_ADDR, &on_event);
+    }
+
+    /// Nothing is sent while a tree has errors (like `try_build_all`); their diagnostics are
+    /// reported as the failure instead.
+    fn send_to(&self, addr: &str, on_event: &dyn Fn(SendEvent)) -> Result<String, String> {
+        on_event(SendEvent::Resolving);
+        let failed: String = self
+            .diagnostics()
+            .iter()
+            .filter(|d| d.has_errors())
+            .map(ToString::to_string)
+            .collect();
+        if !failed.is_empty() {
+            let err = format!("Build failed:\n{}", failed);
+            on_event(SendEvent::Failed(err.clone()));
+            return Err(err);
+        }
+        match self.assemble_ordered() {
+            Ok((order, script)) => {
+                on_event(SendEvent::Resolved(order));
+                transmit(addr, &script, on_event)
+            }
+            Err(err) => {
+                let err = format!("Dependency resolution failed: {}", err);
+                on_event(SendEvent::Failed(err.clone()));
+                Err(err)
+            }
+        }
     }
 }
 
+/// `script` without its leading `import` lines, whose modules are added to `imports`.
+fn hoist_imports(script: &str, imports: &mut BTreeSet<String>) -> String {
+    let mut body = script;
+    while let Some(line) = body.strip_prefix("import ") {
+        let (module, rest) = line.split_once('\n').unwrap_or((line, ""));
+        imports.insert(module.trim().to_string());
+        body = rest;
+    }
+    body.to_string()
+}
+
 /// Topological Sort
 fn resolve_dependencies(items: &[ProjectItem]) -> Result<Vec<&ProjectItem>, String> {
     let all_names: Vec<String> = items.iter().map(|i| i.name.clone()).collect();
@@ -174,3 +496,549 @@ fn resolve_dependencies(items: &[ProjectItem]) -> Result<Vec<&ProjectItem>, Stri
         .filter_map(|name| item_map.remove(&name))
         .collect())
 }
+
+// ---------------------------------------------------------
+// unittest
+// ---------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::nodes::NodeGroupOutput;
+    use crate::core::snapshot::DiffLine;
+    use crate::core::tree::call_external_group;
+    use crate::core::types::{Float, Geo, GeometryNodeGroupExt, NodeSocket};
+
+    #[test]
+    fn test_try_build_all_reports_failing_trees() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let project = BlenderProject::new()
+            .add_geometry_tree("Good", || {
+                NodeGroupOutput::new();
+            })
+            .add_geometry_tree("Bad", || {
+                let geo = NodeSocket::<Geo>::new_output("Elsewhere_node.outputs[0]");
+                NodeGroupOutput::new().set_input(0, geo);
+            });
+
+        let failed = project.try_build_all().unwrap_err();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].tree, "Bad");
+        assert_eq!(
+            failed[0].errors().next().unwrap().kind,
+            DiagnosticKind::UnknownNode
+        );
+
+        let ok = BlenderProject::new().add_geometry_tree("Good", || {
+            NodeGroupOutput::new();
+        });
+        let script = ok.try_build_all().unwrap();
+        assert!(script.starts_with("import bpy"));
+        assert!(script.contains("# --- Setup GeoNodes: Good ---"));
+    }
+
+    #[test]
+    fn test_add_geometry_trees_at_objects() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let project = BlenderProject::new()
+            .add_geometry_tree_at_object("Single", "Plane", || {
+                NodeGroupOutput::new();
+            })
+            .add_geometry_trees_at_objects("Scatter", &["Cube", "Suzanne"], |_object| {
+                NodeGroupOutput::new();
+            });
+
+        let names: Vec<&str> = project.items.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["Single", "Scatter_Cube", "Scatter_Suzanne"]);
+
+        for (item, object) in project.items.iter().zip(["Plane", "Cube", "Suzanne"]) {
+            let select = format!("obj = bpy.data.objects.get(\"{}\")", object);
+            let select_at = item.script.find(&select).unwrap();
+            let attach_at = item
+                .script
+                .find("mod = obj.modifiers.new(name=mod_name, type='NODES')")
+                .unwrap();
+            assert!(select_at < attach_at);
+            assert!(!item.script.contains("bpy.context.object"));
+        }
+    }
+
+    #[test]
+    fn test_send_with_progress_event_sequence() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+        use std::sync::Mutex;
+
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = String::new();
+            stream.read_to_string(&mut received).unwrap();
+            stream.write_all(b"OK").unwrap();
+            received
+        });
+
+        let project = BlenderProject::new().add_geometry_tree("Mock", || {
+            NodeGroupOutput::new();
+        });
+        let events = Mutex::new(Vec::new());
+        let result = project.send_to(&addr, &|e| events.lock().unwrap().push(e));
+
+        let received = server.join().unwrap();
+        assert_eq!(result, Ok("OK".to_string()));
+        assert_eq!(received, project.assemble().unwrap());
+        assert_eq!(
+            events.into_inner().unwrap(),
+            vec![
+                SendEvent::Resolving,
+                SendEvent::Resolved(vec!["Mock".to_string()]),
+                SendEvent::Sending(received.len()),
+                SendEvent::Progress(received.len()),
+                SendEvent::Response("OK".to_string()),
+            ]
+        );
+
+        let cyclic = BlenderProject::new()
+            .add_subtree("A", "'B'")
+            .add_subtree("B", "'A'");
+        let events = Mutex::new(Vec::new());
+        assert!(
+            cyclic
+                .send_to(&addr, &|e| events.lock().unwrap().push(e))
+                .is_err()
+        );
+        let events = events.into_inner().unwrap();
+        assert_eq!(events[0], SendEvent::Resolving);
+        assert!(matches!(&events[1], SendEvent::Failed(err) if err.contains("Cyclic")));
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn test_item_accessors() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let project = BlenderProject::new()
+            .add_shader_tree("Mat", || {})
+            .add_subtree("Helper", "# helper\n");
+
+        let names: Vec<&str> = project.items().iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, vec!["Mat", "Helper"]);
+
+        let mat = project.get_item("Mat").unwrap();
+        assert!(mat.script.contains("# --- Setup Shader: Mat ---"));
+        assert!(project.contains("Mat"));
+        assert!(project.contains("Helper"));
+        assert!(!project.contains("Missing"));
+        assert!(project.get_item("Missing").is_none());
+    }
+
+    #[test]
+    fn test_mathutils_import_only_when_needed() {
+        use crate::core::nodes::FunctionNodeRotateRotation;
+        use crate::core::types::{NodeSocket, Rotation};
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let plain = BlenderProject::new()
+            .add_geometry_tree("Plain", || {})
+            .assemble()
+            .unwrap();
+        assert!(plain.starts_with("import bpy\n"));
+        assert!(!plain.contains("import mathutils"));
+
+        let rotated = BlenderProject::new()
+            .add_geometry_tree("Rotated", || {
+                let _ = FunctionNodeRotateRotation::new()
+                    .with_rotation(NodeSocket::<Rotation>::from((0.0, 0.0, 1.0)));
+            })
+            .assemble()
+            .unwrap();
+        assert!(rotated.starts_with("import bpy\nimport mathutils\n"));
+        assert!(rotated.contains("mathutils.Euler((0.0000, 0.0000, 1.0000))"));
+    }
+
+    #[test]
+    fn test_math_import_once_for_constants() {
+        use crate::core::nodes::ShaderNodeMath;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let plain = BlenderProject::new()
+            .add_geometry_tree("Plain", || {
+                let _ = ShaderNodeMath::new().with_value(std::f32::consts::PI);
+            })
+            .assemble()
+            .unwrap();
+        assert!(!plain.contains("import math"));
+
+        let group = NodeTree::new_geometry_group("Turn")
+            .with_output::<Float>("Angle")
+            .build(|| {
+                NodeGroupOutput::new().set_input(0, NodeSocket::<Float>::tau());
+            });
+        let script = BlenderProject::new()
+            .add_geometry_tree("Circle", || {
+                let half = ShaderNodeMath::new()
+                    .with_value(NodeSocket::<Float>::pi())
+                    .out_value();
+                let _ = ShaderNodeMath::new()
+                    .with_value(half)
+                    .with_value_1(NodeSocket::<Float>::pi());
+            })
+            .add_subtree("Turn", &group)
+            .assemble()
+            .unwrap();
+        assert!(script.starts_with("import bpy\nimport math\n"));
+        assert_eq!(script.matches("import math").count(), 1);
+        assert!(script.contains(".default_value = math.pi\n"));
+        assert!(script.contains(".default_value = math.tau\n"));
+    }
+
+    #[test]
+    fn test_group_calls_checked_against_project() {
+        use crate::core::tree::{call_external_group, call_geometry_group};
+        use std::sync::Mutex;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let call = |group: &'static str| {
+            move || {
+                let step = call_geometry_group(group).out_socket::<Geo>("Geometry");
+                NodeGroupOutput::new().set_input(0, step);
+            }
+        };
+        let project = || {
+            BlenderProject::new()
+                .add_subtree("MandelbulbStep", "")
+                .add_subtree("MandelboxStep", "")
+        };
+
+        project()
+            .add_geometry_tree("Main", call("MandelbulbStep"))
+            .try_build_all()
+            .unwrap();
+        // Groups are checked against the final items: the callee may be added later.
+        BlenderProject::new()
+            .add_geometry_tree("Main", call("MandelbulbStep"))
+            .add_subtree("MandelbulbStep", "")
+            .try_build_all()
+            .unwrap();
+        project()
+            .add_geometry_tree("Main", || {
+                let geo = call_external_group("AssetGroup").out_socket::<Geo>("Geometry");
+                NodeGroupOutput::new().set_input(0, geo);
+            })
+            .try_build_all()
+            .unwrap();
+
+        let failed = project()
+            .add_geometry_tree("Main", call("MandlebulbStep"))
+            .try_build_all()
+            .unwrap_err();
+        let error = failed[0].errors().next().unwrap();
+        assert_eq!(error.kind, DiagnosticKind::UnknownGroup);
+        assert!(error.node.starts_with("GeometryNodeGroup_"));
+        assert_eq!(
+            error.message,
+            "group 'MandlebulbStep' is not a tree of this project \
+             (did you mean MandelbulbStep?); \
+             use call_external_group for groups built elsewhere"
+        );
+
+        // Sending is refused as well, before anything goes out.
+        let events = Mutex::new(Vec::new());
+        let err = project()
+            .add_geometry_tree("Main", call("MandlebulbStep"))
+            .send_to("127.0.0.1:0", &|e| events.lock().unwrap().push(e))
+            .unwrap_err();
+        assert!(err.contains("group 'MandlebulbStep' is not a tree of this project"));
+        assert_eq!(
+            events.into_inner().unwrap(),
+            vec![SendEvent::Resolving, SendEvent::Failed(err)]
+        );
+
+        // Outside a project nothing is checked.
+        let (_, diagnostics) =
+            NodeTree::new_geometry("Loose").build_with_diagnostics(call("Anything"));
+        assert!(!diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_subtree_is_ordered_before_dependent_tree() {
+        use crate::core::tree::call_geometry_group;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let step = NodeTree::new_geometry_group("Step")
+            .with_input::<Geo>("Geometry")
+            .with_output::<Geo>("Geometry")
+            .build(|| {});
+        let main = || {
+            let geo = call_geometry_group("Step").out_socket::<Geo>("Geometry");
+            NodeGroupOutput::new().set_input(0, geo);
+        };
+
+        let project = BlenderProject::new()
+            .add_subtree("Step", &step)
+            .add_geometry_tree("Main", main);
+        assert_eq!(project.get_item("Step").unwrap().script, step);
+        assert!(project.try_build_all().is_ok());
+
+        // Added after the tree that calls it; still assembled first.
+        let project = BlenderProject::new()
+            .add_geometry_tree("Main", main)
+            .add_subtree("Step", &step);
+        let script = project.try_build_all().unwrap();
+        let (order, ordered) = project.assemble_ordered().unwrap();
+        assert_eq!(order, ["Step", "Main"]);
+        assert_eq!(script, ordered);
+    }
+
+    #[test]
+    fn test_compositor_group_is_resolved_before_callers() {
+        use crate::core::nodes::CompositorNodeRLayers;
+        use crate::core::tree::call_compositor_group;
+        use crate::core::types::{Color, CompositorNodeGroupExt};
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let caller = NodeTree::new_compositor("Shot").build(|| {
+            let look = call_compositor_group("Look")
+                .set_input(0, CompositorNodeRLayers::new().out_image());
+            NodeGroupOutput::new().set_input(0, look.out_socket::<Color>("Image"));
+        });
+        let group = NodeTree::new_compositor_group("Look")
+            .with_input::<Color>("Image")
+            .with_output::<Color>("Image")
+            .build(|| {});
+
+        // Added caller first; the group's script must still come first.
+        let script = BlenderProject::new()
+            .add_subtree("Shot", &caller)
+            .add_subtree("Look", &group)
+            .assemble()
+            .unwrap();
+        let group_setup = script
+            .find("# --- Setup Compositor Group: Look ---")
+            .unwrap();
+        let caller_setup = script.find("# --- Setup Compositor: Shot ---").unwrap();
+        assert!(group_setup < caller_setup);
+        assert!(script.contains(".node_tree = bpy.data.node_groups[\"Look\"]"));
+    }
+
+    #[test]
+    fn test_diff_against_snapshot() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let build = |offset: f32, with_extra: bool, with_old: bool| {
+            let mut project = BlenderProject::new().add_geometry_tree("Main", move || {
+                let value = NodeSocket::<Float>::new_output("Elsewhere_node.outputs[0]") + offset;
+                let _ = value * 2.0;
+                NodeGroupOutput::new();
+            });
+            project = project.add_shader_tree("Mat", || {
+                NodeGroupOutput::new();
+            });
+            if with_extra {
+                project = project.add_shader_tree("Extra", || {});
+            }
+            if with_old {
+                project = project.add_shader_tree("Old", || {});
+            }
+            project
+        };
+
+        let previous = build(1.0, false, true).snapshot();
+        assert!(build(1.0, false, true).diff(&previous).is_empty());
+
+        let diff = build(3.0, true, false).diff(&previous);
+        assert_eq!(diff.added, vec!["Extra".to_string()]);
+        assert_eq!(diff.removed, vec!["Old".to_string()]);
+        assert_eq!(diff.unchanged, vec!["Mat".to_string()]);
+        assert_eq!(diff.modified.len(), 1);
+
+        let main = &diff.modified[0];
+        assert_eq!(main.name, "Main");
+        assert_eq!(main.node_delta(), 0);
+        let changed: Vec<_> = main
+            .lines
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Same(_)))
+            .collect();
+        assert_eq!(changed.len(), 2);
+        assert!(matches!(changed[0], DiffLine::Removed(l) if l.ends_with("= 1.0000")));
+        assert!(matches!(changed[1], DiffLine::Added(l) if l.ends_with("= 3.0000")));
+
+        let text = diff.to_string();
+        assert!(text.contains("modified:  Main (3 -> 3 nodes, +0)"));
+        assert!(text.contains("\n-") && text.contains("\n+"));
+    }
+
+    #[test]
+    fn test_observer_event_sequence() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        impl Recorder {
+            fn push(&self, event: String) {
+                self.0.lock().unwrap().push(event);
+            }
+        }
+
+        impl ProjectObserver for Recorder {
+            fn on_item_built(&self, name: &str, stats: ItemStats) {
+                self.push(format!("built {} ({} nodes)", name, stats.node_count));
+            }
+            fn on_resolve(&self, order: &[String]) {
+                self.push(format!("resolve {}", order.join(",")));
+            }
+            fn on_send_start(&self, total_bytes: usize) {
+                self.push(format!("start {}", total_bytes));
+            }
+            fn on_send_progress(&self, sent: usize) {
+                self.push(format!("progress {}", sent));
+            }
+            fn on_response(&self, result: &Result<String, String>) {
+                self.push(format!("response {:?}", result));
+            }
+        }
+
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut received = String::new();
+            stream.read_to_string(&mut received).unwrap();
+            stream.write_all(b"OK").unwrap();
+            received
+        });
+
+        let recorder = Recorder::default();
+        // Main is added first but calls Sub, so Sub is sent first.
+        let project = BlenderProject::new()
+            .with_observer(recorder.clone())
+            .add_geometry_tree("Main", || {
+                call_external_group("Sub");
+                NodeGroupOutput::new();
+            })
+            .add_subtree("Sub", "# Sub\n");
+        let result = project.try_send_to(&addr);
+
+        let received = server.join().unwrap();
+        assert_eq!(result, Ok("OK".to_string()));
+        assert_eq!(
+            recorder.0.lock().unwrap().clone(),
+            vec![
+                "built Main (2 nodes)".to_string(),
+                "built Sub (0 nodes)".to_string(),
+                "resolve Sub,Main".to_string(),
+                format!("start {}", received.len()),
+                format!("progress {}", received.len()),
+                "response Ok(\"OK\")".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_caught_builder_panic_becomes_diagnostic() {
+        use std::sync::Mutex;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let project = BlenderProject::new()
+            .catch_build_panics()
+            .add_geometry_tree("Exploding", || {
+                NodeGroupOutput::new();
+                panic!("radius must be positive");
+            })
+            .add_geometry_tree("Good", || {
+                NodeGroupOutput::new();
+            });
+
+        assert!(!project.contains("Exploding"));
+        assert!(project.contains("Good"));
+        let failed = project.try_build_all().unwrap_err();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].tree, "Exploding");
+        let error = failed[0].errors().next().unwrap();
+        assert_eq!(error.kind, DiagnosticKind::BuilderPanic);
+        assert!(error.message.contains("tree 'Exploding' (Geometry)"));
+        assert!(error.message.ends_with("radius must be positive"));
+
+        // The good tree alone is not sent either.
+        let events = Mutex::new(Vec::new());
+        let err = project
+            .send_to("127.0.0.1:0", &|e| events.lock().unwrap().push(e))
+            .unwrap_err();
+        assert!(err.contains("tree 'Exploding'"));
+        assert!(err.contains("radius must be positive"));
+        assert_eq!(
+            events.into_inner().unwrap(),
+            vec![SendEvent::Resolving, SendEvent::Failed(err)]
+        );
+    }
+
+    #[test]
+    fn test_repeated_subtree_is_emitted_once() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let group = "# --- Setup Group: Shared ---\n";
+        let project = BlenderProject::new()
+            .add_subtree("Shared", group)
+            .add_subtree("Shared", group);
+        assert_eq!(project.items().len(), 1);
+        let script = project.try_build_all().unwrap();
+        assert_eq!(script.matches(group).count(), 1);
+
+        // A conflicting definition is dropped with a warning, not a failed build.
+        let conflicting = project.add_subtree("Shared", "# --- Setup Group: Other ---\n");
+        assert_eq!(conflicting.items().len(), 1);
+        let script = conflicting.try_build_all().unwrap();
+        assert_eq!(script.matches(group).count(), 1);
+        assert!(!script.contains("Other"));
+        let diagnostics = conflicting.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].tree, "Shared");
+        let warning = diagnostics[0].warnings().next().unwrap();
+        assert_eq!(warning.kind, DiagnosticKind::DuplicateItem);
+        assert!(!diagnostics[0].has_errors());
+
+        // Trees go through the same path.
+        let trees = BlenderProject::new()
+            .add_geometry_tree("Main", || {
+                NodeGroupOutput::new();
+            })
+            .add_geometry_tree("Main", || {
+                NodeGroupOutput::new();
+            })
+            .add_geometry_tree("Main", || {
+                NodeGroupOutput::new();
+                NodeGroupOutput::new();
+            });
+        assert_eq!(trees.items().len(), 1);
+        let script = trees.try_build_all().unwrap();
+        assert_eq!(script.matches("# --- Setup GeoNodes: Main ---").count(), 1);
+        assert_eq!(
+            script.matches("tree.nodes.new('NodeGroupOutput')").count(),
+            1
+        );
+        // Only the conflicting third tree is reported.
+        let diagnostics = trees.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].warnings().next().unwrap().kind,
+            DiagnosticKind::DuplicateItem
+        );
+    }
+
+    #[test]
+    fn test_project_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<BlenderProject>();
+    }
+}
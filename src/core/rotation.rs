@@ -0,0 +1,144 @@
+//! # Rotation Utilities
+//!
+//! Thin wrappers over the rotation function nodes, so that orienting instances along
+//! tangents or normals reads as a function call instead of pin indices and enum strings.
+
+use crate::core::nodes::{
+    FunctionNodeAlignRotationToVector, FunctionNodeAlignRotationToVectorAxis,
+    FunctionNodeAlignRotationToVectorPivotAxis, FunctionNodeAxisAngleToRotation,
+    FunctionNodeEulerToRotation, FunctionNodeRotateRotation,
+    FunctionNodeRotateRotationRotationSpace, FunctionNodeRotationToEuler,
+};
+use crate::core::types::{Float, NodeSocket, Rotation, Vector};
+
+/// Local axis that `align_to_vector` points along the target vector.
+pub type Axis = FunctionNodeAlignRotationToVectorAxis;
+/// Axis `align_to_vector` rotates around (`Auto` picks the shortest rotation).
+pub type Pivot = FunctionNodeAlignRotationToVectorPivotAxis;
+/// Space `rotate_rotation` applies the second rotation in.
+pub type Space = FunctionNodeRotateRotationRotationSpace;
+
+/// Rotation that points `axis` along `vector`, rotating around `pivot`.
+///
+/// `factor` blends from the identity rotation (0.0) to the full alignment (1.0).
+pub fn align_to_vector(
+    vector: NodeSocket<Vector>,
+    axis: Axis,
+    pivot: Pivot,
+    factor: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Rotation> {
+    FunctionNodeAlignRotationToVector::new()
+        .with_axis(axis)
+        .with_pivot_axis(pivot)
+        .with_factor(factor)
+        .with_vector(vector)
+        .out_rotation()
+}
+
+/// Converts XYZ euler angles (radians) to a rotation.
+pub fn euler_to_rotation(euler: NodeSocket<Vector>) -> NodeSocket<Rotation> {
+    FunctionNodeEulerToRotation::new()
+        .with_euler(euler)
+        .out_rotation()
+}
+
+/// Converts a rotation to XYZ euler angles (radians).
+pub fn rotation_to_euler(rotation: NodeSocket<Rotation>) -> NodeSocket<Vector> {
+    FunctionNodeRotationToEuler::new()
+        .with_rotation(rotation)
+        .out_euler()
+}
+
+/// Rotation of `angle` radians around `axis`.
+pub fn axis_angle(
+    axis: NodeSocket<Vector>,
+    angle: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Rotation> {
+    FunctionNodeAxisAngleToRotation::new()
+        .with_axis(axis)
+        .with_angle(angle)
+        .out_rotation()
+}
+
+/// Applies `rotate_by` on top of `rotation`, in the given `space`.
+pub fn rotate_rotation(
+    rotation: NodeSocket<Rotation>,
+    rotate_by: NodeSocket<Rotation>,
+    space: Space,
+) -> NodeSocket<Rotation> {
+    FunctionNodeRotateRotation::new()
+        .with_rotation_space(space)
+        .with_rotation(rotation)
+        .with_rotate_by(rotate_by)
+        .out_rotation()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+
+    #[test]
+    fn test_align_to_vector() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let tangent = NodeSocket::<Vector>::new_output("tangent_node.outputs[0]");
+        let rot: NodeSocket<Rotation> = align_to_vector(tangent, Axis::Z, Pivot::Auto, 1.0);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.bl_idname, "FunctionNodeAlignRotationToVector");
+        assert_eq!(node.properties.get("axis").unwrap(), "\"Z\"");
+        assert_eq!(node.properties.get("pivot_axis").unwrap(), "\"AUTO\"");
+        assert_eq!(node.inputs.get(&1).unwrap()[0].expr, "1.0000");
+        assert_eq!(node.inputs.get(&2).unwrap()[0].expr, tangent.python_expr());
+        assert!(rot.python_expr().starts_with(&node.name));
+    }
+
+    #[test]
+    fn test_euler_conversions_and_axis_angle() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let euler = NodeSocket::<Vector>::new_output("euler_node.outputs[0]");
+        let rot: NodeSocket<Rotation> = euler_to_rotation(euler);
+        let back: NodeSocket<Vector> = rotation_to_euler(rot);
+        let spin: NodeSocket<Rotation> = axis_angle(back, 0.5);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].bl_idname, "FunctionNodeEulerToRotation");
+        assert_eq!(nodes[1].bl_idname, "FunctionNodeRotationToEuler");
+        assert_eq!(nodes[1].inputs.get(&0).unwrap()[0].expr, rot.python_expr());
+        assert_eq!(nodes[2].bl_idname, "FunctionNodeAxisAngleToRotation");
+        assert_eq!(nodes[2].inputs.get(&0).unwrap()[0].expr, back.python_expr());
+        assert_eq!(nodes[2].inputs.get(&1).unwrap()[0].expr, "0.5000");
+        assert!(spin.python_expr().starts_with(&nodes[2].name));
+    }
+
+    #[test]
+    fn test_rotate_rotation_space() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let a = NodeSocket::<Rotation>::new_output("a_node.outputs[0]");
+        let b = NodeSocket::<Rotation>::new_output("b_node.outputs[0]");
+        let _ = rotate_rotation(a, b, Space::Local);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "FunctionNodeRotateRotation");
+        assert_eq!(
+            nodes[0].properties.get("rotation_space").unwrap(),
+            "\"LOCAL\""
+        );
+        assert_eq!(nodes[0].inputs.get(&0).unwrap()[0].expr, a.python_expr());
+        assert_eq!(nodes[0].inputs.get(&1).unwrap()[0].expr, b.python_expr());
+    }
+}
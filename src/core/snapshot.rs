@@ -0,0 +1,369 @@
+//! # Send Snapshots
+//!
+//! What a project looked like when it was sent, for showing what changed before the next send:
+//!
+//! ```ignore
+//! let previous = SendSnapshot::read_from("last_send.json").unwrap_or_default();
+//! let project = build_project();
+//! print!("{}", project.diff(&previous));
+//! project.snapshot().write_to("last_send.json")?;
+//! ```
+//!
+//! Scripts are normalized before they are compared: the random suffixes of node names are
+//! replaced by their order of appearance, so rebuilding an unchanged tree gives the same snapshot.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// Length of the hex suffix generated node names end with (`ShaderNodeMath_0123456789ab`).
+const NODE_SUFFIX_LEN: usize = 12;
+
+/// Lines of unchanged context shown around each change.
+const DIFF_CONTEXT: usize = 2;
+
+/// Above this many LCS table cells, a modified item is diffed as a full replacement.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendSnapshot {
+    /// Items in project insertion order.
+    pub items: Vec<SnapshotItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotItem {
+    pub name: String,
+    /// FNV-1a hash of `script`, as 16 hex digits.
+    pub hash: String,
+    pub node_count: usize,
+    /// The item script with node names normalized.
+    pub script: String,
+}
+
+impl SnapshotItem {
+    pub fn new(name: &str, script: &str) -> Self {
+        let script = normalize_script(script);
+        Self {
+            name: name.to_string(),
+            hash: format!("{:016x}", fnv1a(&script)),
+            node_count: script.matches(" = tree.nodes.new(").count(),
+            script,
+        }
+    }
+}
+
+impl SendSnapshot {
+    pub fn get(&self, name: &str) -> Option<&SnapshotItem> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    /// Changes from `self` to `newer`.
+    pub fn diff(&self, newer: &SendSnapshot) -> ProjectDiff {
+        let mut diff = ProjectDiff::default();
+
+        for item in &newer.items {
+            match self.get(&item.name) {
+                None => diff.added.push(item.name.clone()),
+                Some(old) if old.hash == item.hash => diff.unchanged.push(item.name.clone()),
+                Some(old) => diff.modified.push(ItemDiff {
+                    name: item.name.clone(),
+                    nodes_before: old.node_count,
+                    nodes_after: item.node_count,
+                    lines: diff_lines(&old.script, &item.script),
+                }),
+            }
+        }
+        diff.removed = self
+            .items
+            .iter()
+            .filter(|old| newer.get(&old.name).is_none())
+            .map(|old| old.name.clone())
+            .collect();
+        diff
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("SendSnapshot is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|err| format!("invalid send snapshot: {}", err))
+    }
+
+    pub fn write_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    pub fn read_from(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Differences between two snapshots, item names in the newer snapshot's order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<ItemDiff>,
+    pub unchanged: Vec<String>,
+}
+
+impl ProjectDiff {
+    /// True if no item was added, removed or modified.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemDiff {
+    pub name: String,
+    pub nodes_before: usize,
+    pub nodes_after: usize,
+    /// Every line of both scripts, in diff order.
+    pub lines: Vec<DiffLine>,
+}
+
+impl ItemDiff {
+    pub fn node_delta(&self) -> isize {
+        self.nodes_after as isize - self.nodes_before as isize
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+impl fmt::Display for ProjectDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes ({} unchanged)", self.unchanged.len());
+        }
+        for name in &self.added {
+            writeln!(f, "added:     {}", name)?;
+        }
+        for name in &self.removed {
+            writeln!(f, "removed:   {}", name)?;
+        }
+        for item in &self.modified {
+            writeln!(
+                f,
+                "modified:  {} ({} -> {} nodes, {:+})",
+                item.name,
+                item.nodes_before,
+                item.nodes_after,
+                item.node_delta()
+            )?;
+        }
+        for item in &self.modified {
+            writeln!(f, "--- {}\n+++ {}", item.name, item.name)?;
+            write_hunks(f, &item.lines)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the changed lines with `DIFF_CONTEXT` lines around them, hunks separated by `@@`.
+fn write_hunks(f: &mut fmt::Formatter<'_>, lines: &[DiffLine]) -> fmt::Result {
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut shown_until = 0;
+    for (n, &i) in changed.iter().enumerate() {
+        let start = i.saturating_sub(DIFF_CONTEXT).max(shown_until);
+        if n == 0 || start > shown_until {
+            writeln!(f, "@@")?;
+        }
+        let next_change = changed.get(n + 1).copied().unwrap_or(lines.len());
+        let end = (i + 1 + DIFF_CONTEXT).min(next_change).min(lines.len());
+        for line in &lines[start..end] {
+            match line {
+                DiffLine::Same(text) => writeln!(f, " {}", text)?,
+                DiffLine::Removed(text) => writeln!(f, "-{}", text)?,
+                DiffLine::Added(text) => writeln!(f, "+{}", text)?,
+            }
+        }
+        shown_until = end;
+    }
+    Ok(())
+}
+
+/// Line diff of two scripts: common prefix and suffix, with an LCS diff of the rest.
+pub(crate) fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix]
+        .iter()
+        .map(|l| DiffLine::Same(l.to_string()))
+        .collect();
+    if (old_mid.len() + 1) * (new_mid.len() + 1) > MAX_LCS_CELLS {
+        lines.extend(old_mid.iter().map(|l| DiffLine::Removed(l.to_string())));
+        lines.extend(new_mid.iter().map(|l| DiffLine::Added(l.to_string())));
+    } else {
+        lines.extend(lcs_diff(old_mid, new_mid));
+    }
+    lines.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|l| DiffLine::Same(l.to_string())),
+    );
+    lines
+}
+
+fn lcs_diff(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    // table[i][j]: LCS length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut table = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    lines.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    lines
+}
+
+/// Replaces the random suffix of every generated node name by its order of appearance,
+/// e.g. `ShaderNodeMath_3f9a0c1b2d4e` -> `ShaderNodeMath_n0`.
+pub(crate) fn normalize_script(script: &str) -> String {
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut out = String::with_capacity(script.len());
+    let mut rest = script;
+
+    while let Some(start) = rest.find(is_ident_char) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+        let (ident, tail) = rest.split_at(end);
+
+        match node_name_prefix(ident) {
+            Some(prefix) => {
+                let next = ids.len();
+                let id = *ids.entry(ident).or_insert(next);
+                out.push_str(prefix);
+                out.push_str(&format!("_n{}", id));
+            }
+            None => out.push_str(ident),
+        }
+        rest = tail;
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// `Some("ShaderNodeMath")` for `ShaderNodeMath_0123456789ab`.
+fn node_name_prefix(ident: &str) -> Option<&str> {
+    let split = ident.len().checked_sub(NODE_SUFFIX_LEN + 1)?;
+    let (prefix, suffix) = ident.split_at(split);
+    let hex = suffix.strip_prefix('_')?;
+    let is_suffix = hex
+        .bytes()
+        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    (is_suffix && !prefix.is_empty()).then_some(prefix)
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` it is stable across Rust versions,
+/// so hashes in saved snapshots stay comparable.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_script() {
+        let script = "ShaderNodeMath_0123456789ab = tree.nodes.new('ShaderNodeMath')\n\
+                      x = ShaderNodeMath_0123456789ab.outputs[0]\n\
+                      NodeGroupOutput_ba9876543210.name = 'out'\n\
+                      math_1 = my_var_abc";
+        assert_eq!(
+            normalize_script(script),
+            "ShaderNodeMath_n0 = tree.nodes.new('ShaderNodeMath')\n\
+             x = ShaderNodeMath_n0.outputs[0]\n\
+             NodeGroupOutput_n1.name = 'out'\n\
+             math_1 = my_var_abc"
+        );
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let lines = diff_lines("a\nb\nc\nd", "a\nc\nx\nd");
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("x".to_string()),
+                DiffLine::Same("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_json_round_trip() {
+        let snapshot = SendSnapshot {
+            items: vec![SnapshotItem::new("Tree", "a = tree.nodes.new('X')\n")],
+        };
+        assert_eq!(snapshot.items[0].node_count, 1);
+        assert_eq!(
+            SendSnapshot::from_json(&snapshot.to_json()).unwrap(),
+            snapshot
+        );
+        assert!(SendSnapshot::from_json("{").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! # Testing Generated Graphs
+//!
+//! The supported way to assert on the nodes a builder creates, from inside or outside this crate:
+//!
+//! ```ignore
+//! use blender_ramen::core::testing;
+//!
+//! let nodes = testing::capture(|| {
+//!     let _ = my_builder(NodeSocket::from(1.0));
+//! });
+//! assert_eq!(nodes[0].bl_idname, "ShaderNodeMath");
+//! assert_eq!(nodes[0].property("operation"), Some("\"ADD\""));
+//! ```
+//!
+//! `NodeInfo` is a stable projection of the internal node data; use `NodeTree::build_inspect`
+//! to get it together with the script of a whole tree.
+
+use crate::core::context::NodeData;
+use crate::core::graph::NodeGraph;
+use crate::core::tree::NodeTree;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+/// Serializes access to the global build context. Tests that build nodes without `capture`
+/// (e.g. through `NodeTree::build`) should hold it too, since cargo runs tests in parallel.
+pub static TEST_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub name: String,
+    pub bl_idname: String,
+    /// Property values as emitted Python expressions (enum values include their quotes).
+    pub properties: BTreeMap<String, String>,
+    /// Literal input values by pin index, as emitted Python expressions.
+    pub inputs: BTreeMap<usize, String>,
+    /// Links into this node's input pins.
+    pub links: Vec<NodeLink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeLink {
+    pub from_node: String,
+    /// Output socket name, or its index for links by position.
+    pub from_socket: String,
+    pub to_input: usize,
+}
+
+impl NodeInfo {
+    pub(crate) fn from_nodes(nodes: &[NodeData]) -> Vec<NodeInfo> {
+        let graph = NodeGraph::from_nodes("", nodes);
+        graph
+            .nodes
+            .into_iter()
+            .map(|node| NodeInfo {
+                links: graph
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.to_node == node.name)
+                    .map(|edge| NodeLink {
+                        from_node: edge.from_node.clone(),
+                        from_socket: edge.from_socket.clone(),
+                        to_input: edge.to_input,
+                    })
+                    .collect(),
+                name: node.name,
+                bl_idname: node.bl_idname,
+                properties: node.properties,
+                inputs: node.defaults,
+            })
+            .collect()
+    }
+
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    pub fn input(&self, index: usize) -> Option<&str> {
+        self.inputs.get(&index).map(String::as_str)
+    }
+
+    /// Links into input pin `index`.
+    pub fn links_to(&self, index: usize) -> impl Iterator<Item = &NodeLink> {
+        self.links.iter().filter(move |link| link.to_input == index)
+    }
+}
+
+/// Runs `body` in its own scope and returns the nodes it created, in creation order.
+///
+/// Holds `TEST_LOCK` while running, so it must not be called while already holding it.
+pub fn capture<F>(body: F) -> Vec<NodeInfo>
+where
+    F: FnOnce(),
+{
+    let _lock = TEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    NodeInfo::from_nodes(&NodeTree::collect_nodes(body))
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use blender_ramen::core::nodes::GeometryNodeSetPosition;
+    use blender_ramen::core::testing::{self, NodeLink};
+    use blender_ramen::core::types::{Float, Geo, NodeSocket, Vector};
+
+    // A builder as a downstream crate would write it.
+    fn lift(geo: NodeSocket<Geo>, height: f32) -> NodeSocket<Geo> {
+        let offset = NodeSocket::<Float>::from(height) * 2.0;
+        GeometryNodeSetPosition::new()
+            .with_geometry(geo)
+            .with_offset(NodeSocket::<Vector>::from((0.0, 0.0, 1.0)) * offset)
+            .out_geometry()
+    }
+
+    #[test]
+    fn test_capture_downstream_builder() {
+        let nodes = testing::capture(|| {
+            let _ = lift(NodeSocket::new_output("src.outputs[0]"), 0.5);
+        });
+
+        let set_pos = nodes
+            .iter()
+            .find(|n| n.bl_idname == "GeometryNodeSetPosition")
+            .unwrap();
+        assert_eq!(set_pos.links.len(), 2);
+        assert_eq!(
+            set_pos
+                .links_to(GeometryNodeSetPosition::PIN_GEOMETRY)
+                .collect::<Vec<_>>(),
+            vec![&NodeLink {
+                from_node: "src".to_string(),
+                from_socket: "0".to_string(),
+                to_input: GeometryNodeSetPosition::PIN_GEOMETRY,
+            }]
+        );
+
+        let mul = nodes
+            .iter()
+            .find(|n| n.bl_idname == "ShaderNodeMath")
+            .unwrap();
+        assert_eq!(mul.property("operation"), Some("\"MULTIPLY\""));
+        assert_eq!(mul.input(1), Some("2.0000"));
+    }
+
+    #[test]
+    fn test_capture_is_isolated_per_call() {
+        let first = testing::capture(|| {
+            let _ = NodeSocket::<Float>::from(1.0) + 2.0;
+        });
+        let second = testing::capture(|| {});
+        assert_eq!(first.len(), 1);
+        assert!(second.is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+//! # Texture Helpers
+//!
+//! Builders for the procedural textures whose available sockets depend on their properties:
+//! Noise and Voronoi show different inputs per `dimensions`, and Voronoi different outputs per
+//! `feature`. The builders set those properties from the variants they are given and wire only
+//! the pins that are valid for them.
+
+use crate::core::nodes::{
+    ShaderNodeTexNoise, ShaderNodeTexNoiseNoiseDimensions, ShaderNodeTexVoronoi,
+    ShaderNodeTexVoronoiFeature, ShaderNodeTexVoronoiVoronoiDimensions,
+};
+use crate::core::types::{Color, Float, NodeSocket, Vector};
+
+/// Texture space of Noise and Voronoi, with the coordinate inputs it uses.
+/// 2D reads only the x and y of `vector`.
+#[derive(Clone, Copy)]
+pub enum Dimensions {
+    D1 {
+        w: NodeSocket<Float>,
+    },
+    D2 {
+        vector: NodeSocket<Vector>,
+    },
+    D3 {
+        vector: NodeSocket<Vector>,
+    },
+    D4 {
+        vector: NodeSocket<Vector>,
+        w: NodeSocket<Float>,
+    },
+}
+
+impl Dimensions {
+    fn vector(&self) -> Option<NodeSocket<Vector>> {
+        match *self {
+            Dimensions::D1 { .. } => None,
+            Dimensions::D2 { vector }
+            | Dimensions::D3 { vector }
+            | Dimensions::D4 { vector, .. } => Some(vector),
+        }
+    }
+
+    fn w(&self) -> Option<NodeSocket<Float>> {
+        match *self {
+            Dimensions::D1 { w } | Dimensions::D4 { w, .. } => Some(w),
+            Dimensions::D2 { .. } | Dimensions::D3 { .. } => None,
+        }
+    }
+
+    fn noise_dimensions(&self) -> ShaderNodeTexNoiseNoiseDimensions {
+        match self {
+            Dimensions::D1 { .. } => ShaderNodeTexNoiseNoiseDimensions::Variant1d,
+            Dimensions::D2 { .. } => ShaderNodeTexNoiseNoiseDimensions::Variant2d,
+            Dimensions::D3 { .. } => ShaderNodeTexNoiseNoiseDimensions::Variant3d,
+            Dimensions::D4 { .. } => ShaderNodeTexNoiseNoiseDimensions::Variant4d,
+        }
+    }
+
+    fn voronoi_dimensions(&self) -> ShaderNodeTexVoronoiVoronoiDimensions {
+        match self {
+            Dimensions::D1 { .. } => ShaderNodeTexVoronoiVoronoiDimensions::Variant1d,
+            Dimensions::D2 { .. } => ShaderNodeTexVoronoiVoronoiDimensions::Variant2d,
+            Dimensions::D3 { .. } => ShaderNodeTexVoronoiVoronoiDimensions::Variant3d,
+            Dimensions::D4 { .. } => ShaderNodeTexVoronoiVoronoiDimensions::Variant4d,
+        }
+    }
+}
+
+/// What a Voronoi texture computes, with the inputs only that feature uses.
+#[derive(Clone, Copy)]
+pub enum Feature {
+    F1,
+    F2,
+    SmoothF1 { smoothness: NodeSocket<Float> },
+    DistanceToEdge,
+    NSphereRadius,
+}
+
+impl Feature {
+    fn as_enum(&self) -> ShaderNodeTexVoronoiFeature {
+        match self {
+            Feature::F1 => ShaderNodeTexVoronoiFeature::F1,
+            Feature::F2 => ShaderNodeTexVoronoiFeature::F2,
+            Feature::SmoothF1 { .. } => ShaderNodeTexVoronoiFeature::SmoothF1,
+            Feature::DistanceToEdge => ShaderNodeTexVoronoiFeature::DistanceToEdge,
+            Feature::NSphereRadius => ShaderNodeTexVoronoiFeature::NSphereRadius,
+        }
+    }
+
+    /// F1, F2 and Smooth F1 output a cell (color, position, w); the others only a scalar.
+    fn has_cell_outputs(&self) -> bool {
+        matches!(self, Feature::F1 | Feature::F2 | Feature::SmoothF1 { .. })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct NoiseOutputs {
+    pub fac: NodeSocket<Float>,
+    pub color: NodeSocket<Color>,
+}
+
+/// Outputs of a Voronoi texture; `None` where the dimensions and feature hide the socket.
+#[derive(Clone, Copy)]
+pub struct VoronoiOutputs {
+    pub distance: Option<NodeSocket<Float>>,
+    pub color: Option<NodeSocket<Color>>,
+    pub position: Option<NodeSocket<Vector>>,
+    pub w: Option<NodeSocket<Float>>,
+    pub radius: Option<NodeSocket<Float>>,
+}
+
+/// Noise texture over `dimensions`.
+pub fn noise(
+    dimensions: Dimensions,
+    scale: impl Into<NodeSocket<Float>>,
+    detail: impl Into<NodeSocket<Float>>,
+    roughness: impl Into<NodeSocket<Float>>,
+) -> NoiseOutputs {
+    let mut node = ShaderNodeTexNoise::new()
+        .with_noise_dimensions(dimensions.noise_dimensions())
+        .with_scale(scale.into())
+        .with_detail(detail.into())
+        .with_roughness(roughness.into());
+    if let Some(vector) = dimensions.vector() {
+        node = node.with_vector(vector);
+    }
+    if let Some(w) = dimensions.w() {
+        node = node.with_w(w);
+    }
+
+    NoiseOutputs {
+        fac: node.out_factor(),
+        color: node.out_color(),
+    }
+}
+
+/// Voronoi texture over `dimensions` computing `feature`.
+pub fn voronoi(
+    dimensions: Dimensions,
+    feature: Feature,
+    scale: impl Into<NodeSocket<Float>>,
+) -> VoronoiOutputs {
+    let mut node = ShaderNodeTexVoronoi::new()
+        .with_voronoi_dimensions(dimensions.voronoi_dimensions())
+        .with_feature(feature.as_enum())
+        .with_scale(scale.into());
+    if let Some(vector) = dimensions.vector() {
+        node = node.with_vector(vector);
+    }
+    if let Some(w) = dimensions.w() {
+        node = node.with_w(w);
+    }
+    if let Feature::SmoothF1 { smoothness } = feature {
+        node = node.with_smoothness(smoothness);
+    }
+
+    let cell = feature.has_cell_outputs();
+    VoronoiOutputs {
+        distance: (!matches!(feature, Feature::NSphereRadius)).then(|| node.out_distance()),
+        color: cell.then(|| node.out_color()),
+        position: (cell && dimensions.vector().is_some()).then(|| node.out_position()),
+        w: (cell && dimensions.w().is_some()).then(|| node.out_w()),
+        radius: matches!(feature, Feature::NSphereRadius).then(|| node.out_radius()),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::{self, NodeData, test_utils::GLOBAL_TEST_LOCK};
+
+    fn vector() -> NodeSocket<Vector> {
+        NodeSocket::new_output("coords.outputs[0]")
+    }
+
+    fn wired_pins(node: &NodeData) -> Vec<usize> {
+        let mut pins: Vec<usize> = node.inputs.keys().copied().collect();
+        pins.sort();
+        pins
+    }
+
+    #[test]
+    fn test_noise_per_dimension() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let w = NodeSocket::<Float>::from(0.5);
+        let cases = [
+            (Dimensions::D1 { w }, "\"1D\"", vec![1, 2, 3, 4]),
+            (
+                Dimensions::D2 { vector: vector() },
+                "\"2D\"",
+                vec![0, 2, 3, 4],
+            ),
+            (
+                Dimensions::D3 { vector: vector() },
+                "\"3D\"",
+                vec![0, 2, 3, 4],
+            ),
+            (
+                Dimensions::D4 {
+                    vector: vector(),
+                    w,
+                },
+                "\"4D\"",
+                vec![0, 1, 2, 3, 4],
+            ),
+        ];
+
+        for (dimensions, expected, pins) in cases {
+            context::enter_zone();
+            let out = noise(dimensions, 5.0, 2.0, 0.5);
+            let nodes = context::exit_zone();
+
+            assert_eq!(nodes.len(), 1);
+            let node = &nodes[0];
+            assert_eq!(node.properties["noise_dimensions"], expected);
+            assert_eq!(wired_pins(node), pins, "{}", expected);
+            assert_eq!(
+                out.fac.python_expr(),
+                format!("{}.outputs[\"Factor\"]", node.name)
+            );
+            assert_eq!(
+                out.color.python_expr(),
+                format!("{}.outputs[\"Color\"]", node.name)
+            );
+        }
+    }
+
+    #[test]
+    fn test_voronoi_per_dimension_and_feature() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let w = NodeSocket::<Float>::from(0.5);
+        let smoothness = NodeSocket::<Float>::from(0.25);
+
+        context::enter_zone();
+        let smooth = voronoi(
+            Dimensions::D3 { vector: vector() },
+            Feature::SmoothF1 { smoothness },
+            4.0,
+        );
+        let nodes = context::exit_zone();
+        let node = &nodes[0];
+        assert_eq!(node.properties["voronoi_dimensions"], "\"3D\"");
+        assert_eq!(node.properties["feature"], "\"SMOOTH_F1\"");
+        assert_eq!(
+            wired_pins(node),
+            vec![
+                ShaderNodeTexVoronoi::PIN_VECTOR,
+                ShaderNodeTexVoronoi::PIN_SCALE,
+                ShaderNodeTexVoronoi::PIN_SMOOTHNESS
+            ]
+        );
+        assert!(smooth.distance.is_some() && smooth.color.is_some());
+        assert!(smooth.position.is_some());
+        assert!(smooth.w.is_none() && smooth.radius.is_none());
+
+        context::enter_zone();
+        let line = voronoi(Dimensions::D1 { w }, Feature::F1, 4.0);
+        let nodes = context::exit_zone();
+        assert_eq!(nodes[0].properties["voronoi_dimensions"], "\"1D\"");
+        assert_eq!(
+            wired_pins(&nodes[0]),
+            vec![ShaderNodeTexVoronoi::PIN_W, ShaderNodeTexVoronoi::PIN_SCALE]
+        );
+        assert!(line.position.is_none() && line.w.is_some());
+
+        context::enter_zone();
+        let edge = voronoi(
+            Dimensions::D4 {
+                vector: vector(),
+                w,
+            },
+            Feature::DistanceToEdge,
+            4.0,
+        );
+        let nodes = context::exit_zone();
+        assert_eq!(nodes[0].properties["voronoi_dimensions"], "\"4D\"");
+        assert_eq!(nodes[0].properties["feature"], "\"DISTANCE_TO_EDGE\"");
+        assert_eq!(wired_pins(&nodes[0]), vec![0, 1, 2]);
+        assert!(edge.distance.is_some());
+        assert!(edge.color.is_none() && edge.position.is_none() && edge.w.is_none());
+
+        context::enter_zone();
+        let sphere = voronoi(
+            Dimensions::D2 { vector: vector() },
+            Feature::NSphereRadius,
+            4.0,
+        );
+        let _ = context::exit_zone();
+        assert!(sphere.distance.is_none() && sphere.radius.is_some());
+    }
+}
@@ -1,6 +1,19 @@
-use crate::core::context::{enter_zone, exit_zone};
+use crate::core::context::{
+    ParamTarget, ParamUse, Scope, enter_zone, exit_zone, push_group_call, swap_params,
+    swap_required_imports, take_debug_statements,
+};
+use crate::core::debug::tag_debug_line;
+use crate::core::diagnostics::{
+    DiagnosticKind, TreeDiagnostics, check_budget, check_graph, collect_build_diagnostics,
+    is_output_node, node_refs, output_refs,
+};
+use crate::core::graph::NodeGraph;
+use crate::core::testing::NodeInfo;
 use crate::core::types::{SocketDef, python_string_literal};
+use std::any::Any;
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Write;
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TreeType {
@@ -10,6 +23,17 @@ pub enum TreeType {
     ShaderGroup,
     Compositor,
     CompositorGroup,
+    World,
+}
+
+impl TreeType {
+    /// Whether trees of this type declare their own interface sockets, i.e. are node groups.
+    pub fn supports_interface(&self) -> bool {
+        matches!(
+            self,
+            TreeType::GeometryGroup | TreeType::ShaderGroup | TreeType::CompositorGroup
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -17,6 +41,20 @@ pub struct TreeInput {
     pub name: String,
     pub blender_type: String,
     pub default_expr: Option<String>,
+    pub config: InputConfig,
+}
+
+/// Per-socket interface flags of a group input.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputConfig {
+    /// Only accept single values, not fields (`force_non_field`).
+    pub single_value: bool,
+    /// Do not show the input in the modifier panel.
+    pub hide_in_modifier: bool,
+    /// Hide the value widget of the unlinked socket.
+    pub hide_value: bool,
+    /// Named attribute the modifier binds to this input by default.
+    pub default_attribute_name: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -30,8 +68,57 @@ pub struct NodeTree {
     tree_type: TreeType,
     inputs: Vec<TreeInput>,
     outputs: Vec<TreeOutput>,
+    /// Object the geometry modifier is attached to; `None` means the active object.
+    target_object: Option<String>,
+    phase_hooks: Vec<(Phase, PhaseHook)>,
+    node_budget: Option<usize>,
+    /// Reuse the object's `RamenNodes` modifier (and its input values) instead of replacing it.
+    keep_modifier_settings: bool,
+    /// Emit links right after the nodes they connect instead of in the linking phase.
+    interleave_links: bool,
 }
 
+/// Python dict (tree name -> input name -> socket identifier) filled by group setup scripts.
+const SOCKET_IDS_DICT: &str = "ramen_socket_ids";
+
+/// A built group tree: its script plus what is needed to refer to its interface afterwards.
+#[derive(Debug, Clone)]
+pub struct BuiltGroup {
+    pub name: String,
+    pub script: String,
+    inputs: Vec<String>,
+}
+
+impl BuiltGroup {
+    /// Python expression for the stable identifier (e.g. `Socket_2`) of the input `name`,
+    /// valid anywhere after this group's script ran. `None` if the group has no such input.
+    ///
+    /// Use it where Blender addresses group inputs by identifier, such as modifier values
+    /// (`mod[<expr>] = ...`) or driver data paths.
+    pub fn socket_identifier_lookup_expr(&self, name: &str) -> Option<String> {
+        self.inputs.iter().any(|i| i == name).then(|| {
+            format!(
+                "{}[{}][{}]",
+                SOCKET_IDS_DICT,
+                python_string_literal(&self.name),
+                python_string_literal(name)
+            )
+        })
+    }
+}
+
+/// Points in a tree script where `NodeTree::on_phase` hooks inject code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// After the post creation phase, before the linking phase.
+    PostCreation,
+    /// At the very end of the tree script.
+    PostLinking,
+}
+
+/// Emits Python for a `Phase`, given the nodes collected for the tree.
+pub type PhaseHook = Box<dyn Fn(&Scope) -> String>;
+
 impl NodeTree {
     pub fn new_geometry(name: &str) -> Self {
         Self {
@@ -39,6 +126,11 @@ impl NodeTree {
             tree_type: TreeType::Geometry,
             inputs: vec![],
             outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
         }
     }
 
@@ -48,6 +140,26 @@ impl NodeTree {
             tree_type: TreeType::Shader,
             inputs: vec![],
             outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
+        }
+    }
+
+    /// Shader tree of the scene's world (background/environment lighting).
+    pub fn new_world(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            tree_type: TreeType::World,
+            inputs: vec![],
+            outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
         }
     }
 
@@ -57,6 +169,11 @@ impl NodeTree {
             tree_type: TreeType::GeometryGroup,
             inputs: vec![],
             outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
         }
     }
 
@@ -66,6 +183,11 @@ impl NodeTree {
             tree_type: TreeType::ShaderGroup,
             inputs: vec![],
             outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
         }
     }
 
@@ -75,6 +197,11 @@ impl NodeTree {
             tree_type: TreeType::Compositor,
             inputs: vec![],
             outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
         }
     }
 
@@ -84,20 +211,21 @@ impl NodeTree {
             tree_type: TreeType::CompositorGroup,
             inputs: vec![],
             outputs: vec![],
+            target_object: None,
+            phase_hooks: Vec::new(),
+            node_budget: None,
+            keep_modifier_settings: false,
+            interleave_links: false,
         }
     }
 
     pub fn with_input<S: SocketDef>(mut self, name: &str) -> Self {
-        assert!(
-            self.tree_type == TreeType::GeometryGroup
-                || self.tree_type == TreeType::ShaderGroup
-                || self.tree_type == TreeType::CompositorGroup,
-            "with_input can only be used on Group Node Trees!"
-        );
+        self.assert_interface("with_input");
         self.inputs.push(TreeInput {
             name: name.to_string(),
             blender_type: S::blender_socket_type().to_string(),
             default_expr: None,
+            config: InputConfig::default(),
         });
         self
     }
@@ -107,12 +235,7 @@ impl NodeTree {
         name: &str,
         default_val: impl Into<crate::core::types::NodeSocket<S>>,
     ) -> Self {
-        assert!(
-            self.tree_type == TreeType::GeometryGroup
-                || self.tree_type == TreeType::ShaderGroup
-                || self.tree_type == TreeType::CompositorGroup,
-            "with_input_default can only be used on Group Node Trees!"
-        );
+        self.assert_interface("with_input_default");
         let socket = default_val.into();
         assert!(
             socket.is_literal,
@@ -122,17 +245,24 @@ impl NodeTree {
             name: name.to_string(),
             blender_type: S::blender_socket_type().to_string(),
             default_expr: Some(socket.python_expr()),
+            config: InputConfig::default(),
         });
         self
     }
 
+    /// Sets the interface flags of the most recently added input.
+    pub fn with_input_config(mut self, config: InputConfig) -> Self {
+        self.assert_interface("with_input_config");
+        let input = self
+            .inputs
+            .last_mut()
+            .expect("with_input_config must follow with_input or with_input_default");
+        input.config = config;
+        self
+    }
+
     pub fn with_output<S: SocketDef>(mut self, name: &str) -> Self {
-        assert!(
-            self.tree_type == TreeType::GeometryGroup
-                || self.tree_type == TreeType::ShaderGroup
-                || self.tree_type == TreeType::CompositorGroup,
-            "with_output can only be used on Group Node Trees!"
-        );
+        self.assert_interface("with_output");
         self.outputs.push(TreeOutput {
             name: name.to_string(),
             blender_type: S::blender_socket_type().to_string(),
@@ -140,6 +270,67 @@ impl NodeTree {
         self
     }
 
+    fn assert_interface(&self, method: &str) {
+        assert!(
+            self.tree_type.supports_interface(),
+            "{} can only be used on group node trees (new_*_group), but '{}' is a {:?} tree",
+            method,
+            self.name,
+            self.tree_type
+        );
+    }
+
+    /// Attaches the modifier of a geometry tree to the object named `object_name`
+    /// instead of the active object.
+    pub fn at_object(mut self, object_name: &str) -> Self {
+        assert!(
+            self.tree_type == TreeType::Geometry,
+            "at_object can only be used on Geometry Node Trees!"
+        );
+        self.target_object = Some(object_name.to_string());
+        self
+    }
+
+    /// With `true`, an existing `RamenNodes` modifier is kept and pointed at the rebuilt tree,
+    /// so values set on its inputs in Blender survive a resend. By default it is replaced.
+    pub fn keep_modifier_settings(mut self, keep: bool) -> Self {
+        assert!(
+            self.tree_type == TreeType::Geometry,
+            "keep_modifier_settings can only be used on Geometry Node Trees!"
+        );
+        self.keep_modifier_settings = keep;
+        self
+    }
+
+    /// With `true`, each link is emitted in the creation phase as soon as both nodes it
+    /// connects exist, so a node's creation is directly followed by its links. Links that touch
+    /// nodes completed in the post creation phase (zones, dynamic items) stay in the linking
+    /// phase. The resulting tree is the same either way; by default all links come last.
+    pub fn interleave_links(mut self, interleave: bool) -> Self {
+        self.interleave_links = interleave;
+        self
+    }
+
+    /// Limits the number of nodes this tree may create. Exceeding it is reported as an error
+    /// by `build_checked` and as a printed warning by `build`.
+    pub fn with_node_budget(mut self, budget: usize) -> Self {
+        self.node_budget = Some(budget);
+        self
+    }
+
+    /// Registers `hook` to inject code at `phase` of this tree's script.
+    /// Hooks of the same phase run in registration order.
+    pub fn on_phase(mut self, phase: Phase, hook: impl Fn(&Scope) -> String + 'static) -> Self {
+        self.phase_hooks.push((phase, Box::new(hook)));
+        self
+    }
+
+    fn emit_phase_hooks(&self, phase: Phase, my_nodes: &Scope, code: &mut String) {
+        for (_, hook) in self.phase_hooks.iter().filter(|(p, _)| *p == phase) {
+            code.push_str(&hook(my_nodes));
+        }
+    }
+
     fn setup_shader(&self) -> String {
         let safe_name = python_string_literal(&self.name);
         format!(
@@ -156,8 +347,53 @@ tree.nodes.clear()
         )
     }
 
+    fn setup_world(&self) -> String {
+        let safe_name = python_string_literal(&self.name);
+        format!(
+            r#"
+# --- Setup World: {name} ---
+scene = bpy.context.scene
+world = bpy.data.worlds.get({safe_name})
+if not world:
+    world = bpy.data.worlds.new(name={safe_name})
+scene.world = world
+world.use_nodes = True
+tree = scene.world.node_tree
+tree.nodes.clear()
+"#,
+            name = self.name,
+            safe_name = safe_name
+        )
+    }
+
     fn setup_geometry(&self) -> String {
         let safe_name = python_string_literal(&self.name);
+        let select_object = match &self.target_object {
+            Some(object_name) => format!(
+                r#"obj = bpy.data.objects.get({safe_object})
+if not obj:
+    raise RuntimeError("Object not found, cannot attach the GeoNodes modifier: " + {safe_object})"#,
+                safe_object = python_string_literal(object_name)
+            ),
+            None => r#"obj = bpy.context.object
+if not obj:
+    raise RuntimeError("No active object in scene; please select an object to attach the GeoNodes modifier.")"#
+                .to_string(),
+        };
+        let attach_modifier = if self.keep_modifier_settings {
+            r#"mod = obj.modifiers.get(mod_name)
+if mod and mod.type != 'NODES':
+    obj.modifiers.remove(mod)
+    mod = None
+if not mod:
+    mod = obj.modifiers.new(name=mod_name, type='NODES')"#
+        } else {
+            r#"existing_mod = obj.modifiers.get(mod_name)
+if existing_mod:
+    obj.modifiers.remove(existing_mod)
+
+mod = obj.modifiers.new(name=mod_name, type='NODES')"#
+        };
         format!(
             r#"
 # --- Setup GeoNodes: {name} ---
@@ -166,23 +402,18 @@ if tree_name in bpy.data.node_groups:
     bpy.data.node_groups.remove(bpy.data.node_groups[tree_name])
 group = bpy.data.node_groups.new(name=tree_name, type='GeometryNodeTree')
 
-obj = bpy.context.object
-if not obj:
-    raise RuntimeError("No active object in scene; please select an object to attach the GeoNodes modifier.")
+{select_object}
 
 mod_name = 'RamenNodes'
-existing_mod = obj.modifiers.get(mod_name)
-if existing_mod:
-    obj.modifiers.remove(existing_mod)
-
-mod = obj.modifiers.new(name=mod_name, type='NODES')
-mod.node_group = group
+{attach_modifier}
 tree = group
 
 tree.interface.new_socket('Geometry', in_out='OUTPUT', socket_type='NodeSocketGeometry')
 "#,
             name = self.name,
-            safe_name = safe_name
+            safe_name = safe_name,
+            select_object = select_object,
+            attach_modifier = attach_modifier
         )
     }
 
@@ -224,18 +455,57 @@ tree.interface.new_socket('Alpha', in_out='OUTPUT', socket_type='NodeSocketFloat
         )
     }
 
-    fn append_sockets(&self, code: &mut String) {
-        for input in &self.inputs {
+    /// Declares the interface, `params` being the inputs added for the `Param`s the tree read.
+    /// Param defaults are also set on the modifier of geometry trees. Only params: declared
+    /// inputs are limited to groups (`assert_interface`), which have no modifier.
+    fn append_sockets(&self, code: &mut String, params: &[TreeInput]) {
+        if !self.inputs.is_empty() || !params.is_empty() {
+            let _ = writeln!(
+                code,
+                "sock_ids = globals().setdefault('{}', {{}}).setdefault(tree.name, {{}})",
+                SOCKET_IDS_DICT
+            );
+        }
+        for (position, input) in self.inputs.iter().chain(params).enumerate() {
+            let is_param = position >= self.inputs.len();
             let safe_name = python_string_literal(&input.name);
             let _ = writeln!(
                 code,
                 "sock = tree.interface.new_socket({}, in_out='INPUT', socket_type='{}')",
                 safe_name, input.blender_type
             );
+            let _ = writeln!(code, "sock_ids[{}] = sock.identifier", safe_name);
 
             if let Some(expr) = &input.default_expr {
                 let _ = writeln!(code, "sock.default_value = {}", expr);
             }
+            if let (TreeType::Geometry, Some(expr), true) =
+                (self.tree_type, &input.default_expr, is_param)
+            {
+                if self.keep_modifier_settings {
+                    let _ = writeln!(code, "if sock.identifier not in mod.keys():");
+                    let _ = writeln!(code, "    mod[sock.identifier] = {}", expr);
+                } else {
+                    let _ = writeln!(code, "mod[sock.identifier] = {}", expr);
+                }
+            }
+            let config = &input.config;
+            if config.single_value {
+                let _ = writeln!(code, "sock.force_non_field = True");
+            }
+            if config.hide_in_modifier {
+                let _ = writeln!(code, "sock.hide_in_modifier = True");
+            }
+            if config.hide_value {
+                let _ = writeln!(code, "sock.hide_value = True");
+            }
+            if let Some(attribute) = &config.default_attribute_name {
+                let _ = writeln!(
+                    code,
+                    "sock.default_attribute_name = {}",
+                    python_string_literal(attribute)
+                );
+            }
         }
         for output in &self.outputs {
             let safe_name = python_string_literal(&output.name);
@@ -247,9 +517,10 @@ tree.interface.new_socket('Alpha', in_out='OUTPUT', socket_type='NodeSocketFloat
         }
     }
 
-    fn generate_setup_script(&self) -> String {
+    fn generate_setup_script(&self, params: &[TreeInput]) -> String {
         let mut code = match self.tree_type {
             TreeType::Shader => self.setup_shader(),
+            TreeType::World => self.setup_world(),
             TreeType::Geometry => self.setup_geometry(),
             TreeType::GeometryGroup => self.setup_group("GeoNodes Group", "GeometryNodeTree"),
             TreeType::ShaderGroup => self.setup_group("Shader Group", "ShaderNodeTree"),
@@ -257,87 +528,539 @@ tree.interface.new_socket('Alpha', in_out='OUTPUT', socket_type='NodeSocketFloat
             TreeType::CompositorGroup => self.setup_group("Compositor Group", "CompositorNodeTree"),
         };
 
-        self.append_sockets(&mut code);
+        self.append_sockets(&mut code, params);
+        if self.tree_type == TreeType::Geometry {
+            // Only once the interface exists: Blender rebuilds the modifier's input values from
+            // it, keeping those whose sockets still match (see `keep_modifier_settings`).
+            code.push_str("mod.node_group = group\n");
+        }
         code
     }
 
+    /// Runs `body` in a fresh scope and emits the Python script for this tree.
+    ///
+    /// The script is emitted in three phases, each for every node before moving on:
+    /// 1. **Creation**: `tree.nodes.new(...)`, properties and literal default values.
+    /// 2. **Post creation**: `NodeData::post_creation_script`, e.g. zone pairing
+    ///    (`pair_with_output`) and dynamic item registration (`repeat_items.new`).
+    ///    This needs every node from phase 1 to exist.
+    /// 3. **Linking**: `NodeData::links_script`, including `custom_links_script`.
+    ///    Links to dynamic sockets rely on the items registered in phase 2.
+    ///
+    /// `on_phase` hooks are emitted after phase 2 (`Phase::PostCreation`) and after phase 3
+    /// (`Phase::PostLinking`). With `interleave_links`, most links move into phase 1.
+    ///
+    /// `Param`s read by `body` add their inputs to the interface; see `core::param`.
+    ///
+    /// Modules the script needs besides `bpy` (e.g. `math` for `NodeSocket::<Float>::pi()`) are
+    /// imported at its top, so it runs on its own after `generate_script_header`.
+    /// `BlenderProject` moves these imports into its header.
+    ///
+    /// No validation is done here apart from the node budget, the group interface (output
+    /// indices and input names must be declared) and build diagnostics (e.g. lossy float
+    /// literals, input indices a node doesn't have), which are printed;
+    /// use `build_checked` to get diagnostics.
     pub fn build<F>(&self, body: F) -> String
     where
         F: FnOnce(),
     {
-        struct PanicGuard {
-            is_panicking: bool,
+        let (my_nodes, params, imports) = self.collect(body);
+        self.emit_reported(&my_nodes, &params, &imports)
+    }
+
+    /// `emit_script`, printing the budget and interface checks and build warnings.
+    fn emit_reported(
+        &self,
+        my_nodes: &Scope,
+        params: &[TreeInput],
+        imports: &BTreeSet<&'static str>,
+    ) -> String {
+        let mut diagnostics = TreeDiagnostics::new(&self.name);
+        if let Some(budget) = self.node_budget {
+            check_budget(my_nodes, budget, &mut diagnostics);
         }
+        self.check_interface(my_nodes, params, &mut diagnostics);
+        collect_build_diagnostics(&mut diagnostics);
+        if !diagnostics.is_empty() {
+            eprintln!("⚠️ {}", diagnostics);
+        }
+        self.emit_script(my_nodes, params, imports)
+    }
 
-        impl Drop for PanicGuard {
+    /// Like `build`, but runs every validation pass first.
+    ///
+    /// Returns the script if no pass reported an error (warnings are dropped),
+    /// otherwise all diagnostics of the tree at once.
+    pub fn build_checked<F>(&self, body: F) -> Result<String, TreeDiagnostics>
+    where
+        F: FnOnce(),
+    {
+        let (script, diagnostics) = self.build_with_diagnostics(body);
+        if diagnostics.has_errors() {
+            Err(diagnostics)
+        } else {
+            Ok(script)
+        }
+    }
+
+    /// Builds the script and collects diagnostics without failing on either.
+    pub(crate) fn build_with_diagnostics<F>(&self, body: F) -> (String, TreeDiagnostics)
+    where
+        F: FnOnce(),
+    {
+        let (my_nodes, params, imports) = self.collect(body);
+        let diagnostics = self.diagnose(&my_nodes, &params);
+        (self.emit_script(&my_nodes, &params, &imports), diagnostics)
+    }
+
+    /// Runs `body` like `build`, but returns the collected nodes and links as a `NodeGraph`
+    /// instead of a script.
+    pub fn build_to_graph<F>(&self, body: F) -> NodeGraph
+    where
+        F: FnOnce(),
+    {
+        let (my_nodes, _, _) = self.collect(body);
+        NodeGraph::from_nodes(&self.name, &my_nodes)
+    }
+
+    /// Runs `body` like `build` and returns the script together with a public view of the
+    /// collected nodes, for asserting on generated graphs (see `core::testing`).
+    pub fn build_inspect<F>(&self, body: F) -> (String, Vec<NodeInfo>)
+    where
+        F: FnOnce(),
+    {
+        let (my_nodes, params, imports) = self.collect(body);
+        (
+            self.emit_script(&my_nodes, &params, &imports),
+            NodeInfo::from_nodes(&my_nodes),
+        )
+    }
+
+    /// Builds a group tree like `build`, keeping its interface for later lookups.
+    pub fn build_group<F>(&self, body: F) -> BuiltGroup
+    where
+        F: FnOnce(),
+    {
+        self.assert_interface("build_group");
+        let (my_nodes, params, imports) = self.collect(body);
+        BuiltGroup {
+            name: self.name.clone(),
+            script: self.emit_reported(&my_nodes, &params, &imports),
+            inputs: self
+                .inputs
+                .iter()
+                .chain(&params)
+                .map(|i| i.name.clone())
+                .collect(),
+        }
+    }
+
+    fn param_target(&self) -> ParamTarget {
+        match self.tree_type {
+            TreeType::Shader => ParamTarget::MaterialDriver,
+            TreeType::World | TreeType::Compositor => ParamTarget::Literal,
+            _ => ParamTarget::GroupInput,
+        }
+    }
+
+    /// `collect_nodes` with `Param`s read for this tree; also returns the inputs they need
+    /// (params named like a declared input read that input instead) and the modules `body`
+    /// required (`context::require_import`).
+    ///
+    /// A panic in `body` is raised again with the tree's name, type and the number of nodes
+    /// created before it, e.g. `tree 'Bulb' (Geometry) panicked after 12 node(s): ...`.
+    fn collect<F>(&self, body: F) -> (Scope, Vec<TreeInput>, BTreeSet<&'static str>)
+    where
+        F: FnOnce(),
+    {
+        type BuildState = ((Option<ParamTarget>, Vec<ParamUse>), BTreeSet<&'static str>);
+
+        /// Restores the params and imports of an enclosing build, also when `body` panics.
+        struct BuildGuard(Option<BuildState>);
+
+        impl Drop for BuildGuard {
             fn drop(&mut self) {
-                if self.is_panicking {
-                    let _ = exit_zone();
+                if let Some(((target, used), imports)) = self.0.take() {
+                    let _ = swap_params(target, used);
+                    let _ = swap_required_imports(imports);
                 }
             }
         }
 
+        let mut guard = BuildGuard(Some((
+            swap_params(Some(self.param_target()), Vec::new()),
+            swap_required_imports(BTreeSet::new()),
+        )));
+        let my_nodes = match Self::try_collect_nodes(body) {
+            Ok(nodes) => nodes,
+            Err(panic) => panic!(
+                "tree '{}' ({:?}) panicked after {} node(s): {}",
+                self.name,
+                self.tree_type,
+                panic.nodes_created,
+                panic_message(panic.payload.as_ref())
+            ),
+        };
+        let ((target, used), imports) = guard.0.take().expect("build state is restored once");
+        let (_, params) = swap_params(target, used);
+        let imports = swap_required_imports(imports);
+
+        let params = if self.param_target() == ParamTarget::GroupInput {
+            params
+                .into_iter()
+                .filter(|p| !self.inputs.iter().any(|i| i.name == p.name))
+                .map(|p| TreeInput {
+                    name: p.name,
+                    blender_type: p.blender_type,
+                    default_expr: Some(p.default_expr),
+                    config: InputConfig::default(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        (my_nodes, params, imports)
+    }
+
+    pub(crate) fn collect_nodes<F>(body: F) -> Scope
+    where
+        F: FnOnce(),
+    {
+        Self::try_collect_nodes(body).unwrap_or_else(|panic| resume_unwind(panic.payload))
+    }
+
+    /// Like `collect_nodes`, but returns a panic of `body` instead of unwinding. The scope of
+    /// `body` is exited either way.
+    pub(crate) fn try_collect_nodes<F>(body: F) -> Result<Scope, BodyPanic>
+    where
+        F: FnOnce(),
+    {
+        // Left over from a capture that was never emitted.
+        let _ = take_debug_statements();
         enter_zone();
-        let mut guard = PanicGuard { is_panicking: true };
-        body();
-        guard.is_panicking = false;
-        let my_nodes = exit_zone();
+        let result = catch_unwind(AssertUnwindSafe(body));
+        let nodes = exit_zone();
+        match result {
+            Ok(()) => Ok(nodes),
+            Err(payload) => Err(BodyPanic {
+                payload,
+                nodes_created: nodes.len(),
+            }),
+        }
+    }
 
-        let mut code = self.generate_setup_script();
+    fn emit_script(
+        &self,
+        my_nodes: &Scope,
+        params: &[TreeInput],
+        imports: &BTreeSet<&'static str>,
+    ) -> String {
+        let mut code = import_lines(imports.iter().copied());
+        code.push_str(&self.generate_setup_script(params));
 
         code.push_str("\n# --- Node Creation Phase ---\n");
-        for node in &my_nodes {
-            code.push_str(&node.creation_script());
-        }
+        let late_links = if self.interleave_links {
+            Some(emit_interleaved(my_nodes, &mut code))
+        } else {
+            for node in my_nodes {
+                code.push_str(&node.creation_script());
+            }
+            None
+        };
 
         // For calling custom groups, etc
         code.push_str("\n# --- Node Post Creation Phase ---\n");
-        for node in &my_nodes {
+        for node in my_nodes {
             if !node.post_creation_script.is_empty() {
                 code.push_str(&node.post_creation_script);
                 code.push('\n');
             }
         }
+        for statement in take_debug_statements() {
+            code.push_str(&tag_debug_line(&statement));
+        }
+
+        self.emit_phase_hooks(Phase::PostCreation, my_nodes, &mut code);
 
         code.push_str("\n# --- Node Linking Phase ---\n");
-        for node in &my_nodes {
-            code.push_str(&node.links_script());
+        match late_links {
+            Some(links) => code.push_str(&links),
+            None => {
+                for node in my_nodes {
+                    code.push_str(&node.links_script());
+                }
+            }
         }
 
+        self.emit_phase_hooks(Phase::PostLinking, my_nodes, &mut code);
+
         code
     }
+
+    /// Names of the sockets `NodeGroupOutput` can feed in this tree, if it has a group output
+    /// at all.
+    fn declared_outputs(&self) -> Option<Vec<String>> {
+        match self.tree_type {
+            TreeType::Geometry => Some(vec!["Geometry".to_string()]),
+            TreeType::Compositor => Some(vec!["Image".to_string(), "Alpha".to_string()]),
+            _ if self.tree_type.supports_interface() => {
+                Some(self.outputs.iter().map(|o| o.name.clone()).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Names of the sockets `NodeGroupInput` provides in this tree, if it has an interface.
+    fn declared_inputs(&self, params: &[TreeInput]) -> Option<Vec<String>> {
+        (self.tree_type == TreeType::Geometry || self.tree_type.supports_interface()).then(|| {
+            self.inputs
+                .iter()
+                .chain(params)
+                .map(|i| i.name.clone())
+                .collect()
+        })
+    }
+
+    /// Checks that group output indices and group input reads are within the declared
+    /// interface.
+    fn check_interface(
+        &self,
+        my_nodes: &Scope,
+        params: &[TreeInput],
+        diagnostics: &mut TreeDiagnostics,
+    ) {
+        if let Some(outputs) = self.declared_outputs() {
+            for node in my_nodes.iter().filter(|n| n.bl_idname == "NodeGroupOutput") {
+                let mut indices: Vec<usize> = node.inputs.keys().copied().collect();
+                indices.sort();
+                for index in indices.into_iter().filter(|&i| i >= outputs.len()) {
+                    diagnostics.error(
+                        DiagnosticKind::InterfaceMismatch,
+                        &node.name,
+                        format!(
+                            "sets group output {} but the tree declares {} output(s): {}",
+                            index,
+                            outputs.len(),
+                            interface_list(&outputs)
+                        ),
+                    );
+                }
+            }
+        }
+
+        if let Some(inputs) = self.declared_inputs(params) {
+            let group_inputs: Vec<&str> = my_nodes
+                .iter()
+                .filter(|n| n.bl_idname == "NodeGroupInput")
+                .map(|n| n.name.as_str())
+                .collect();
+            let declared: Vec<String> = inputs.iter().map(|i| python_string_literal(i)).collect();
+
+            for node in my_nodes {
+                for r in node_refs(node)
+                    .into_iter()
+                    .filter(|r| group_inputs.contains(&r.node.as_str()))
+                {
+                    let known = match r.key.parse::<usize>() {
+                        Ok(index) => index < declared.len(),
+                        Err(_) => declared.contains(&r.key),
+                    };
+                    if !known {
+                        diagnostics.error(
+                            DiagnosticKind::InterfaceMismatch,
+                            &node.name,
+                            format!(
+                                "reads group input {} which the tree does not declare; inputs: {}",
+                                r.key,
+                                interface_list(&inputs)
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn diagnose(&self, my_nodes: &Scope, params: &[TreeInput]) -> TreeDiagnostics {
+        let mut diagnostics = TreeDiagnostics::new(&self.name);
+        collect_build_diagnostics(&mut diagnostics);
+        check_graph(my_nodes, &mut diagnostics);
+        if let Some(budget) = self.node_budget {
+            check_budget(my_nodes, budget, &mut diagnostics);
+        }
+
+        if !my_nodes.iter().any(is_output_node) {
+            diagnostics.warning(
+                DiagnosticKind::MissingOutput,
+                "",
+                "tree has no output node, so nothing it builds is used",
+            );
+        }
+
+        self.check_interface(my_nodes, params, &mut diagnostics);
+        diagnostics
+    }
+}
+
+/// A panic caught while collecting the nodes of a tree.
+pub(crate) struct BodyPanic {
+    pub payload: Box<dyn Any + Send>,
+    pub nodes_created: usize,
+}
+
+/// Text of a panic payload (`panic!` messages are `&str` or `String`).
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// `names` as a quoted, comma separated list for interface errors.
+fn interface_list(names: &[String]) -> String {
+    if names.is_empty() {
+        return "none".to_string();
+    }
+    names
+        .iter()
+        .map(|n| python_string_literal(n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Emits the creation phase with each input link right after the last node it connects,
+/// returning the links that have to wait for the post creation phase.
+fn emit_interleaved(my_nodes: &Scope, code: &mut String) -> String {
+    // Nodes with post creation code, or whose sockets it adds (zone outputs), are only
+    // complete after that phase.
+    let post_scripts: String = my_nodes
+        .iter()
+        .map(|node| node.post_creation_script.as_str())
+        .collect();
+    let late: HashSet<&str> = my_nodes
+        .iter()
+        .filter(|node| {
+            !node.post_creation_script.is_empty()
+                || !node.custom_links_script.is_empty()
+                || post_scripts.contains(node.name.as_str())
+        })
+        .map(|node| node.name.as_str())
+        .collect();
+    let in_scope: HashSet<&str> = my_nodes.iter().map(|node| node.name.as_str()).collect();
+
+    let mut created = HashSet::new();
+    let mut pending: Vec<(Vec<String>, String)> = Vec::new();
+    let mut late_links = String::new();
+    for node in my_nodes {
+        code.push_str(&node.creation_script());
+        created.insert(node.name.clone());
+
+        if late.contains(node.name.as_str()) {
+            late_links.push_str(&node.links_script());
+        } else {
+            for (expr, line) in node.input_links() {
+                let sources: Vec<String> = output_refs(expr)
+                    .into_iter()
+                    .map(|r| r.node)
+                    .filter(|source| in_scope.contains(source.as_str()))
+                    .collect();
+                if sources.iter().any(|source| late.contains(source.as_str())) {
+                    late_links.push_str(&line);
+                } else {
+                    pending.push((sources, line));
+                }
+            }
+        }
+
+        pending.retain(|(sources, line)| {
+            let ready = sources.iter().all(|source| created.contains(source));
+            if ready {
+                code.push_str(line);
+            }
+            !ready
+        });
+    }
+    late_links
 }
 
 pub fn generate_script_header() -> String {
     "import bpy\n".to_string()
 }
 
+/// `generate_script_header` plus an import line per extra module (see `context::require_import`).
+pub fn generate_script_header_with(imports: &BTreeSet<String>) -> String {
+    let mut header = generate_script_header();
+    header.push_str(&import_lines(
+        imports.iter().map(String::as_str).filter(|m| *m != "bpy"),
+    ));
+    header
+}
+
+fn import_lines<'a>(modules: impl IntoIterator<Item = &'a str>) -> String {
+    modules
+        .into_iter()
+        .map(|module| format!("import {}\n", module))
+        .collect()
+}
+
 /// call and instantiate geometry node groups
+///
+/// In a `BlenderProject`, `group_name` has to be one of its items by the time it is built
+/// (`try_build_all` reports an `UnknownGroup` error otherwise), in whatever order they were added.
 pub fn call_geometry_group(group_name: &str) -> crate::core::nodes::GeometryNodeGroup {
-    let node = crate::core::nodes::GeometryNodeGroup::new();
-    crate::core::context::update_property(
-        &node.name,
-        "node_tree",
-        format!(
-            "bpy.data.node_groups[{}]",
-            python_string_literal(group_name)
-        ),
-    );
+    let node = call_external_group(group_name);
+    push_group_call(&node.name, group_name);
     node
 }
 
 /// call and instantiate shader node groups
 pub fn call_shader_group(group_name: &str) -> crate::core::nodes::ShaderNodeGroup {
+    let node = call_external_shader_group(group_name);
+    push_group_call(&node.name, group_name);
+    node
+}
+
+/// call and instantiate compositor node groups
+pub fn call_compositor_group(group_name: &str) -> crate::core::nodes::CompositorNodeGroup {
+    let node = call_external_compositor_group(group_name);
+    push_group_call(&node.name, group_name);
+    node
+}
+
+/// Like `call_geometry_group`, for a group that is not built by the current project
+/// (e.g. one appended from an asset library), so it is not checked against the project.
+pub fn call_external_group(group_name: &str) -> crate::core::nodes::GeometryNodeGroup {
+    let node = crate::core::nodes::GeometryNodeGroup::new();
+    set_group_tree(&node.name, group_name);
+    node
+}
+
+/// Shader counterpart of `call_external_group`.
+pub fn call_external_shader_group(group_name: &str) -> crate::core::nodes::ShaderNodeGroup {
     let node = crate::core::nodes::ShaderNodeGroup::new();
+    set_group_tree(&node.name, group_name);
+    node
+}
+
+/// Compositor counterpart of `call_external_group`.
+pub fn call_external_compositor_group(group_name: &str) -> crate::core::nodes::CompositorNodeGroup {
+    let node = crate::core::nodes::CompositorNodeGroup::new();
+    set_group_tree(&node.name, group_name);
+    node
+}
+
+fn set_group_tree(node_name: &str, group_name: &str) {
     crate::core::context::update_property(
-        &node.name,
+        node_name,
         "node_tree",
         format!(
             "bpy.data.node_groups[{}]",
             python_string_literal(group_name)
         ),
     );
-    node
 }
 
 // ---------------------------------------------------------
@@ -346,7 +1069,11 @@ pub fn call_shader_group(group_name: &str) -> crate::core::nodes::ShaderNodeGrou
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::types::{Float, Geo, Object};
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::nodes::{NodeGroupOutput, ShaderNodeOutputWorld};
+    use crate::core::snapshot::normalize_script;
+    use crate::core::types::{Float, Geo, NodeGroupInputExt, NodeSocket, Object};
+    use crate::core::zone::repeat_zone;
 
     #[test]
     fn test_tree_io_definitions() {
@@ -380,7 +1107,7 @@ mod tests {
             .with_output::<Geo>("Geometry");
 
         let mut code = String::new();
-        tree.append_sockets(&mut code);
+        tree.append_sockets(&mut code, &[]);
 
         assert!(
             code.contains("sock = tree.interface.new_socket(\"Threshold\", in_out='INPUT', socket_type='NodeSocketFloat')"),
@@ -390,10 +1117,576 @@ mod tests {
             code.contains("sock.default_value = 0.7500"),
             "Default value assignment script is missing or incorrect."
         );
+        assert!(code.contains(
+            "sock_ids = globals().setdefault('ramen_socket_ids', {}).setdefault(tree.name, {})"
+        ));
+        assert!(code.contains("sock_ids[\"Threshold\"] = sock.identifier"));
 
         assert!(
             code.contains("tree.interface.new_socket(\"Geometry\", in_out='OUTPUT', socket_type='NodeSocketGeometry')"),
             "Output socket creation script is missing or incorrect."
         );
+        assert!(!code.contains("force_non_field"));
+        assert!(!code.contains("hide_in_modifier"));
+    }
+
+    #[test]
+    fn test_append_sockets_input_config() {
+        let tree = NodeTree::new_geometry_group("ConfigGroup")
+            .with_input::<Float>("Seed")
+            .with_input_config(InputConfig {
+                single_value: true,
+                hide_in_modifier: true,
+                hide_value: true,
+                ..InputConfig::default()
+            })
+            .with_input::<Float>("Weight")
+            .with_input_config(InputConfig {
+                default_attribute_name: Some("weight".to_string()),
+                ..InputConfig::default()
+            });
+
+        let mut code = String::new();
+        tree.append_sockets(&mut code, &[]);
+
+        let weight = code.find("new_socket(\"Weight\"").unwrap();
+        let (seed_part, weight_part) = code.split_at(weight);
+        assert!(seed_part.contains("sock.force_non_field = True\n"));
+        assert!(seed_part.contains("sock.hide_in_modifier = True\n"));
+        assert!(seed_part.contains("sock.hide_value = True\n"));
+        assert!(!seed_part.contains("default_attribute_name"));
+        assert!(weight_part.contains("sock.default_attribute_name = \"weight\"\n"));
+        assert!(!weight_part.contains("force_non_field"));
+    }
+
+    #[test]
+    fn test_repeat_zone_phase_ordering() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let script = NodeTree::new_geometry("PhaseOrder").build(|| {
+            let geo = NodeSocket::<Geo>::new_output("source_geo.outputs[0]");
+            let offset = NodeSocket::<Float>::from(0.5);
+            let (out_geo, _) = repeat_zone(3, (geo, offset), |(g, f)| (g, f + 1.0));
+            NodeGroupOutput::new().set_input(0, out_geo);
+        });
+
+        let post_banner = script.find("# --- Node Post Creation Phase ---").unwrap();
+        let link_banner = script.find("# --- Node Linking Phase ---").unwrap();
+
+        let pair = script.find("pair_with_output").unwrap();
+        assert_eq!(
+            script.matches("pair_with_output").count(),
+            1,
+            "pairing must be emitted exactly once"
+        );
+        assert!(post_banner < pair && pair < link_banner);
+
+        let item_news: Vec<usize> = script
+            .match_indices("repeat_items.new")
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(item_news.len(), 2);
+        assert!(item_news.iter().all(|&i| pair < i && i < link_banner));
+
+        let first_link = script.find("tree.links.new").unwrap();
+        assert!(link_banner < first_link);
+        assert!(item_news.iter().all(|&i| i < first_link));
+    }
+
+    #[test]
+    fn test_interleaved_links_match_phased_script() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let body = || {
+            let geo = NodeSocket::<Geo>::new_output("source_geo.outputs[0]");
+            let offset = NodeSocket::<Float>::new_output("source_value.outputs[0]");
+            let scaled = offset * 2.0 + 1.0;
+            let (out_geo, _) = repeat_zone(3, (geo, scaled), |(g, f)| (g, f + 1.0));
+            NodeGroupOutput::new().set_input(0, out_geo);
+        };
+        let tree = NodeTree::new_geometry("Interleaved");
+        let phased = tree.build(body);
+        let interleaved = NodeTree::new_geometry("Interleaved")
+            .interleave_links(true)
+            .build(body);
+
+        let sorted_lines = |script: &str| {
+            let mut lines: Vec<String> = normalize_script(script)
+                .lines()
+                .map(str::to_string)
+                .collect();
+            lines.sort();
+            lines
+        };
+        assert_eq!(sorted_lines(&phased), sorted_lines(&interleaved));
+
+        let post_banner = interleaved
+            .find("# --- Node Post Creation Phase ---")
+            .unwrap();
+        let link_banner = interleaved.find("# --- Node Linking Phase ---").unwrap();
+        let created_at = |node: &str| {
+            interleaved
+                .find(&format!("{} = tree.nodes.new(", node))
+                .unwrap()
+        };
+        let links: Vec<(usize, &str)> = interleaved.match_indices("tree.links.new(").collect();
+        let early: Vec<_> = links.iter().filter(|(i, _)| *i < post_banner).collect();
+        assert!(!early.is_empty());
+        for (i, _) in early {
+            let line = interleaved[*i..].lines().next().unwrap();
+            for r in output_refs(line).iter().filter(|r| r.node.contains("Node")) {
+                assert!(created_at(&r.node) < *i, "{}", line);
+            }
+            let target = line.rsplit(", ").next().unwrap().split('.').next().unwrap();
+            assert!(created_at(target) < *i, "{}", line);
+            assert!(!line.contains("Repeat"), "zone links wait: {}", line);
+        }
+        // Zone links need the items registered in the post creation phase.
+        assert!(
+            links
+                .iter()
+                .filter(|(i, _)| *i > link_banner)
+                .any(|(i, _)| interleaved[*i..].lines().next().unwrap().contains("Repeat"))
+        );
+    }
+
+    #[test]
+    fn test_group_sockets_declare_and_read() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        ramen_macros::group_sockets!(StepIn {
+            x: Float = "X",
+            target: Object = "Target",
+        });
+
+        assert_eq!(StepIn::X, 0);
+        assert_eq!(StepIn::TARGET, 1);
+        assert_eq!(StepIn::NAMES, ["X", "Target"]);
+
+        let tree = StepIn::declare(NodeTree::new_geometry_group("Step"));
+        assert_eq!(tree.inputs.len(), 2);
+        assert_eq!(tree.inputs[0].name, "X");
+        assert_eq!(tree.inputs[0].blender_type, "NodeSocketFloat");
+        assert_eq!(tree.inputs[1].name, "Target");
+        assert_eq!(tree.inputs[1].blender_type, "NodeSocketObject");
+
+        crate::core::context::enter_zone();
+        let group_in = crate::core::nodes::NodeGroupInput::new();
+        let read = StepIn::read(&group_in);
+        let _ = crate::core::context::exit_zone();
+
+        assert_eq!(
+            read.x.python_expr(),
+            format!("{}.outputs[\"X\"]", group_in.name)
+        );
+        assert_eq!(
+            read.target.python_expr(),
+            format!("{}.outputs[\"Target\"]", group_in.name)
+        );
+    }
+
+    #[test]
+    fn test_build_checked_collects_all_errors() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let result = NodeTree::new_geometry_group("Broken")
+            .with_input::<Float>("X")
+            .with_output::<Float>("Out")
+            .build_checked(|| {
+                let group_in = crate::core::nodes::NodeGroupInput::new();
+                // Typo: "Y" is never declared.
+                let y = group_in.socket::<Float>("Y");
+                // Leaked from another tree.
+                let foreign = NodeSocket::<Float>::new_output("OtherTree_node.outputs[0]");
+                NodeGroupOutput::new().set_input(0, y + foreign);
+            });
+
+        let diagnostics = result.unwrap_err();
+        assert_eq!(diagnostics.tree, "Broken");
+        let kinds: Vec<DiagnosticKind> = diagnostics.errors().map(|d| d.kind).collect();
+        assert!(kinds.contains(&DiagnosticKind::InterfaceMismatch));
+        assert!(kinds.contains(&DiagnosticKind::UnknownNode));
+
+        let report = diagnostics.to_string();
+        assert!(report.contains("\"Y\""));
+        assert!(report.contains("OtherTree_node"));
+    }
+
+    #[test]
+    fn test_group_output_index_checked_against_interface() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        let tree = NodeTree::new_geometry_group("Pair")
+            .with_output::<Float>("A")
+            .with_output::<Float>("B");
+
+        let result = tree.build_checked(|| {
+            NodeGroupOutput::new().set_input(5, NodeSocket::<Float>::from(1.0));
+        });
+        let report = result.unwrap_err().to_string();
+        assert!(
+            report.contains("sets group output 5 but the tree declares 2 output(s): \"A\", \"B\"")
+        );
+
+        let result = tree.build_checked(|| {
+            NodeGroupOutput::new()
+                .set_input(0, NodeSocket::<Float>::from(1.0))
+                .set_input(1, NodeSocket::<Float>::from(2.0));
+        });
+        assert!(result.is_ok());
+
+        // Params declare group inputs too.
+        let scale = crate::core::param::Param::<Float>::new("Scale", 2.0);
+        let result = tree.build_checked(|| {
+            let group_in = crate::core::nodes::NodeGroupInput::new();
+            let typo = group_in.socket::<Float>("Scael");
+            NodeGroupOutput::new()
+                .set_input(0, NodeSocket::<Float>::from(scale))
+                .set_input(1, typo);
+        });
+        let report = result.unwrap_err().to_string();
+        assert!(report.contains(
+            "reads group input \"Scael\" which the tree does not declare; inputs: \"Scale\""
+        ));
+    }
+
+    #[test]
+    fn test_build_checked_passes_with_warnings_only() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let result = NodeTree::new_geometry_group("Fine")
+            .with_input::<Float>("X")
+            .with_output::<Float>("Out")
+            .build_checked(|| {
+                let x = crate::core::nodes::NodeGroupInput::new().socket::<Float>("X");
+                let _unused = x * 2.0;
+                NodeGroupOutput::new().set_input(0, x);
+            });
+
+        let script = result.unwrap();
+        assert!(script.contains("# --- Node Linking Phase ---"));
+
+        let (_, diagnostics) = NodeTree::new_geometry_group("Fine")
+            .with_output::<Float>("Out")
+            .build_with_diagnostics(|| {
+                NodeGroupOutput::new().set_input(3, NodeSocket::<Float>::from(1.0));
+            });
+        assert_eq!(diagnostics.errors().count(), 1);
+        assert_eq!(
+            diagnostics.errors().next().unwrap().kind,
+            DiagnosticKind::InterfaceMismatch
+        );
+    }
+
+    #[test]
+    fn test_build_to_graph_chain() {
+        use crate::core::graph::GraphEdge;
+        use crate::core::nodes::GeometryNodeMeshCube;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let graph = NodeTree::new_geometry("GraphTree").build_to_graph(|| {
+            let cube = GeometryNodeMeshCube::new().with_vertices_x(3);
+            NodeGroupOutput::new().set_input(0, cube.out_mesh());
+        });
+
+        assert_eq!(graph.tree, "GraphTree");
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].bl_idname, "GeometryNodeMeshCube");
+        assert_eq!(graph.nodes[0].defaults.values().next().unwrap(), "3");
+        assert_eq!(
+            graph.edges,
+            vec![GraphEdge {
+                from_node: graph.nodes[0].name.clone(),
+                from_socket: "Mesh".to_string(),
+                to_node: graph.nodes[1].name.clone(),
+                to_input: 0,
+            }]
+        );
+
+        let json = graph.to_json();
+        assert!(json.contains("\"from_socket\": \"Mesh\""));
+    }
+
+    #[test]
+    fn test_build_inspect_matches_script() {
+        use crate::core::nodes::GeometryNodeMeshCube;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let (script, nodes) = NodeTree::new_geometry("InspectTree").build_inspect(|| {
+            let cube = GeometryNodeMeshCube::new().with_vertices_x(3);
+            NodeGroupOutput::new().set_input(0, cube.out_mesh());
+        });
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[0].input(GeometryNodeMeshCube::PIN_VERTICES_X),
+            Some("3")
+        );
+        assert_eq!(nodes[1].links[0].from_node, nodes[0].name);
+        assert_eq!(nodes[1].links[0].from_socket, "Mesh");
+        for node in &nodes {
+            assert!(script.contains(&format!("{} = tree.nodes.new(", node.name)));
+        }
+    }
+
+    #[test]
+    fn test_phase_hooks_placement() {
+        use crate::core::nodes::GeometryNodeMeshCube;
+        use std::fmt::Write;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let per_node = |scope: &Scope| {
+            scope.iter().fold(String::new(), |mut out, n| {
+                let _ = writeln!(out, "# created {}", n.name);
+                out
+            })
+        };
+        let script = NodeTree::new_geometry("Hooked")
+            .on_phase(Phase::PostLinking, |_| "# tagged\n".to_string())
+            .on_phase(Phase::PostCreation, per_node)
+            .on_phase(Phase::PostCreation, |_| "# second\n".to_string())
+            .build(|| {
+                let cube = GeometryNodeMeshCube::new();
+                NodeGroupOutput::new().set_input(0, cube.out_mesh());
+            });
+
+        let post_creation = script.find("# --- Node Post Creation Phase ---").unwrap();
+        let linking = script.find("# --- Node Linking Phase ---").unwrap();
+        let created: Vec<usize> = script.match_indices("# created ").map(|(i, _)| i).collect();
+        let second = script.find("# second").unwrap();
+        let tagged = script.find("# tagged").unwrap();
+
+        assert_eq!(created.len(), 2);
+        assert!(created.iter().all(|&i| post_creation < i && i < second));
+        assert!(second < linking);
+        assert!(linking < tagged);
+        assert!(script.trim_end().ends_with("# tagged"));
+    }
+
+    #[test]
+    fn test_built_group_socket_identifier_lookup() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let group = NodeTree::new_geometry_group("Ripple \"v2\"")
+            .with_input::<Float>("Scale")
+            .with_output::<Geo>("Geometry")
+            .build_group(|| {
+                NodeGroupOutput::new();
+            });
+
+        assert!(
+            group
+                .script
+                .contains("sock_ids[\"Scale\"] = sock.identifier")
+        );
+        assert_eq!(
+            group.socket_identifier_lookup_expr("Scale").unwrap(),
+            r#"ramen_socket_ids["Ripple \"v2\""]["Scale"]"#
+        );
+        assert_eq!(group.socket_identifier_lookup_expr("Geometry"), None);
+    }
+
+    #[test]
+    fn test_node_budget_exceeded() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let tree = NodeTree::new_geometry("Budget").with_node_budget(3);
+        let body = || {
+            let x = NodeSocket::<Float>::from(1.0);
+            let _ = x + 1.0 + 2.0 + 3.0;
+            NodeGroupOutput::new();
+        };
+
+        let diagnostics = tree.build_checked(body).unwrap_err();
+        let error = diagnostics.errors().next().unwrap();
+        assert_eq!(error.kind, DiagnosticKind::NodeBudget);
+        assert!(error.message.starts_with("4 nodes exceed the budget of 3"));
+        assert!(
+            error
+                .message
+                .contains("ShaderNodeMath x3, NodeGroupOutput x1")
+        );
+        assert!(error.message.contains("longest chain: 3 nodes"));
+
+        // `build` only warns and still emits the script.
+        assert!(tree.build(body).contains("# --- Node Linking Phase ---"));
+    }
+
+    #[test]
+    fn test_keep_modifier_settings_reuses_modifier() {
+        let replaced = NodeTree::new_geometry("Geo").setup_geometry();
+        assert!(replaced.contains("obj.modifiers.remove(existing_mod)"));
+        assert!(replaced.contains("mod = obj.modifiers.new(name=mod_name, type='NODES')\n"));
+
+        let kept = NodeTree::new_geometry("Geo")
+            .keep_modifier_settings(true)
+            .setup_geometry();
+        assert!(kept.contains("mod = obj.modifiers.get(mod_name)\n"));
+        assert!(!kept.contains("existing_mod"));
+        // Only a modifier of the wrong type is removed; a new one is added only if missing.
+        assert!(kept.contains("if mod and mod.type != 'NODES':\n    obj.modifiers.remove(mod)"));
+        assert!(
+            kept.contains("if not mod:\n    mod = obj.modifiers.new(name=mod_name, type='NODES')")
+        );
+        assert!(!kept.contains("mod.node_group"));
+
+        let input = TreeInput {
+            name: "Power".to_string(),
+            blender_type: "NodeSocketFloat".to_string(),
+            default_expr: Some("8.0000".to_string()),
+            config: InputConfig::default(),
+        };
+        let script = NodeTree::new_geometry("Geo")
+            .keep_modifier_settings(true)
+            .generate_setup_script(&[input]);
+        let assign_at = script.find("mod.node_group = group\n").unwrap();
+        let last_socket_at = script.rfind("tree.interface.new_socket(").unwrap();
+        let kept_value_at = script
+            .find("if sock.identifier not in mod.keys():")
+            .unwrap();
+        assert!(last_socket_at < assign_at);
+        assert!(kept_value_at < assign_at);
+    }
+
+    #[test]
+    fn test_world_setup_script() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let script = NodeTree::new_world("Sky").build(|| {
+            ShaderNodeOutputWorld::new();
+        });
+
+        assert!(script.contains("# --- Setup World: Sky ---"));
+        assert!(script.contains("world = bpy.data.worlds.new(name=\"Sky\")"));
+        assert!(script.contains("scene.world = world"));
+        assert!(script.contains("tree = scene.world.node_tree\ntree.nodes.clear()"));
+        assert!(script.contains("ShaderNodeOutputWorld"));
+    }
+
+    #[test]
+    fn test_lossy_literal_in_diagnostics() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let (_, diagnostics) = NodeTree::new_geometry("Lossy").build_with_diagnostics(|| {
+            let x = NodeSocket::<Float>::from(0.00001) + 0.25;
+            NodeGroupOutput::new().set_input(0, x);
+        });
+        let warnings: Vec<_> = diagnostics
+            .warnings()
+            .filter(|d| d.kind == DiagnosticKind::PrecisionLoss)
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.starts_with("float literal 1e-5 "));
+    }
+
+    #[test]
+    fn test_supports_interface() {
+        let plain = [
+            TreeType::Geometry,
+            TreeType::Shader,
+            TreeType::Compositor,
+            TreeType::World,
+        ];
+        let groups = [
+            TreeType::GeometryGroup,
+            TreeType::ShaderGroup,
+            TreeType::CompositorGroup,
+        ];
+        assert!(plain.iter().all(|t| !t.supports_interface()));
+        assert!(groups.iter().all(|t| t.supports_interface()));
+
+        let err =
+            std::panic::catch_unwind(|| NodeTree::new_shader("Mat").with_output::<Float>("X"))
+                .err()
+                .unwrap();
+        assert_eq!(
+            err.downcast_ref::<String>().unwrap(),
+            "with_output can only be used on group node trees (new_*_group), but 'Mat' is a Shader tree"
+        );
+    }
+
+    #[test]
+    fn test_call_compositor_group() {
+        use crate::core::context;
+        use crate::core::types::{Color, CompositorNodeGroupExt};
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let group = call_compositor_group("Cinematic \"Look\"");
+        let image = group.out_socket::<Color>("Image");
+        let nodes = context::exit_zone();
+
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "CompositorNodeGroup");
+        assert_eq!(
+            nodes[0].properties["node_tree"],
+            r#"bpy.data.node_groups["Cinematic \"Look\""]"#
+        );
+        assert_eq!(
+            image.python_expr(),
+            format!("{}.outputs[\"Image\"]", nodes[0].name)
+        );
+    }
+
+    #[test]
+    fn test_builder_panic_names_the_tree() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let result = catch_unwind(|| {
+            NodeTree::new_geometry("Exploding").build(|| {
+                NodeGroupOutput::new();
+                panic!("radius must be positive");
+            })
+        });
+        let payload = result.unwrap_err();
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert_eq!(
+            message,
+            "tree 'Exploding' (Geometry) panicked after 1 node(s): radius must be positive"
+        );
+
+        // The scope of the failed tree is gone; the next build starts clean.
+        let script = NodeTree::new_geometry("After").build(|| {
+            NodeGroupOutput::new();
+        });
+        assert_eq!(script.matches("tree.nodes.new(").count(), 1);
+    }
+
+    #[test]
+    fn test_build_imports_required_modules() {
+        use crate::core::context::require_import;
+        use crate::core::nodes::FunctionNodeRotateRotation;
+        use crate::core::types::Rotation;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        // Left over outside any build; never emitted by one.
+        require_import("random");
+        let script = NodeTree::new_geometry("Turned").build(|| {
+            let _ = FunctionNodeRotateRotation::new()
+                .with_rotation(NodeSocket::<Rotation>::from((0.0, 0.0, 1.0)));
+        });
+        assert!(script.starts_with("import mathutils\n\n# --- Setup GeoNodes: Turned ---"));
+        assert!(!script.contains("import random"));
+
+        let plain = NodeTree::new_geometry("Plain").build(|| {});
+        assert!(!plain.contains("import "));
+    }
+
+    #[test]
+    fn test_build_imports_math_for_constants() {
+        use crate::core::nodes::ShaderNodeMath;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        let script = NodeTree::new_geometry_group("Turn")
+            .with_output::<Float>("Angle")
+            .build(|| {
+                let full = ShaderNodeMath::new()
+                    .with_value(NodeSocket::<Float>::pi())
+                    .with_value_1(NodeSocket::<Float>::tau())
+                    .out_value();
+                NodeGroupOutput::new().set_input(0, full);
+            });
+        assert!(script.starts_with("import math\n"));
+        assert_eq!(script.matches("import math").count(), 1);
+        assert!(script.contains(".default_value = math.pi\n"));
     }
 }
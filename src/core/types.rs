@@ -18,6 +18,7 @@ pub struct Matrix;
 pub struct Rotation;
 pub struct Menu;
 pub struct Bundle;
+pub struct Closure;
 pub struct Any;
 
 // helpers ===============================================================================
@@ -47,7 +48,29 @@ pub fn fmt_f32(v: f32) -> String {
     } else if v.is_infinite() {
         "float('-inf')".to_string()
     } else {
-        format!("{:.4}", v)
+        let text = format!("{:.4}", v);
+        check_precision(v, &text);
+        text
+    }
+}
+
+/// Relative error above which a formatted literal is reported as lossy.
+const MAX_LITERAL_RELATIVE_ERROR: f32 = 1e-3;
+
+/// Warns (through the build context) when `text` no longer represents `v`,
+/// e.g. `0.00001` printed as `0.0000`.
+fn check_precision(v: f32, text: &str) {
+    let Ok(parsed) = text.parse::<f32>() else {
+        return;
+    };
+    if v != 0.0 && ((parsed - v) / v).abs() > MAX_LITERAL_RELATIVE_ERROR {
+        crate::core::context::warn(
+            crate::core::diagnostics::DiagnosticKind::PrecisionLoss,
+            format!(
+                "float literal {:e} is emitted as {}, losing precision",
+                v, text
+            ),
+        );
     }
 }
 
@@ -122,6 +145,62 @@ impl<T> NodeSocket<T> {
     pub fn python_expr(&self) -> String {
         get_expr(self.expr_id).expect("internal error: invalid expression id")
     }
+
+    /// Like `python_expr`, but returns an error instead of panicking on an unknown expression id,
+    /// and checks that a non-literal expression has the form `<node>.outputs[<key>]`.
+    pub fn python_expr_checked(&self) -> Result<String, &'static str> {
+        let expr = get_expr(self.expr_id).ok_or("invalid expression id")?;
+        if !self.is_literal && !is_output_expr(&expr) {
+            return Err("malformed output expression");
+        }
+        Ok(expr)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.python_expr_checked().is_ok()
+    }
+
+    /// Value of a numeric literal (as written by `fmt_f32` or an integer conversion).
+    /// `None` for linked sockets and non-numeric literals.
+    pub fn literal_f32(&self) -> Option<f32> {
+        if !self.is_literal {
+            return None;
+        }
+        parse_f32_literal(&self.python_expr())
+    }
+
+    /// Components of a 3-tuple literal such as `(1.0000, 0.0000, 2.5000)`.
+    /// `None` for linked sockets and other literals.
+    pub fn literal_vec3(&self) -> Option<(f32, f32, f32)> {
+        if !self.is_literal {
+            return None;
+        }
+        let expr = self.python_expr();
+        let inner = expr.strip_prefix('(')?.strip_suffix(')')?;
+        let mut parts = inner.split(", ").map(parse_f32_literal);
+        let v = (parts.next()??, parts.next()??, parts.next()??);
+        parts.next().is_none().then_some(v)
+    }
+}
+
+/// Inverse of `fmt_f32` (also accepts integer literals).
+fn parse_f32_literal(expr: &str) -> Option<f32> {
+    match expr {
+        "float('nan')" => Some(f32::NAN),
+        "float('inf')" => Some(f32::INFINITY),
+        "float('-inf')" => Some(f32::NEG_INFINITY),
+        _ => expr.parse().ok(),
+    }
+}
+
+fn is_output_expr(expr: &str) -> bool {
+    let Some((node, key)) = expr.split_once(".outputs[") else {
+        return false;
+    };
+    !node.is_empty()
+        && node.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && key.len() > 1
+        && key.ends_with(']')
 }
 
 // float ===============================================================================
@@ -245,10 +324,15 @@ impl From<NodeSocket<Color>> for NodeSocket<Vector> {
     }
 }
 
+// Rotation and matrix sockets do not take plain tuples as `default_value`,
+// so their literals are built with `mathutils` (imported in the project header on demand).
+
+/// XYZ euler angles in radians.
 impl From<(f32, f32, f32)> for NodeSocket<Rotation> {
     fn from(v: (f32, f32, f32)) -> Self {
+        crate::core::context::require_import("mathutils");
         Self::new_literal(format!(
-            "({}, {}, {})",
+            "mathutils.Euler(({}, {}, {}))",
             fmt_f32(v.0),
             fmt_f32(v.1),
             fmt_f32(v.2)
@@ -256,6 +340,86 @@ impl From<(f32, f32, f32)> for NodeSocket<Rotation> {
     }
 }
 
+/// Row-major 4x4 matrix.
+impl From<[[f32; 4]; 4]> for NodeSocket<Matrix> {
+    fn from(rows: [[f32; 4]; 4]) -> Self {
+        crate::core::context::require_import("mathutils");
+        let rows: Vec<String> = rows
+            .iter()
+            .map(|row| {
+                let cells: Vec<String> = row.iter().map(|&v| fmt_f32(v)).collect();
+                format!("({})", cells.join(", "))
+            })
+            .collect();
+        Self::new_literal(format!("mathutils.Matrix(({}))", rows.join(", ")))
+    }
+}
+
+// constants =======================================================================
+impl Rotation {
+    /// Euler rotation literal from degrees (`From<(f32, f32, f32)>` takes radians).
+    pub fn from_degrees(v: (f32, f32, f32)) -> NodeSocket<Rotation> {
+        NodeSocket::from((v.0.to_radians(), v.1.to_radians(), v.2.to_radians()))
+    }
+}
+
+impl Matrix {
+    pub fn identity() -> NodeSocket<Matrix> {
+        NodeSocket::from([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+// Python's `math` constants are exact where a 4 decimal literal is not.
+impl NodeSocket<Float> {
+    /// `math.pi`; the script of the tree using it imports `math` (see `NodeTree::build`).
+    pub fn pi() -> Self {
+        crate::core::context::require_import("math");
+        Self::new_literal("math.pi")
+    }
+
+    /// `math.tau`, a full turn in radians.
+    pub fn tau() -> Self {
+        crate::core::context::require_import("math");
+        Self::new_literal("math.tau")
+    }
+}
+
+impl Vector {
+    pub fn zero() -> NodeSocket<Vector> {
+        NodeSocket::from((0.0, 0.0, 0.0))
+    }
+    pub fn one() -> NodeSocket<Vector> {
+        NodeSocket::from((1.0, 1.0, 1.0))
+    }
+    pub fn unit_x() -> NodeSocket<Vector> {
+        NodeSocket::from((1.0, 0.0, 0.0))
+    }
+    pub fn unit_y() -> NodeSocket<Vector> {
+        NodeSocket::from((0.0, 1.0, 0.0))
+    }
+    pub fn unit_z() -> NodeSocket<Vector> {
+        NodeSocket::from((0.0, 0.0, 1.0))
+    }
+}
+
+impl Color {
+    pub fn black() -> NodeSocket<Color> {
+        NodeSocket::from((0.0, 0.0, 0.0, 1.0))
+    }
+    pub fn white() -> NodeSocket<Color> {
+        NodeSocket::from((1.0, 1.0, 1.0, 1.0))
+    }
+    /// Black with zero alpha.
+    pub fn transparent() -> NodeSocket<Color> {
+        NodeSocket::from((0.0, 0.0, 0.0, 0.0))
+    }
+}
+
 // reference =======================================================================
 fn bpy_data_get_expr(domain: &str, name: &str) -> String {
     format!("bpy.data.{}.get({})", domain, python_string_literal(name))
@@ -330,6 +494,7 @@ impl_socket_def!(Matrix, "MATRIX", "Matrix", "NodeSocketMatrix");
 impl_socket_def!(Rotation, "ROTATION", "Rotation", "NodeSocketRotation");
 impl_socket_def!(Menu, "MENU", "Menu", "NodeSocketMenu");
 impl_socket_def!(Bundle, "BUNDLE", "Bundle", "NodeSocketBundle");
+impl_socket_def!(Closure, "CLOSURE", "Closure", "NodeSocketClosure");
 
 // extensions ==========================================================================
 pub trait NodeGroupInputExt {
@@ -374,6 +539,37 @@ impl ShaderNodeGroupExt for crate::core::nodes::ShaderNodeGroup {
     }
 }
 
+pub trait CompositorNodeGroupExt {
+    fn out_socket<T>(&self, name: &str) -> NodeSocket<T>;
+}
+
+impl CompositorNodeGroupExt for crate::core::nodes::CompositorNodeGroup {
+    fn out_socket<T>(&self, name: &str) -> NodeSocket<T> {
+        NodeSocket::new_output(format!(
+            "{}.outputs[{}]",
+            self.name,
+            python_string_literal(name)
+        ))
+    }
+}
+
+// geometry ==========================================================================
+
+/// Socket types that carry geometry: `Geo` itself, and `Any` (dynamic sockets such as group outputs).
+pub trait GeometryType {}
+impl GeometryType for Geo {}
+impl GeometryType for Any {}
+
+impl<T: GeometryType> NodeSocket<T> {
+    /// Views this socket as generic geometry.
+    ///
+    /// Curve, mesh, point cloud and instance outputs are all generated as `NodeSocket<Geo>`,
+    /// so this is only a no-op for them; prefer it over `cast::<Geo>()`, which accepts any type.
+    pub fn into_geo(self) -> NodeSocket<Geo> {
+        self.cast::<Geo>()
+    }
+}
+
 // any ===============================================================================
 macro_rules! impl_into_any {
     ($($t:ty),*) => {
@@ -389,7 +585,7 @@ macro_rules! impl_into_any {
 
 impl_into_any!(
     Geo, Float, Int, Vector2D, Vector, Vector4D, Color, StringType, Bool, Material, Object,
-    Collection, Image, Shader, Matrix, Rotation, Menu, Bundle
+    Collection, Image, Shader, Matrix, Rotation, Menu, Bundle, Closure
 );
 
 // ---------------------------------------------------------
@@ -437,6 +633,10 @@ mod tests {
 
     #[test]
     fn test_tuple_conversions() {
+        let _lock = crate::core::context::test_utils::GLOBAL_TEST_LOCK
+            .lock()
+            .unwrap();
+
         let v = NodeSocket::<Vector>::from((1.0, 0.5, -2.1));
         assert_eq!(v.python_expr(), "(1.0000, 0.5000, -2.1000)");
 
@@ -447,12 +647,114 @@ mod tests {
         assert_eq!(v2.python_expr(), "(1.0000, 0.4000)");
 
         let rot = NodeSocket::<Rotation>::from((0.0, 1.57, 0.0));
-        assert_eq!(rot.python_expr(), "(0.0000, 1.5700, 0.0000)");
+        assert_eq!(
+            rot.python_expr(),
+            "mathutils.Euler((0.0000, 1.5700, 0.0000))"
+        );
+
+        let m = NodeSocket::<Matrix>::from([
+            [2.0, 0.0, 0.0, 1.0],
+            [0.0, 2.0, 0.0, 0.0],
+            [0.0, 0.0, 2.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        assert_eq!(
+            m.python_expr(),
+            "mathutils.Matrix(((2.0000, 0.0000, 0.0000, 1.0000), (0.0000, 2.0000, 0.0000, 0.0000), \
+             (0.0000, 0.0000, 2.0000, 0.0000), (0.0000, 0.0000, 0.0000, 1.0000)))"
+        );
 
         let menu = NodeSocket::<Menu>::from("LINEAR");
         assert_eq!(menu.python_expr(), "\"LINEAR\"");
     }
 
+    #[test]
+    fn test_python_expr_checked() {
+        let ok = NodeSocket::<Float>::new_output("math_1.outputs[\"Value\"]");
+        assert_eq!(ok.python_expr_checked(), Ok(ok.python_expr()));
+        assert!(ok.is_valid());
+
+        let literal = NodeSocket::<Float>::from(1.5);
+        assert_eq!(literal.python_expr_checked(), Ok("1.5000".to_string()));
+
+        let dangling = NodeSocket::<Float> {
+            expr_id: usize::MAX,
+            is_literal: false,
+            _marker: std::marker::PhantomData,
+        };
+        assert_eq!(dangling.python_expr_checked(), Err("invalid expression id"));
+        assert!(!dangling.is_valid());
+
+        for bad in ["1.5", ".outputs[0]", "node.outputs[]", "a + b.outputs[0]"] {
+            let socket = NodeSocket::<Float>::new_output(bad);
+            assert_eq!(
+                socket.python_expr_checked(),
+                Err("malformed output expression"),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_constant_constructors() {
+        let _lock = crate::core::context::test_utils::GLOBAL_TEST_LOCK
+            .lock()
+            .unwrap();
+
+        let rot = Rotation::from_degrees((90.0, 0.0, -180.0));
+        assert_eq!(
+            rot.python_expr(),
+            "mathutils.Euler((1.5708, 0.0000, -3.1416))"
+        );
+        assert!(rot.is_literal);
+
+        assert_eq!(Vector::zero().python_expr(), "(0.0000, 0.0000, 0.0000)");
+        assert_eq!(Vector::one().python_expr(), "(1.0000, 1.0000, 1.0000)");
+        assert_eq!(Vector::unit_x().python_expr(), "(1.0000, 0.0000, 0.0000)");
+        assert_eq!(Vector::unit_y().python_expr(), "(0.0000, 1.0000, 0.0000)");
+        assert_eq!(Vector::unit_z().python_expr(), "(0.0000, 0.0000, 1.0000)");
+
+        assert_eq!(
+            Color::black().python_expr(),
+            "(0.0000, 0.0000, 0.0000, 1.0000)"
+        );
+        assert_eq!(
+            Color::white().python_expr(),
+            "(1.0000, 1.0000, 1.0000, 1.0000)"
+        );
+        assert_eq!(
+            Color::transparent().python_expr(),
+            "(0.0000, 0.0000, 0.0000, 0.0000)"
+        );
+    }
+
+    #[test]
+    fn test_menu_socket_enum_setter() {
+        use crate::core::context::{self, test_utils::GLOBAL_TEST_LOCK};
+        use crate::core::nodes::{GeometryNodeResampleCurve, GeometryNodeResampleCurveModeMenu};
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        GeometryNodeResampleCurve::new().with_mode(GeometryNodeResampleCurveModeMenu::Length);
+        GeometryNodeResampleCurve::new().with_mode("Length");
+        let nodes = context::exit_zone();
+
+        let pin = GeometryNodeResampleCurve::PIN_MODE;
+        assert_eq!(nodes[0].inputs.get(&pin).unwrap()[0].expr, "\"Length\"");
+        assert_eq!(
+            nodes[0].inputs.get(&pin).unwrap()[0].expr,
+            nodes[1].inputs.get(&pin).unwrap()[0].expr
+        );
+        assert!(nodes[0].creation_script().contains(&format!(
+            "{}.inputs[{}].default_value = \"Length\"",
+            nodes[0].name, pin
+        )));
+        assert_eq!(
+            "Count".parse::<GeometryNodeResampleCurveModeMenu>(),
+            Ok(GeometryNodeResampleCurveModeMenu::Count)
+        );
+    }
+
     #[test]
     fn test_socket_casting() {
         let vec = NodeSocket::<Vector>::new_output("some_node.outputs[0]");
@@ -480,4 +782,82 @@ mod tests {
         let img = NodeSocket::<Image>::from("Noise.png");
         assert_eq!(img.python_expr(), "bpy.data.images.get(\"Noise.png\")");
     }
+
+    #[test]
+    fn test_into_geo() {
+        let curve = NodeSocket::<Geo>::new_output("circle.outputs[\"Curve\"]");
+        let geo: NodeSocket<Geo> = curve.into_geo();
+        assert_eq!(geo.python_expr(), "circle.outputs[\"Curve\"]");
+        assert!(!geo.is_literal);
+
+        let dynamic = NodeSocket::<Any>::new_output("group.outputs[\"Mesh\"]");
+        let geo: NodeSocket<Geo> = dynamic.into_geo();
+        assert_eq!(geo.python_expr(), "group.outputs[\"Mesh\"]");
+    }
+
+    #[test]
+    fn test_lossy_float_literal_warns_once() {
+        use crate::core::context::{self, test_utils::GLOBAL_TEST_LOCK};
+        use crate::core::diagnostics::DiagnosticKind;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::take_diagnostics();
+
+        let _ = NodeSocket::<Float>::from(0.25);
+        assert!(context::take_diagnostics().is_empty());
+
+        let _ = NodeSocket::<Float>::from(1e-6);
+        let _ = NodeSocket::<Float>::from(1e-6);
+        let warnings = context::take_diagnostics();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, DiagnosticKind::PrecisionLoss);
+        assert_eq!(
+            warnings[0].message,
+            "float literal 1e-6 is emitted as 0.0000, losing precision"
+        );
+    }
+
+    #[test]
+    fn test_literal_read_back() {
+        assert_eq!(NodeSocket::<Float>::from(0.25).literal_f32(), Some(0.25));
+        assert_eq!(NodeSocket::<Int>::from(7).literal_f32(), Some(7.0));
+        assert_eq!(
+            NodeSocket::<Float>::from(f32::INFINITY).literal_f32(),
+            Some(f32::INFINITY)
+        );
+        assert_eq!(
+            NodeSocket::<Vector>::from((1.0, -2.0, 0.5)).literal_vec3(),
+            Some((1.0, -2.0, 0.5))
+        );
+        assert_eq!(
+            NodeSocket::<Vector>::from((1.0, 2.0, 3.0)).literal_f32(),
+            None
+        );
+        assert_eq!(NodeSocket::<Float>::from(1.0).literal_vec3(), None);
+
+        let linked = NodeSocket::<Float>::new_output("math.outputs[0]");
+        assert_eq!(linked.literal_f32(), None);
+        let linked = NodeSocket::<Vector>::new_output("pos.outputs[\"Position\"]");
+        assert_eq!(linked.literal_vec3(), None);
+    }
+
+    #[test]
+    fn test_get_input_literal() {
+        use crate::core::context::{self, test_utils::GLOBAL_TEST_LOCK};
+        use crate::core::nodes::ShaderNodeMath;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        context::enter_zone();
+        let linked = NodeSocket::<Float>::new_output("src.outputs[0]");
+        let math = ShaderNodeMath::new()
+            .set_input(0, linked)
+            .set_input(1, NodeSocket::<Float>::from(1.5));
+        let name = math.name.clone();
+        assert_eq!(context::get_input_literal(&name, 0), None);
+        assert_eq!(
+            context::get_input_literal(&name, 1).as_deref(),
+            Some("1.5000")
+        );
+        assert_eq!(context::get_input_literal(&name, 2), None);
+        context::exit_zone();
+    }
 }
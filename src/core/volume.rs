@@ -0,0 +1,193 @@
+//! # Volume Helpers
+//!
+//! Wrappers for the SDF-style workflow (density field → volume grid → mesh) that take care of
+//! the resolution pins and `resolution_mode` enums of the underlying volume nodes.
+
+use crate::core::nodes::{
+    GeometryNodePointsToVolume, GeometryNodePointsToVolumeResolutionMode, GeometryNodeVolumeCube,
+    GeometryNodeVolumeToMesh, GeometryNodeVolumeToMeshResolutionMode,
+};
+use crate::core::types::{Float, Geo, NodeSocket, Vector};
+
+/// Voxel count of a `cube` grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// Same voxel count on every axis.
+    Uniform(i32),
+    /// Voxel count per axis (x, y, z).
+    PerAxis(i32, i32, i32),
+}
+
+/// How `to_mesh` chooses its meshing resolution.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MeshResolution {
+    /// Mesh at the resolution of the input grid.
+    Grid,
+    /// Remesh with voxels of the given size.
+    Voxel(f32),
+    /// Remesh with the given number of voxels along the longest side.
+    VoxelAmount(f32),
+}
+
+/// Samples the `density` field into a volume grid spanning `bounds` (min, max).
+pub fn cube(
+    density: NodeSocket<Float>,
+    bounds: (impl Into<NodeSocket<Vector>>, impl Into<NodeSocket<Vector>>),
+    resolution: Resolution,
+) -> NodeSocket<Geo> {
+    let (x, y, z) = match resolution {
+        Resolution::Uniform(n) => (n, n, n),
+        Resolution::PerAxis(x, y, z) => (x, y, z),
+    };
+
+    GeometryNodeVolumeCube::new()
+        .with_resolution_x(x)
+        .with_resolution_y(y)
+        .with_resolution_z(z)
+        .with_min(bounds.0)
+        .with_max(bounds.1)
+        .set_input(GeometryNodeVolumeCube::PIN_DENSITY, density)
+        .out_volume()
+}
+
+/// Meshes the iso-surface of `volume` at `threshold`.
+pub fn to_mesh(
+    volume: NodeSocket<Geo>,
+    threshold: impl Into<NodeSocket<Float>>,
+    resolution: MeshResolution,
+) -> NodeSocket<Geo> {
+    let node = GeometryNodeVolumeToMesh::new()
+        .with_volume(volume)
+        .with_threshold(threshold);
+
+    let node = match resolution {
+        MeshResolution::Grid => {
+            node.with_resolution_mode(GeometryNodeVolumeToMeshResolutionMode::Grid)
+        }
+        MeshResolution::Voxel(size) => node
+            .with_resolution_mode(GeometryNodeVolumeToMeshResolutionMode::VoxelSize)
+            .with_voxel_size(size),
+        MeshResolution::VoxelAmount(amount) => node
+            .with_resolution_mode(GeometryNodeVolumeToMeshResolutionMode::VoxelAmount)
+            .with_voxel_amount(amount),
+    };
+
+    node.out_mesh()
+}
+
+/// Converts `points` to a fog volume: a sphere of `radius` per point, sampled with voxels of size `voxel`.
+pub fn points_to_volume(
+    points: NodeSocket<Geo>,
+    radius: impl Into<NodeSocket<Float>>,
+    voxel: impl Into<NodeSocket<Float>>,
+) -> NodeSocket<Geo> {
+    GeometryNodePointsToVolume::new()
+        .with_resolution_mode(GeometryNodePointsToVolumeResolutionMode::VoxelSize)
+        .with_points(points)
+        .with_radius(radius)
+        .with_voxel_size(voxel)
+        .out_volume()
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+
+    #[test]
+    fn test_cube_density_pin_and_resolution() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let density = NodeSocket::<Float>::new_output("sdf_node.outputs[0]");
+        let _ = cube(
+            density,
+            ((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0)),
+            Resolution::Uniform(64),
+        );
+        let _ = cube(
+            density,
+            ((0.0, 0.0, 0.0), (2.0, 1.0, 1.0)),
+            Resolution::PerAxis(128, 64, 32),
+        );
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodeVolumeCube");
+        assert_eq!(GeometryNodeVolumeCube::PIN_DENSITY, 0);
+        assert_eq!(
+            nodes[0]
+                .inputs
+                .get(&GeometryNodeVolumeCube::PIN_DENSITY)
+                .unwrap()[0]
+                .expr,
+            density.python_expr()
+        );
+        for pin in 4..=6 {
+            assert_eq!(nodes[0].inputs.get(&pin).unwrap()[0].expr, "64");
+        }
+        assert_eq!(nodes[1].inputs.get(&4).unwrap()[0].expr, "128");
+        assert_eq!(nodes[1].inputs.get(&5).unwrap()[0].expr, "64");
+        assert_eq!(nodes[1].inputs.get(&6).unwrap()[0].expr, "32");
+    }
+
+    #[test]
+    fn test_to_mesh_resolution_mode() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let volume = NodeSocket::<Geo>::new_output("vol_node.outputs[0]");
+        let _ = to_mesh(volume, 0.01, MeshResolution::Grid);
+        let _ = to_mesh(volume, 0.5, MeshResolution::Voxel(0.05));
+        let _ = to_mesh(volume, 0.5, MeshResolution::VoxelAmount(32.0));
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 3);
+        let mode = |i: usize| nodes[i].properties.get("resolution_mode").unwrap().as_str();
+        assert_eq!(mode(0), "\"GRID\"");
+        assert_eq!(mode(1), "\"VOXEL_SIZE\"");
+        assert_eq!(mode(2), "\"VOXEL_AMOUNT\"");
+
+        let pin = |i: usize, p: usize| nodes[i].inputs.get(&p).map(|v| v[0].expr.clone());
+        assert_eq!(
+            pin(0, GeometryNodeVolumeToMesh::PIN_THRESHOLD).as_deref(),
+            Some("0.0100")
+        );
+        assert_eq!(pin(0, GeometryNodeVolumeToMesh::PIN_VOXEL_SIZE), None);
+        assert_eq!(
+            pin(1, GeometryNodeVolumeToMesh::PIN_VOXEL_SIZE).as_deref(),
+            Some("0.0500")
+        );
+        assert_eq!(
+            pin(2, GeometryNodeVolumeToMesh::PIN_VOXEL_AMOUNT).as_deref(),
+            Some("32.0000")
+        );
+    }
+
+    #[test]
+    fn test_points_to_volume() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let points = NodeSocket::<Geo>::new_output("pts_node.outputs[0]");
+        let _ = points_to_volume(points, 0.2, 0.05);
+
+        let nodes = context::exit_zone();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].bl_idname, "GeometryNodePointsToVolume");
+        assert_eq!(
+            nodes[0].properties.get("resolution_mode").unwrap(),
+            "\"VOXEL_SIZE\""
+        );
+        assert_eq!(
+            nodes[0].inputs.get(&0).unwrap()[0].expr,
+            points.python_expr()
+        );
+        assert_eq!(nodes[0].inputs.get(&2).unwrap()[0].expr, "0.0500");
+        assert_eq!(nodes[0].inputs.get(&4).unwrap()[0].expr, "0.2000");
+    }
+}
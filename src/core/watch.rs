@@ -0,0 +1,311 @@
+//! # Rebuild on Change
+//!
+//! Rebuilds a project whenever watched files change and sends it to Blender:
+//!
+//! ```ignore
+//! watch::watch(&["params.txt"], Duration::from_millis(300), || build_scene(&read_params()))?;
+//! ```
+//!
+//! The build closure runs in this process, so it picks up whatever it reads at build time
+//! (parameter files, data). Changes to Rust sources need a recompile; run the program under a
+//! tool like `cargo watch` for those.
+//!
+//! Saves that arrive within `debounce` of each other are coalesced into one rebuild. A rebuild
+//! whose scripts match the last successful send is not sent again, and a failed send or a
+//! panicking build is reported without ending the watch.
+//!
+//! `watch` needs the `watch` feature (file events via `notify`). The loop itself,
+//! `watch_with`, takes any `EventSource`.
+
+use crate::core::project::BlenderProject;
+use crate::core::snapshot::SendSnapshot;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    Changed(PathBuf),
+    /// The timeout passed without a change.
+    Idle,
+    /// No more events will come; ends the watch.
+    Closed,
+}
+
+/// Where file changes come from.
+pub trait EventSource {
+    /// Waits for the next event, at most `timeout` if given.
+    fn next_event(&mut self, timeout: Option<Duration>) -> WatchEvent;
+}
+
+/// Counts of a finished `watch_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchReport {
+    pub rebuilds: usize,
+    pub sends: usize,
+    /// Panicked builds and failed sends.
+    pub failures: usize,
+}
+
+/// Waits for a change, then keeps collecting until `debounce` passes without another one.
+/// Returns the changed paths without duplicates, or `None` once the source is closed.
+pub fn next_batch(source: &mut impl EventSource, debounce: Duration) -> Option<Vec<PathBuf>> {
+    let first = loop {
+        match source.next_event(None) {
+            WatchEvent::Changed(path) => break path,
+            WatchEvent::Idle => continue,
+            WatchEvent::Closed => return None,
+        }
+    };
+
+    let mut changed = vec![first];
+    loop {
+        match source.next_event(Some(debounce)) {
+            WatchEvent::Changed(path) => {
+                if !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+            WatchEvent::Idle | WatchEvent::Closed => return Some(changed),
+        }
+    }
+}
+
+/// Builds and sends once, then again after every batch of changes from `source`, until it
+/// is closed. `send` is `BlenderProject::try_send` for `watch`.
+pub fn watch_with<S: EventSource>(
+    source: &mut S,
+    debounce: Duration,
+    build: impl Fn() -> BlenderProject,
+    mut send: impl FnMut(&BlenderProject) -> Result<String, String>,
+) -> WatchReport {
+    let mut report = WatchReport::default();
+    let mut last_sent: Option<SendSnapshot> = None;
+
+    loop {
+        report.rebuilds += 1;
+        match catch_unwind(AssertUnwindSafe(&build)) {
+            Ok(project) => {
+                let snapshot = project.snapshot();
+                let diff = last_sent.as_ref().map(|sent| sent.diff(&snapshot));
+                match diff {
+                    Some(diff) if diff.is_empty() => {
+                        println!("🍜 Blender Ramen: no changes, nothing to send");
+                    }
+                    _ => {
+                        if let Some(diff) = diff {
+                            print!("{}", diff);
+                        }
+                        report.sends += 1;
+                        match send(&project) {
+                            Ok(_) => last_sent = Some(snapshot),
+                            Err(err) => {
+                                report.failures += 1;
+                                eprintln!("❌ Send failed, retrying on the next change: {}", err);
+                            }
+                        }
+                    }
+                }
+            }
+            Err(_) => {
+                report.failures += 1;
+                eprintln!("❌ Build panicked, waiting for the next change");
+            }
+        }
+
+        let Some(changed) = next_batch(source, debounce) else {
+            return report;
+        };
+        let names: Vec<String> = changed.iter().map(|p| p.display().to_string()).collect();
+        println!("🔁 Changed: {}", names.join(", "));
+    }
+}
+
+/// Watches `paths` (files or directories, recursively) and sends the project `build` returns
+/// on start and after every change. Runs until the watcher fails.
+#[cfg(feature = "watch")]
+pub fn watch<P: AsRef<std::path::Path>>(
+    paths: &[P],
+    debounce: Duration,
+    build: impl Fn() -> BlenderProject,
+) -> notify::Result<WatchReport> {
+    let mut source = NotifySource::new(paths)?;
+    println!("👀 Blender Ramen: watching for changes (Ctrl+C to stop)");
+    Ok(watch_with(&mut source, debounce, build, |project| {
+        project.try_send()
+    }))
+}
+
+/// File events from the platform's watcher.
+#[cfg(feature = "watch")]
+pub struct NotifySource {
+    _watcher: notify::RecommendedWatcher,
+    events: std::sync::mpsc::Receiver<notify::Result<notify::Event>>,
+    pending: std::collections::VecDeque<PathBuf>,
+}
+
+#[cfg(feature = "watch")]
+impl NotifySource {
+    pub fn new<P: AsRef<std::path::Path>>(paths: &[P]) -> notify::Result<Self> {
+        use notify::Watcher;
+
+        let (tx, events) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        for path in paths {
+            watcher.watch(path.as_ref(), notify::RecursiveMode::Recursive)?;
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            pending: Default::default(),
+        })
+    }
+}
+
+#[cfg(feature = "watch")]
+impl EventSource for NotifySource {
+    fn next_event(&mut self, timeout: Option<Duration>) -> WatchEvent {
+        use std::sync::mpsc::RecvTimeoutError;
+
+        loop {
+            if let Some(path) = self.pending.pop_front() {
+                return WatchEvent::Changed(path);
+            }
+            let received = match timeout {
+                Some(timeout) => self.events.recv_timeout(timeout),
+                None => self
+                    .events
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                // Reads don't change anything.
+                Ok(Ok(event)) if event.kind.is_access() => {}
+                Ok(Ok(event)) => self.pending.extend(event.paths),
+                Ok(Err(err)) => eprintln!("⚠️ File watcher error: {}", err),
+                Err(RecvTimeoutError::Timeout) => return WatchEvent::Idle,
+                Err(RecvTimeoutError::Disconnected) => return WatchEvent::Closed,
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// unittest
+// ----------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
+    use crate::core::nodes::{NodeGroupOutput, ShaderNodeMath};
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+
+    /// Replays scripted events, recording the timeout each was asked for.
+    struct FakeSource {
+        events: VecDeque<WatchEvent>,
+        timeouts: Vec<Option<Duration>>,
+    }
+
+    impl FakeSource {
+        fn new(events: impl IntoIterator<Item = WatchEvent>) -> Self {
+            Self {
+                events: events.into_iter().collect(),
+                timeouts: Vec::new(),
+            }
+        }
+    }
+
+    impl EventSource for FakeSource {
+        fn next_event(&mut self, timeout: Option<Duration>) -> WatchEvent {
+            self.timeouts.push(timeout);
+            self.events.pop_front().unwrap_or(WatchEvent::Closed)
+        }
+    }
+
+    fn changed(path: &str) -> WatchEvent {
+        WatchEvent::Changed(PathBuf::from(path))
+    }
+
+    const DEBOUNCE: Duration = Duration::from_millis(300);
+
+    #[test]
+    fn test_next_batch_coalesces_rapid_changes() {
+        let mut source = FakeSource::new([
+            WatchEvent::Idle,
+            changed("a.txt"),
+            changed("b.txt"),
+            changed("a.txt"),
+            WatchEvent::Idle,
+            changed("c.txt"),
+        ]);
+
+        let batch = next_batch(&mut source, DEBOUNCE).unwrap();
+        assert_eq!(batch, [PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+        // Blocks for the first change, then waits `debounce` after each one.
+        assert_eq!(
+            source.timeouts,
+            [None, None, Some(DEBOUNCE), Some(DEBOUNCE), Some(DEBOUNCE)]
+        );
+
+        // A close during the debounce still delivers what was collected.
+        assert_eq!(
+            next_batch(&mut source, DEBOUNCE).unwrap(),
+            [PathBuf::from("c.txt")]
+        );
+        assert_eq!(next_batch(&mut source, DEBOUNCE), None);
+    }
+
+    #[test]
+    fn test_watch_with_skips_unchanged_and_survives_failures() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+
+        // 1: sent; 2: same scripts, skipped; 3: panics; 4: changed, but the send fails;
+        // 5: same as 4, still sent since 1 was the last successful send.
+        let builds = Cell::new(0);
+        let build = || {
+            builds.set(builds.get() + 1);
+            let value = match builds.get() {
+                1 | 2 => 1.0,
+                3 => panic!("bad params"),
+                _ => 2.0,
+            };
+            BlenderProject::new().add_geometry_tree("Watched", move || {
+                let v = ShaderNodeMath::new().with_value(value).out_value();
+                NodeGroupOutput::new().set_input(1, v);
+            })
+        };
+        let sends = Cell::new(0);
+        let send = |_: &BlenderProject| {
+            sends.set(sends.get() + 1);
+            if sends.get() == 2 {
+                Err("connection refused".to_string())
+            } else {
+                Ok("ok".to_string())
+            }
+        };
+
+        let mut source = FakeSource::new([
+            changed("p.txt"),
+            WatchEvent::Idle,
+            changed("p.txt"),
+            changed("p.txt"),
+            WatchEvent::Idle,
+            changed("p.txt"),
+            WatchEvent::Idle,
+            changed("p.txt"),
+            WatchEvent::Idle,
+        ]);
+        let report = watch_with(&mut source, DEBOUNCE, build, send);
+
+        assert_eq!(builds.get(), 5);
+        assert_eq!(
+            report,
+            WatchReport {
+                rebuilds: 5,
+                sends: 3,
+                failures: 2,
+            }
+        );
+    }
+}
@@ -1,10 +1,13 @@
 use crate::core::context::{append_custom_link, update_post_creation};
-use crate::core::nodes::{GeometryNodeRepeatInput, GeometryNodeRepeatOutput};
-use crate::core::types::{Int, NodeSocket, SocketDef};
+use crate::core::nodes::{
+    GeometryNodeRepeatInput, GeometryNodeRepeatOutput, NodeClosureInput, NodeClosureOutput,
+    NodeEvaluateClosure,
+};
+use crate::core::types::{Closure, Int, NodeSocket, SocketDef};
 use std::fmt::Write;
 
 /// manually link
-fn add_custom_link<T>(src: &NodeSocket<T>, dst_node: &str, index: usize) {
+pub(crate) fn add_custom_link<T>(src: &NodeSocket<T>, dst_node: &str, index: usize) {
     if src.is_literal {
         let script = format!(
             "{}.inputs[{}].default_value = {}\n",
@@ -24,6 +27,46 @@ fn add_custom_link<T>(src: &NodeSocket<T>, dst_node: &str, index: usize) {
     }
 }
 
+/// A socket type that Blender's zone item collections (`repeat_items`, and likewise simulation
+/// and bake items) do not accept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedZoneItem {
+    pub socket_type: &'static str,
+}
+
+impl std::fmt::Display for UnsupportedZoneItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "socket type '{}' cannot be used as a zone item (repeat/simulation/bake)",
+            self.socket_type
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedZoneItem {}
+
+/// Maps a socket type to the identifier passed to `<zone>_items.new(...)`.
+pub fn zone_item_type<T: SocketDef>() -> Result<&'static str, UnsupportedZoneItem> {
+    match T::socket_type() {
+        "FLOAT" => Ok("FLOAT"),
+        "INT" => Ok("INT"),
+        "BOOLEAN" => Ok("BOOLEAN"),
+        "VECTOR" => Ok("VECTOR"),
+        "RGBA" => Ok("RGBA"),
+        "ROTATION" => Ok("ROTATION"),
+        "MATRIX" => Ok("MATRIX"),
+        "STRING" => Ok("STRING"),
+        "GEOMETRY" => Ok("GEOMETRY"),
+        "OBJECT" => Ok("OBJECT"),
+        "COLLECTION" => Ok("COLLECTION"),
+        "MATERIAL" => Ok("MATERIAL"),
+        "IMAGE" => Ok("IMAGE"),
+        // VECTOR2D, VECTOR4D, MENU, BUNDLE, SHADER, and anything added later.
+        socket_type => Err(UnsupportedZoneItem { socket_type }),
+    }
+}
+
 pub trait RepeatItems {
     fn setup_items(out_name: &str, post_code: &mut String)
     where
@@ -47,30 +90,61 @@ impl RepeatItems for () {
     fn create_output(_out_name: &str) -> Self {}
 }
 
+/// One zone item: a socket whose type maps to a zone item type.
+///
+/// Used by the tuple impls below and by `#[derive(RamenItems)]`, so both register items and
+/// links the same way.
+pub trait ZoneSocket {
+    /// Appends the `repeat_items.new(...)` call for this item.
+    fn write_repeat_item(out_name: &str, post_code: &mut String)
+    where
+        Self: Sized;
+    /// Links (or, for literals, assigns) this socket to input `index` of `node`.
+    fn link_to(&self, node: &str, index: usize);
+    /// Socket reading output `index` of `node`.
+    fn from_output(node: &str, index: usize) -> Self
+    where
+        Self: Sized;
+}
+
+impl<T: SocketDef> ZoneSocket for NodeSocket<T> {
+    fn write_repeat_item(out_name: &str, post_code: &mut String) {
+        let item_type = zone_item_type::<T>().unwrap_or_else(|e| panic!("{}", e));
+        let _ = writeln!(
+            post_code,
+            "{}.repeat_items.new('{}', '{}')",
+            out_name,
+            item_type,
+            T::default_name()
+        );
+    }
+    fn link_to(&self, node: &str, index: usize) {
+        add_custom_link(self, node, index);
+    }
+    fn from_output(node: &str, index: usize) -> Self {
+        NodeSocket::new_output(format!("{}.outputs[{}]", node, index))
+    }
+}
+
 // for at least one element tuple ===================================
+// Repeat Input has the iterations socket first, so item `i` is input/output `i + 1` there.
 macro_rules! impl_repeat_items {
     ( $($idx:tt => $T:ident),+ ) => {
         impl<$($T: SocketDef),+> RepeatItems for ($(NodeSocket<$T>,)+) {
             fn setup_items(out_name: &str, post_code: &mut String) {
-                $(
-                    let _ = writeln!(
-                        post_code,
-                        "{}.repeat_items.new('{}', '{}')",
-                        out_name, $T::socket_type(), $T::default_name()
-                    );
-                )+
+                $( NodeSocket::<$T>::write_repeat_item(out_name, post_code); )+
             }
             fn link_initial(&self, in_name: &str) {
-                $( add_custom_link(&self.$idx, in_name, $idx + 1); )+
+                $( self.$idx.link_to(in_name, $idx + 1); )+
             }
             fn create_inner(in_name: &str) -> Self {
-                ( $( NodeSocket::<$T>::new_output(format!("{}.outputs[{}]", in_name, $idx + 1)), )+ )
+                ( $( NodeSocket::<$T>::from_output(in_name, $idx + 1), )+ )
             }
             fn link_result(&self, out_name: &str) {
-                $( add_custom_link(&self.$idx, out_name, $idx); )+
+                $( self.$idx.link_to(out_name, $idx); )+
             }
             fn create_output(out_name: &str) -> Self {
-                ( $( NodeSocket::<$T>::new_output(format!("{}.outputs[{}]", out_name, $idx)), )+ )
+                ( $( NodeSocket::<$T>::from_output(out_name, $idx), )+ )
             }
         }
     };
@@ -86,6 +160,8 @@ impl_repeat_items!(0 => T0, 1 => T1, 2 => T2, 3 => T3, 4 => T4);
 impl_repeat_items!(0 => T0, 1 => T1, 2 => T2, 3 => T3, 4 => T4, 5 => T5);
 
 /// build Repeat Zone of Geometry Nodes
+///
+/// `body` may build further zones; they are paired by node name and end up in the same tree.
 pub fn repeat_zone<T, F>(iterations: impl Into<NodeSocket<Int>>, initial_items: T, body: F) -> T
 where
     T: RepeatItems,
@@ -114,6 +190,111 @@ where
     T::create_output(out_name)
 }
 
+// closure zone ======================================================
+/// Inputs or outputs of a closure signature. Unlike repeat items, closure items may be of any
+/// socket type (bundles and closures included).
+pub trait ClosureItems {
+    /// Appends `<node>.<collection>.new(...)` for each item.
+    fn setup_items(node: &str, collection: &str, post_code: &mut String)
+    where
+        Self: Sized;
+    /// Links the items to the inputs of `node` starting at `offset`.
+    fn link_items(&self, node: &str, offset: usize);
+    /// Items read from the outputs of `node` starting at `offset`.
+    fn create_items(node: &str, offset: usize) -> Self
+    where
+        Self: Sized;
+}
+
+impl ClosureItems for () {
+    fn setup_items(_node: &str, _collection: &str, _post_code: &mut String) {}
+    fn link_items(&self, _node: &str, _offset: usize) {}
+    fn create_items(_node: &str, _offset: usize) -> Self {}
+}
+
+fn write_closure_item<T: SocketDef>(node: &str, collection: &str, post_code: &mut String) {
+    let _ = writeln!(
+        post_code,
+        "{}.{}.new('{}', '{}')",
+        node,
+        collection,
+        T::socket_type(),
+        T::default_name()
+    );
+}
+
+macro_rules! impl_closure_items {
+    ( $($idx:tt => $T:ident),+ ) => {
+        impl<$($T: SocketDef),+> ClosureItems for ($(NodeSocket<$T>,)+) {
+            fn setup_items(node: &str, collection: &str, post_code: &mut String) {
+                $( write_closure_item::<$T>(node, collection, post_code); )+
+            }
+            fn link_items(&self, node: &str, offset: usize) {
+                $( self.$idx.link_to(node, $idx + offset); )+
+            }
+            fn create_items(node: &str, offset: usize) -> Self {
+                ( $( NodeSocket::<$T>::from_output(node, $idx + offset), )+ )
+            }
+        }
+    };
+}
+
+impl_closure_items!(0 => T0);
+impl_closure_items!(0 => T0, 1 => T1);
+impl_closure_items!(0 => T0, 1 => T1, 2 => T2);
+impl_closure_items!(0 => T0, 1 => T1, 2 => T2, 3 => T3);
+impl_closure_items!(0 => T0, 1 => T1, 2 => T2, 3 => T3, 4 => T4);
+impl_closure_items!(0 => T0, 1 => T1, 2 => T2, 3 => T3, 4 => T4, 5 => T5);
+
+/// build Closure Zone: `body` maps the closure's inputs `I` to its outputs `O`.
+/// Call the returned closure with `evaluate_closure`.
+pub fn closure_zone<I, O, F>(body: F) -> NodeSocket<Closure>
+where
+    I: ClosureItems,
+    O: ClosureItems,
+    F: FnOnce(I) -> O,
+{
+    let closure_out = NodeClosureOutput::new();
+    let closure_in = NodeClosureInput::new();
+
+    let in_name = &closure_in.name;
+    let out_name = &closure_out.name;
+
+    let mut post_code = String::new();
+    let _ = writeln!(&mut post_code, "{in_name}.pair_with_output({out_name})");
+    let _ = writeln!(&mut post_code, "{out_name}.input_items.clear()");
+    let _ = writeln!(&mut post_code, "{out_name}.output_items.clear()");
+    I::setup_items(out_name, "input_items", &mut post_code);
+    O::setup_items(out_name, "output_items", &mut post_code);
+    update_post_creation(in_name, post_code);
+
+    body(I::create_items(in_name, 0)).link_items(out_name, 0);
+
+    closure_out.out_closure()
+}
+
+/// Calls `closure` with `inputs` (`NodeEvaluateClosure`). `I` and `O` have to match the
+/// signature the closure was built with.
+pub fn evaluate_closure<I, O>(closure: NodeSocket<Closure>, inputs: I) -> O
+where
+    I: ClosureItems,
+    O: ClosureItems,
+{
+    let node = NodeEvaluateClosure::new().with_closure(closure);
+    let name = &node.name;
+
+    let mut post_code = String::new();
+    let _ = writeln!(&mut post_code, "{name}.input_items.clear()");
+    let _ = writeln!(&mut post_code, "{name}.output_items.clear()");
+    I::setup_items(name, "input_items", &mut post_code);
+    O::setup_items(name, "output_items", &mut post_code);
+    update_post_creation(name, post_code);
+
+    // Input 0 is the closure itself.
+    inputs.link_items(name, 1);
+    O::create_items(name, 0)
+}
+
 // ----------------------------------------------------------------------------
 // unittest
 // ----------------------------------------------------------------------------
@@ -123,6 +304,7 @@ mod tests {
     use crate::core::context;
     use crate::core::context::test_utils::GLOBAL_TEST_LOCK;
     use crate::core::types::{Float, Geo, Vector};
+    use ramen_macros::RamenItems;
 
     #[test]
     fn test_repeat_zone_empty_tuple() {
@@ -212,10 +394,76 @@ mod tests {
         assert!(out_f.python_expr().contains(".outputs[1]"));
         assert!(out_v.python_expr().contains(".outputs[2]"));
 
+        assert_three_item_zone(&nodes);
+    }
+
+    #[test]
+    fn test_repeat_zone_nested() {
+        use crate::core::nodes::GeometryNodeSetPosition;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let initial = NodeSocket::<Geo>::new_output("source_geo.outputs[0]");
+        let mut inner_state = String::new();
+        let (result,) = repeat_zone(3, (initial,), |(outer_geo,)| {
+            repeat_zone(4, (outer_geo,), |(inner_geo,)| {
+                inner_state = inner_geo.python_expr();
+                let moved = GeometryNodeSetPosition::new()
+                    .with_geometry(inner_geo)
+                    .out_geometry();
+                (moved,)
+            })
+        });
+
+        // Zones live in the tree that contains them, so nesting adds no scope of its own.
+        let nodes = context::exit_zone();
+        let of_type = |idname: &str| -> Vec<&context::NodeData> {
+            nodes.iter().filter(|n| n.bl_idname == idname).collect()
+        };
+        let (inputs, outputs) = (
+            of_type("GeometryNodeRepeatInput"),
+            of_type("GeometryNodeRepeatOutput"),
+        );
+        assert_eq!((inputs.len(), outputs.len()), (2, 2));
+        let (outer_in, inner_in) = (inputs[0], inputs[1]);
+        let (outer_out, inner_out) = (outputs[0], outputs[1]);
+
+        for (input, output) in [(outer_in, outer_out), (inner_in, inner_out)] {
+            assert!(input.post_creation_script.starts_with(&format!(
+                "{}.pair_with_output({})\n",
+                input.name, output.name
+            )));
+        }
+        assert_eq!(inner_in.inputs[&0][0].expr, "4");
+
+        // outer state -> inner zone -> body -> inner result -> outer result
+        assert!(inner_in.custom_links_script.contains(&format!(
+            "tree.links.new({}.outputs[1], {}.inputs[1])",
+            outer_in.name, inner_in.name
+        )));
+        assert_eq!(inner_state, format!("{}.outputs[1]", inner_in.name));
+        let body = &of_type("GeometryNodeSetPosition")[0];
+        assert_eq!(body.inputs[&0][0].expr, inner_state);
+        assert!(inner_out.custom_links_script.contains(&format!(
+            "{}.outputs[\"Geometry\"], {}.inputs[0])",
+            body.name, inner_out.name
+        )));
+        assert!(outer_out.custom_links_script.contains(&format!(
+            "tree.links.new({}.outputs[0], {}.inputs[0])",
+            inner_out.name, outer_out.name
+        )));
+        assert_eq!(
+            result.python_expr(),
+            format!("{}.outputs[0]", outer_out.name)
+        );
+    }
+
+    /// Checks item registration and link indices of a zone carrying (Geo, Float, Vector).
+    fn assert_three_item_zone(nodes: &[context::NodeData]) {
         let mut in_node_name = String::new();
         let mut out_node_name = String::new();
 
-        for node in &nodes {
+        for node in nodes {
             if node.bl_idname == "GeometryNodeRepeatInput" {
                 in_node_name = node.name.clone();
             } else if node.bl_idname == "GeometryNodeRepeatOutput" {
@@ -274,4 +522,155 @@ mod tests {
                 .contains(&format!("{}.inputs[2]", out_node_name))
         );
     }
+
+    #[derive(RamenItems)]
+    struct State {
+        geo: NodeSocket<Geo>,
+        value: NodeSocket<Float>,
+        dir: NodeSocket<Vector>,
+    }
+
+    #[test]
+    fn test_repeat_zone_derived_struct() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let initial = State {
+            geo: NodeSocket::new_output("source_geo"),
+            value: NodeSocket::new_output("source_float"),
+            dir: NodeSocket::new_output("source_vec"),
+        };
+
+        let out = repeat_zone(10, initial, |s| {
+            assert!(s.geo.python_expr().contains(".outputs[1]"));
+            assert!(s.value.python_expr().contains(".outputs[2]"));
+            assert!(s.dir.python_expr().contains(".outputs[3]"));
+            State {
+                value: s.value + 1.0,
+                ..s
+            }
+        });
+
+        let nodes = context::exit_zone();
+
+        assert!(out.geo.python_expr().contains(".outputs[0]"));
+        assert!(out.value.python_expr().contains(".outputs[1]"));
+        assert!(out.dir.python_expr().contains(".outputs[2]"));
+        assert_three_item_zone(&nodes);
+    }
+
+    #[test]
+    fn test_closure_zone_pairs_and_registers_items() {
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let closure = closure_zone(|(geo, offset): (NodeSocket<Geo>, NodeSocket<Vector>)| {
+            assert!(geo.python_expr().ends_with(".outputs[0]"));
+            assert!(offset.python_expr().ends_with(".outputs[1]"));
+            let moved = crate::core::nodes::GeometryNodeSetPosition::new()
+                .with_geometry(geo)
+                .with_offset(offset)
+                .out_geometry();
+            (moved,)
+        });
+        let geo = NodeSocket::<Geo>::new_output("source_geo.outputs[0]");
+        let offset = NodeSocket::<Vector>::new_output("source_vec.outputs[0]");
+        let (result,): (NodeSocket<Geo>,) = evaluate_closure(closure, (geo, offset));
+
+        let nodes = context::exit_zone();
+        let find = |idname: &str| nodes.iter().find(|n| n.bl_idname == idname).unwrap();
+        let (closure_in, closure_out) = (find("NodeClosureInput"), find("NodeClosureOutput"));
+        let evaluate = find("NodeEvaluateClosure");
+
+        let out = &closure_out.name;
+        assert_eq!(
+            closure_in.post_creation_script,
+            format!(
+                "{}.pair_with_output({out})\n\
+                 {out}.input_items.clear()\n\
+                 {out}.output_items.clear()\n\
+                 {out}.input_items.new('GEOMETRY', 'Geometry')\n\
+                 {out}.input_items.new('VECTOR', 'Vector')\n\
+                 {out}.output_items.new('GEOMETRY', 'Geometry')\n",
+                closure_in.name
+            )
+        );
+        assert!(
+            closure_out
+                .custom_links_script
+                .contains(&format!("{}.inputs[0])", out))
+        );
+        assert_eq!(
+            closure.python_expr(),
+            format!("{}.outputs[\"Closure\"]", out)
+        );
+
+        let eval = &evaluate.name;
+        assert!(
+            evaluate
+                .post_creation_script
+                .contains(&format!("{eval}.input_items.new('VECTOR', 'Vector')"))
+        );
+        assert_eq!(evaluate.inputs[&0][0].expr, closure.python_expr());
+        assert!(evaluate.custom_links_script.contains(&format!(
+            "tree.links.new(source_geo.outputs[0], {eval}.inputs[1])"
+        )));
+        assert!(evaluate.custom_links_script.contains(&format!(
+            "tree.links.new(source_vec.outputs[0], {eval}.inputs[2])"
+        )));
+        assert_eq!(result.python_expr(), format!("{eval}.outputs[0]"));
+    }
+
+    #[test]
+    fn test_zone_item_type_mapping() {
+        use crate::core::types::{
+            Bool, Bundle, Collection, Color, Image, Material, Matrix, Menu, Object, Rotation,
+            Shader, StringType, Vector2D, Vector4D,
+        };
+
+        assert_eq!(zone_item_type::<Float>(), Ok("FLOAT"));
+        assert_eq!(zone_item_type::<Int>(), Ok("INT"));
+        assert_eq!(zone_item_type::<Bool>(), Ok("BOOLEAN"));
+        assert_eq!(zone_item_type::<Vector>(), Ok("VECTOR"));
+        assert_eq!(zone_item_type::<Color>(), Ok("RGBA"));
+        assert_eq!(zone_item_type::<Rotation>(), Ok("ROTATION"));
+        assert_eq!(zone_item_type::<Matrix>(), Ok("MATRIX"));
+        assert_eq!(zone_item_type::<StringType>(), Ok("STRING"));
+        assert_eq!(zone_item_type::<Geo>(), Ok("GEOMETRY"));
+        assert_eq!(zone_item_type::<Object>(), Ok("OBJECT"));
+        assert_eq!(zone_item_type::<Collection>(), Ok("COLLECTION"));
+        assert_eq!(zone_item_type::<Material>(), Ok("MATERIAL"));
+        assert_eq!(zone_item_type::<Image>(), Ok("IMAGE"));
+
+        for err in [
+            zone_item_type::<Vector2D>(),
+            zone_item_type::<Vector4D>(),
+            zone_item_type::<Menu>(),
+            zone_item_type::<Bundle>(),
+            zone_item_type::<Shader>(),
+        ] {
+            assert!(err.is_err());
+        }
+        assert_eq!(
+            zone_item_type::<Menu>().unwrap_err().to_string(),
+            "socket type 'MENU' cannot be used as a zone item (repeat/simulation/bake)"
+        );
+    }
+
+    #[test]
+    fn test_repeat_zone_rejects_unsupported_item() {
+        use crate::core::types::Vector2D;
+        let _lock = GLOBAL_TEST_LOCK.lock().unwrap();
+        context::enter_zone();
+
+        let result = std::panic::catch_unwind(|| {
+            let uv = NodeSocket::<Vector2D>::from((0.0, 0.0));
+            let _ = repeat_zone(3, (uv,), |(uv,)| (uv,));
+        });
+        let _ = context::exit_zone();
+
+        let err = result.unwrap_err();
+        let msg = err.downcast_ref::<String>().unwrap();
+        assert!(msg.contains("'VECTOR2D' cannot be used as a zone item"));
+    }
 }
@@ -1 +1,5 @@
+// Lets macro-generated `blender_ramen::...` paths resolve inside this crate (e.g. in unit tests).
+extern crate self as blender_ramen;
+
 pub mod core;
+pub mod prelude;
@@ -0,0 +1,113 @@
+//! # Prelude
+//!
+//! `use blender_ramen::prelude::*;` brings in what a typical build script needs:
+//! socket types, tree/project builders, group helpers, zones, `helpers` and the macros.
+//! Generated node structs are not included; import them from `core::nodes` as needed.
+
+pub use crate::core::helpers::*;
+pub use crate::core::live_link::{SendEvent, send_to_blender};
+pub use crate::core::ops::{math_add, math_binary, math_div, math_mul, math_sub};
+pub use crate::core::project::BlenderProject;
+pub use crate::core::snapshot::SendSnapshot;
+pub use crate::core::tree::{
+    InputConfig, NodeTree, call_compositor_group, call_external_compositor_group,
+    call_external_group, call_external_shader_group, call_geometry_group, call_shader_group,
+    generate_script_header, generate_script_header_with,
+};
+pub use crate::core::types::{
+    Any, Bool, Bundle, Collection, Color, CompositorNodeGroupExt, Float, Geo, GeometryNodeGroupExt,
+    Image, Int, Material, Matrix, Menu, NodeGroupInputExt, NodeSocket, Object, Rotation, Shader,
+    ShaderNodeGroupExt, SocketDef, StringType, Vector, Vector2D,
+};
+pub use crate::core::zone::repeat_zone;
+pub use ramen_macros::{RamenItems, group_sockets, ramen_math, ramen_vec};
+
+// ---------------------------------------------------------
+// unittest
+// ---------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use blender_ramen::prelude::*;
+
+    fn socket_type_of<T: SocketDef>() -> &'static str {
+        T::blender_socket_type()
+    }
+
+    #[test]
+    fn test_prelude_provides_listed_items() {
+        let markers = [
+            socket_type_of::<Float>(),
+            socket_type_of::<Vector>(),
+            socket_type_of::<Int>(),
+            socket_type_of::<Bool>(),
+            socket_type_of::<Color>(),
+            socket_type_of::<Geo>(),
+            socket_type_of::<Vector2D>(),
+            socket_type_of::<Rotation>(),
+            socket_type_of::<Material>(),
+            socket_type_of::<Object>(),
+            socket_type_of::<Collection>(),
+            socket_type_of::<Image>(),
+            socket_type_of::<Shader>(),
+            socket_type_of::<StringType>(),
+            socket_type_of::<Matrix>(),
+            socket_type_of::<Bundle>(),
+            socket_type_of::<Menu>(),
+        ];
+        assert!(markers.iter().all(|t| t.starts_with("NodeSocket")));
+        let _: Option<NodeSocket<Any>> = None;
+
+        // Builders, group calls, zones and the live link, referenced by path only.
+        let _ = NodeTree::new_geometry_group;
+        let _ = BlenderProject::new;
+        let _ = call_geometry_group;
+        let _ = call_shader_group;
+        let _ = call_external_group;
+        let _ = call_external_shader_group;
+        let _ = call_compositor_group;
+        let _ = call_external_compositor_group;
+        let _ = send_to_blender;
+        assert_eq!(generate_script_header(), "import bpy\n");
+
+        fn _uses_repeat_zone(x: NodeSocket<Float>) -> NodeSocket<Float> {
+            let (x,) = repeat_zone(3, (x,), |v| v);
+            x
+        }
+
+        // Extension traits are in scope.
+        fn _uses_ext_traits(
+            group_in: &crate::core::nodes::NodeGroupInput,
+            geo_group: &crate::core::nodes::GeometryNodeGroup,
+            shader_group: &crate::core::nodes::ShaderNodeGroup,
+            compositor_group: &crate::core::nodes::CompositorNodeGroup,
+        ) {
+            let _: NodeSocket<Float> = group_in.socket("X");
+            let _: NodeSocket<Geo> = geo_group.out_socket("Geometry");
+            let _: NodeSocket<Shader> = shader_group.out_socket("Shader");
+            let _: NodeSocket<Color> = compositor_group.out_socket("Image");
+        }
+
+        // helpers.rs functions.
+        let _ = project_to_plane;
+        let _ = reflect;
+        let _ = face_forward;
+        let _ = attribute_statistic::<Float>;
+        let _ = multilayer_output;
+        let _ = curve_length;
+        let _ = spline_length;
+        let _ = spline_parameter;
+        let _ = join_geometry_vec;
+        let _ = select_mask;
+        let _ = instance_on_edges;
+        let _ = instance_on_curve_points;
+        fn _uses_ramen_vec(a: NodeSocket<Vector>, b: NodeSocket<Vector>) {
+            let _: NodeSocket<Vector> = ramen_vec!(normalize(-a + b) % b);
+            let _: NodeSocket<Float> = ramen_vec!(dot(a, cross(a, b)));
+            let _: NodeSocket<Vector> = ramen_vec!(refract(a, b, 1.45));
+        }
+        fn _uses_generic_helpers(v: NodeSocket<Vector>, f: NodeSocket<Float>) {
+            let _ = refract(v, v, f);
+            let _ = threshold(f, 0.5);
+        }
+    }
+}
@@ -69,3 +69,15 @@ def test_dumped_json_schema(dumped_json_file):
     assert len(operation_prop["enum_items"]) > 0
     assert "identifier" in operation_prop["enum_items"][0]
     assert "name" in operation_prop["enum_items"][0]
+
+
+def test_menu_socket_items(dumped_json_file):
+    with open(dumped_json_file, "r", encoding="utf-8") as f:
+        data = json.load(f)
+
+    # Menu items only exist on node instances; the crate's menu enums are built from these.
+    resample = data["GeometryNodes"]["GeometryNodeResampleCurve"]
+    mode = next(s for s in resample["inputs"] if s["identifier"] == "Mode")
+    assert mode["type"] == "NodeSocketMenu"
+    identifiers = [item["identifier"] for item in mode.get("enum_items", [])]
+    assert {"Count", "Length", "Evaluated"} <= set(identifiers)